@@ -0,0 +1,204 @@
+use super::{LinkProcessor, Note};
+use std::collections::{HashMap, HashSet};
+
+/// A single `[[link]]` pointing at a note, with the surrounding text.
+#[derive(Debug, Clone)]
+pub struct Backlink {
+    pub source_note_id: String,
+    pub link_text: String,
+    pub start: usize,
+    pub end: usize,
+    pub context: String,
+}
+
+/// Incrementally maintained index of incoming wiki links.
+///
+/// Mentions are stored under the lowercased link target rather than a note id,
+/// so a note picks up links to its title or any of its aliases, and renaming a
+/// note or adding an alias resolves existing links without rescanning the vault.
+pub struct BacklinkIndex {
+    link_processor: LinkProcessor,
+    mentions: HashMap<String, Vec<Backlink>>, // lowercase target -> mentions
+    outgoing: HashMap<String, HashSet<String>>, // source note id -> lowercase targets
+    names: HashMap<String, Vec<String>>, // lowercase title or alias -> note ids, in the order they took the name
+    note_names: HashMap<String, Vec<String>>, // note id -> lowercase title and aliases
+}
+
+const CONTEXT_RADIUS: usize = 60;
+
+impl BacklinkIndex {
+    pub fn new() -> Self {
+        Self {
+            link_processor: LinkProcessor::new(),
+            mentions: HashMap::new(),
+            outgoing: HashMap::new(),
+            names: HashMap::new(),
+            note_names: HashMap::new(),
+        }
+    }
+    
+    pub fn rebuild<'a>(&mut self, notes: impl IntoIterator<Item = &'a Note>) {
+        self.mentions.clear();
+        self.outgoing.clear();
+        self.names.clear();
+        self.note_names.clear();
+        
+        for note in notes {
+            self.update_note(note);
+        }
+    }
+    
+    /// Re-indexes a single note's title, aliases and outgoing links.
+    pub fn update_note(&mut self, note: &Note) {
+        if note.is_deleted {
+            self.remove_note(&note.id);
+            return;
+        }
+        self.remove_links(&note.id);
+        
+        // Register the names this note can be linked by; names it already
+        // had keep their place among notes sharing them
+        let mut names = vec![note.title.trim().to_lowercase()];
+        names.extend(note.aliases().iter().map(|alias| alias.to_lowercase()));
+        let mut seen = HashSet::new();
+        names.retain(|name| !name.is_empty() && seen.insert(name.clone()));
+        
+        let previous = self.note_names.remove(&note.id).unwrap_or_default();
+        for name in previous.iter().filter(|name| !names.contains(name)) {
+            self.release_name(name, &note.id);
+        }
+        for name in names.iter().filter(|name| !previous.contains(name)) {
+            self.names.entry(name.clone()).or_default().push(note.id.clone());
+        }
+        self.note_names.insert(note.id.clone(), names);
+        
+        // Record every outgoing link under its target name
+        let mut targets = HashSet::new();
+        for link in self.link_processor.extract_wiki_links(&note.content) {
+            let target = link.target().to_lowercase();
+            if target.is_empty() {
+                continue;
+            }
+            
+            self.mentions.entry(target.clone()).or_default().push(Backlink {
+                source_note_id: note.id.clone(),
+                link_text: link.text.clone(),
+                start: link.start,
+                end: link.end,
+                context: context_snippet(&note.content, link.start, link.end, CONTEXT_RADIUS),
+            });
+            targets.insert(target);
+        }
+        self.outgoing.insert(note.id.clone(), targets);
+    }
+    
+    pub fn remove_note(&mut self, note_id: &str) {
+        self.remove_links(note_id);
+        for name in self.note_names.remove(note_id).unwrap_or_default() {
+            self.release_name(&name, note_id);
+        }
+    }
+    
+    fn remove_links(&mut self, note_id: &str) {
+        if let Some(targets) = self.outgoing.remove(note_id) {
+            for target in targets {
+                if let Some(mentions) = self.mentions.get_mut(&target) {
+                    mentions.retain(|m| m.source_note_id != note_id);
+                    if mentions.is_empty() {
+                        self.mentions.remove(&target);
+                    }
+                }
+            }
+        }
+    }
+    
+    /// Another note sharing the name now resolves in its place.
+    fn release_name(&mut self, name: &str, note_id: &str) {
+        if let Some(ids) = self.names.get_mut(name) {
+            ids.retain(|id| id != note_id);
+            if ids.is_empty() {
+                self.names.remove(name);
+            }
+        }
+    }
+    
+    /// Resolves a link target (title or alias, any case) to a note id. When
+    /// notes share a name, the one that had it first wins.
+    pub fn resolve(&self, target: &str) -> Option<&str> {
+        self.names.get(&target.trim().to_lowercase()).and_then(|ids| ids.first()).map(String::as_str)
+    }
+    
+    /// The lowercased title and aliases the note can be linked by.
+    pub fn names(&self, note_id: &str) -> &[String] {
+        self.note_names.get(note_id).map_or(&[], Vec::as_slice)
+    }
+    
+    /// All links from other notes that resolve to `note_id`.
+    pub fn backlinks(&self, note_id: &str) -> Vec<&Backlink> {
+        let names = match self.note_names.get(note_id) {
+            Some(names) => names,
+            None => return Vec::new(),
+        };
+        
+        let mut result: Vec<&Backlink> = names
+            .iter()
+            .filter(|name| self.resolve(name) == Some(note_id))
+            .filter_map(|name| self.mentions.get(name))
+            .flatten()
+            .filter(|m| m.source_note_id != note_id)
+            .collect();
+        
+        result.sort_by(|a, b| a.source_note_id.cmp(&b.source_note_id).then(a.start.cmp(&b.start)));
+        result
+    }
+    
+    /// Ids of the notes linking to `note_id`, without duplicates.
+    pub fn backlink_sources(&self, note_id: &str) -> Vec<String> {
+        let mut sources: Vec<String> = self
+            .backlinks(note_id)
+            .into_iter()
+            .map(|m| m.source_note_id.clone())
+            .collect();
+        sources.dedup();
+        sources
+    }
+    
+    /// Snapshot of the whole index as `note id -> linking note ids`.
+    pub fn to_map(&self) -> HashMap<String, Vec<String>> {
+        self.note_names
+            .keys()
+            .map(|note_id| (note_id.clone(), self.backlink_sources(note_id)))
+            .filter(|(_, sources)| !sources.is_empty())
+            .collect()
+    }
+}
+
+/// Text around `start..end` on the same line, trimmed to `radius` bytes per side.
+pub fn context_snippet(content: &str, start: usize, end: usize, radius: usize) -> String {
+    let line_start = content[..start].rfind('\n').map(|pos| pos + 1).unwrap_or(0);
+    let line_end = content[end..].find('\n').map(|pos| end + pos).unwrap_or(content.len());
+    
+    let mut from = start.saturating_sub(radius).max(line_start);
+    while !content.is_char_boundary(from) {
+        from += 1;
+    }
+    let mut to = (end + radius).min(line_end);
+    while !content.is_char_boundary(to) {
+        to -= 1;
+    }
+    
+    let mut snippet = content[from..to].trim().to_string();
+    if from > line_start {
+        snippet = format!("...{}", snippet);
+    }
+    if to < line_end {
+        snippet = format!("{}...", snippet);
+    }
+    snippet
+}
+
+impl Default for BacklinkIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,264 @@
+use regex::Regex;
+
+/// A kanban board read from a markdown note: every heading is a column and
+/// every top-level list item under it is a card. Text that is neither is
+/// kept in place so the note round-trips unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Board {
+    preamble: Vec<String>, // Frontmatter and anything before the first column
+    pub columns: Vec<BoardColumn>,
+    column_level: usize,
+    missing_final_newline: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardColumn {
+    pub title: String,
+    heading: String,
+    items: Vec<ColumnItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ColumnItem {
+    Card(BoardCard),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardCard {
+    pub text: String,         // Item text without the list marker or checkbox
+    pub done: Option<bool>,   // Set for `- [ ]` / `- [x]` cards
+    pub link: Option<String>, // Target when the card is just a `[[Note]]`
+    lines: Vec<String>,       // Source lines, including nested items
+}
+
+impl Board {
+    pub fn parse(content: &str) -> Self {
+        let heading_regex = Regex::new(r"^(#{1,6})\s+(.*?)\s*#*\s*$").unwrap();
+        let item_regex = Regex::new(r"^(?:[-*+]|\d+[.)])\s+(?:\[([ xX])\]\s+)?(.*)$").unwrap();
+        
+        let mut lines: Vec<String> = content.split_inclusive('\n').map(String::from).collect();
+        let missing_final_newline = lines.last().is_some_and(|line| !line.ends_with('\n'));
+        if let Some(last) = lines.last_mut().filter(|_| missing_final_newline) {
+            last.push('\n');
+        }
+        
+        // Columns are `##` headings when the note has any, so a `# Title`
+        // above them stays part of the preamble
+        let mut levels = Vec::new();
+        let mut in_code_block = false;
+        for line in &lines {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+            } else if !in_code_block {
+                if let Some(cap) = heading_regex.captures(line.trim_end()) {
+                    levels.push(cap[1].len());
+                }
+            }
+        }
+        let column_level = if levels.contains(&2) { 2 } else { levels.iter().copied().min().unwrap_or(2) };
+        
+        let mut board = Board {
+            preamble: Vec::new(),
+            columns: Vec::new(),
+            column_level,
+            missing_final_newline,
+        };
+        let mut in_code_block = false;
+        
+        for line in lines {
+            let trimmed = line.trim_end();
+            let is_fence = trimmed.trim_start().starts_with("```");
+            
+            if !in_code_block && !is_fence {
+                if let Some(cap) = heading_regex.captures(trimmed) {
+                    if cap[1].len() == column_level {
+                        board.columns.push(BoardColumn {
+                            title: cap[2].to_string(),
+                            heading: line.clone(),
+                            items: Vec::new(),
+                        });
+                        continue;
+                    }
+                }
+            }
+            
+            let column = match board.columns.last_mut() {
+                Some(column) => column,
+                None => {
+                    board.preamble.push(line);
+                    if is_fence {
+                        in_code_block = !in_code_block;
+                    }
+                    continue;
+                }
+            };
+            
+            // Indented lines belong to the card above them
+            let continues_card = !trimmed.is_empty() && line.starts_with([' ', '\t']);
+            if in_code_block || continues_card {
+                if let Some(ColumnItem::Card(card)) = column.items.last_mut().filter(|_| continues_card) {
+                    card.lines.push(line);
+                } else {
+                    column.items.push(ColumnItem::Text(line));
+                }
+            } else if let Some(cap) = item_regex.captures(trimmed).filter(|_| !is_fence) {
+                column.items.push(ColumnItem::Card(BoardCard::from_parts(
+                    cap.get(1).map(|mark| mark.as_str() != " "),
+                    cap[2].to_string(),
+                    line,
+                )));
+            } else {
+                column.items.push(ColumnItem::Text(line));
+            }
+            
+            if is_fence {
+                in_code_block = !in_code_block;
+            }
+        }
+        
+        board
+    }
+    
+    pub fn to_markdown(&self) -> String {
+        let mut markdown: String = self.preamble.concat();
+        for column in &self.columns {
+            markdown.push_str(&column.heading);
+            for item in &column.items {
+                match item {
+                    ColumnItem::Card(card) => markdown.push_str(&card.lines.concat()),
+                    ColumnItem::Text(line) => markdown.push_str(line),
+                }
+            }
+        }
+        if self.missing_final_newline && markdown.ends_with('\n') {
+            markdown.pop();
+        }
+        markdown
+    }
+    
+    /// Moves a card so it sits before the `to_index`-th card of `to_column`,
+    /// or after its last card when `to_index` is past the end.
+    pub fn move_card(&mut self, from_column: usize, card: usize, to_column: usize, to_index: usize) -> bool {
+        if to_column >= self.columns.len() {
+            return false;
+        }
+        let card = match self.columns.get_mut(from_column).and_then(|column| column.take_card(card)) {
+            Some(card) => card,
+            None => return false,
+        };
+        self.columns[to_column].insert_card(to_index, card);
+        true
+    }
+    
+    pub fn add_card(&mut self, column: usize, text: &str) -> bool {
+        let text = text.trim();
+        if text.is_empty() || column >= self.columns.len() {
+            return false;
+        }
+        let line = format!("- {}\n", text);
+        let card = BoardCard::from_parts(None, text.to_string(), line);
+        let index = self.columns[column].cards().len();
+        self.columns[column].insert_card(index, card);
+        true
+    }
+    
+    pub fn add_column(&mut self, title: &str) -> bool {
+        let title = title.trim();
+        if title.is_empty() {
+            return false;
+        }
+        
+        // Keep a blank line between the previous column and the new heading
+        let needs_gap = match self.columns.last() {
+            Some(column) => !matches!(column.items.last(), Some(ColumnItem::Text(line)) if line.trim().is_empty()),
+            None => self.preamble.last().is_some_and(|line| !line.trim().is_empty()),
+        };
+        if needs_gap {
+            match self.columns.last_mut() {
+                Some(column) => column.items.push(ColumnItem::Text("\n".to_string())),
+                None => self.preamble.push("\n".to_string()),
+            }
+        }
+        
+        self.columns.push(BoardColumn {
+            title: title.to_string(),
+            heading: format!("{} {}\n", "#".repeat(self.column_level), title),
+            items: vec![ColumnItem::Text("\n".to_string())],
+        });
+        true
+    }
+}
+
+impl BoardColumn {
+    pub fn cards(&self) -> Vec<&BoardCard> {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                ColumnItem::Card(card) => Some(card),
+                ColumnItem::Text(_) => None,
+            })
+            .collect()
+    }
+    
+    fn card_position(&self, card: usize) -> Option<usize> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| matches!(item, ColumnItem::Card(_)))
+            .nth(card)
+            .map(|(position, _)| position)
+    }
+    
+    fn take_card(&mut self, card: usize) -> Option<BoardCard> {
+        let position = self.card_position(card)?;
+        match self.items.remove(position) {
+            ColumnItem::Card(card) => Some(card),
+            ColumnItem::Text(_) => None,
+        }
+    }
+    
+    fn insert_card(&mut self, index: usize, card: BoardCard) {
+        if self.items.is_empty() {
+            self.items.push(ColumnItem::Text("\n".to_string()));
+        }
+        
+        let position = self.card_position(index).unwrap_or_else(|| {
+            // After the last card, or after any text that opens an empty column
+            let last_card = self.items.iter().rposition(|item| matches!(item, ColumnItem::Card(_)));
+            let last_text = self.items.iter().rposition(|item| matches!(item, ColumnItem::Text(line) if !line.trim().is_empty()));
+            last_card.or(last_text).map_or(0, |position| position + 1)
+        });
+        
+        // An empty column reads better with the card separated from the heading
+        if position == 0 && matches!(self.items.first(), Some(ColumnItem::Text(line)) if line.trim().is_empty()) {
+            self.items.insert(1, ColumnItem::Card(card));
+        } else {
+            self.items.insert(position, ColumnItem::Card(card));
+        }
+    }
+}
+
+impl BoardCard {
+    fn from_parts(done: Option<bool>, text: String, line: String) -> Self {
+        let link = text
+            .trim()
+            .strip_prefix("[[")
+            .and_then(|rest| rest.strip_suffix("]]"))
+            .filter(|inner| !inner.contains("]]"))
+            .and_then(|inner| inner.split(['|', '#']).next())
+            .map(|target| target.trim().to_string())
+            .filter(|target| !target.is_empty());
+        Self {
+            text,
+            done,
+            link,
+            lines: vec![line],
+        }
+    }
+    
+    /// Nested lines under the card, such as sub-items or notes.
+    pub fn details(&self) -> Vec<&str> {
+        self.lines[1..].iter().map(|line| line.trim()).filter(|line| !line.is_empty()).collect()
+    }
+}
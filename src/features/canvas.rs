@@ -0,0 +1,324 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+pub const CANVAS_EXTENSION: &str = "canvas";
+
+/// A whiteboard stored in the open JSON Canvas format (jsoncanvas.org), so
+/// `.canvas` files in the vault stay readable by other tools.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Canvas {
+    #[serde(default)]
+    pub nodes: Vec<CanvasNode>,
+    #[serde(default)]
+    pub edges: Vec<CanvasEdge>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>, // Fields from other editors, written back untouched
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RawCanvasNode")]
+pub struct CanvasNode {
+    pub id: String,
+    #[serde(flatten)]
+    pub kind: CanvasNodeKind,
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>, // Preset "1"-"6" or a hex color
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `CanvasNode` as read from disk. Both flattened fields see every key the
+/// node doesn't name itself, so the ones `kind` used are dropped from `extra`.
+#[derive(Deserialize)]
+struct RawCanvasNode {
+    id: String,
+    #[serde(flatten)]
+    kind: CanvasNodeKind,
+    x: i64,
+    y: i64,
+    width: i64,
+    height: i64,
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+impl From<RawCanvasNode> for CanvasNode {
+    fn from(raw: RawCanvasNode) -> Self {
+        let mut extra = raw.extra;
+        extra.retain(|key, _| !raw.kind.keys().contains(&key.as_str()));
+        Self {
+            id: raw.id,
+            kind: raw.kind,
+            x: raw.x,
+            y: raw.y,
+            width: raw.width,
+            height: raw.height,
+            color: raw.color,
+            extra,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CanvasNodeKind {
+    Text {
+        text: String,
+    },
+    /// A note or other vault file, by path relative to the vault.
+    File {
+        file: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subpath: Option<String>,
+    },
+    Link {
+        url: String,
+    },
+    Group {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanvasEdge {
+    pub id: String,
+    pub from_node: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_side: Option<CanvasSide>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_end: Option<CanvasEdgeEnd>,
+    pub to_node: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_side: Option<CanvasSide>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_end: Option<CanvasEdgeEnd>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CanvasSide {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CanvasEdgeEnd {
+    None,
+    Arrow,
+}
+
+impl CanvasNodeKind {
+    /// JSON keys this kind reads, including the `type` tag.
+    fn keys(&self) -> &'static [&'static str] {
+        match self {
+            CanvasNodeKind::Text { .. } => &["type", "text"],
+            CanvasNodeKind::File { .. } => &["type", "file", "subpath"],
+            CanvasNodeKind::Link { .. } => &["type", "url"],
+            CanvasNodeKind::Group { .. } => &["type", "label"],
+        }
+    }
+}
+
+impl CanvasNode {
+    /// The note title for `.md` file nodes, taken from the file name.
+    pub fn note_title(&self) -> Option<&str> {
+        match &self.kind {
+            CanvasNodeKind::File { file, .. } => {
+                let name = file.rsplit('/').next().unwrap_or(file);
+                name.strip_suffix(".md")
+            }
+            _ => None,
+        }
+    }
+    
+    /// Resolves a `.md` file node to a note id. The whole path is tried as a
+    /// title first, since titles may contain `/`, then just the file name,
+    /// for vault paths written by other editors.
+    pub fn resolve_note(&self, resolve: impl Fn(&str) -> Option<String>) -> Option<String> {
+        let path_title = match &self.kind {
+            CanvasNodeKind::File { file, .. } => file.strip_suffix(".md"),
+            _ => None,
+        };
+        path_title.and_then(&resolve).or_else(|| self.note_title().and_then(&resolve))
+    }
+    
+    pub fn is_image(&self) -> bool {
+        match &self.kind {
+            CanvasNodeKind::File { file, .. } => matches!(
+                crate::utils::get_file_extension(file).map(|ext| ext.to_lowercase()).as_deref(),
+                Some("png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp")
+            ),
+            _ => false,
+        }
+    }
+    
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x as f32 && x <= (self.x + self.width) as f32 && y >= self.y as f32 && y <= (self.y + self.height) as f32
+    }
+    
+    pub fn center(&self) -> (f32, f32) {
+        (self.x as f32 + self.width as f32 / 2.0, self.y as f32 + self.height as f32 / 2.0)
+    }
+}
+
+impl CanvasEdge {
+    /// Edges without explicit ends draw an arrow at the target only.
+    pub fn to_arrow(&self) -> bool {
+        self.to_end.unwrap_or(CanvasEdgeEnd::Arrow) == CanvasEdgeEnd::Arrow
+    }
+    
+    pub fn from_arrow(&self) -> bool {
+        self.from_end.unwrap_or(CanvasEdgeEnd::None) == CanvasEdgeEnd::Arrow
+    }
+}
+
+impl Canvas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn from_json(json: &str) -> Result<Self> {
+        // An empty file is a valid, empty canvas
+        if json.trim().is_empty() {
+            return Ok(Self::new());
+        }
+        Ok(serde_json::from_str(json)?)
+    }
+    
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+    
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_json(&crate::utils::read_file_to_string(path)?)
+    }
+    
+    pub fn save(&self, path: &Path) -> Result<()> {
+        crate::utils::write_string_to_file(path, &self.to_json()?)
+    }
+    
+    pub fn node(&self, id: &str) -> Option<&CanvasNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+    
+    pub fn node_mut(&mut self, id: &str) -> Option<&mut CanvasNode> {
+        self.nodes.iter_mut().find(|node| node.id == id)
+    }
+    
+    /// Adds a node with its top-left corner at the given position and returns its id.
+    pub fn add_node(&mut self, kind: CanvasNodeKind, x: i64, y: i64) -> String {
+        let (width, height) = match &kind {
+            CanvasNodeKind::Text { .. } => (250, 60),
+            CanvasNodeKind::File { .. } => (400, 300),
+            CanvasNodeKind::Link { .. } => (400, 120),
+            CanvasNodeKind::Group { .. } => (600, 400),
+        };
+        let id = new_id();
+        self.nodes.push(CanvasNode {
+            id: id.clone(),
+            kind,
+            x,
+            y,
+            width,
+            height,
+            color: None,
+            extra: Map::new(),
+        });
+        id
+    }
+    
+    /// Connects two nodes with an arrow. Returns `None` if either node is
+    /// missing or they are already connected in that direction.
+    pub fn connect(&mut self, from: &str, to: &str) -> Option<String> {
+        if from == to || self.node(from).is_none() || self.node(to).is_none() {
+            return None;
+        }
+        if self.edges.iter().any(|edge| edge.from_node == from && edge.to_node == to) {
+            return None;
+        }
+        
+        let id = new_id();
+        self.edges.push(CanvasEdge {
+            id: id.clone(),
+            from_node: from.to_string(),
+            from_side: None,
+            from_end: None,
+            to_node: to.to_string(),
+            to_side: None,
+            to_end: None,
+            color: None,
+            label: None,
+            extra: Map::new(),
+        });
+        Some(id)
+    }
+    
+    /// Removes a node together with every edge touching it.
+    pub fn remove_node(&mut self, id: &str) -> bool {
+        let before = self.nodes.len();
+        self.nodes.retain(|node| node.id != id);
+        self.edges.retain(|edge| edge.from_node != id && edge.to_node != id);
+        self.nodes.len() != before
+    }
+    
+    pub fn remove_edge(&mut self, id: &str) -> bool {
+        let before = self.edges.len();
+        self.edges.retain(|edge| edge.id != id);
+        self.edges.len() != before
+    }
+    
+    /// Topmost node under a canvas position. Later nodes draw on top, and
+    /// groups sit behind everything else.
+    pub fn node_at(&self, x: f32, y: f32) -> Option<&CanvasNode> {
+        self.nodes
+            .iter()
+            .rev()
+            .filter(|node| node.contains(x, y))
+            .min_by_key(|node| matches!(node.kind, CanvasNodeKind::Group { .. }))
+    }
+}
+
+/// Vault path of a note, as used by file nodes. The title is kept as is so
+/// the node resolves back to the same note.
+pub fn note_file_path(title: &str) -> String {
+    format!("{}.md", title)
+}
+
+/// `.canvas` files in a directory, sorted by name.
+pub fn list_canvases(dir: &Path) -> Vec<PathBuf> {
+    let mut canvases: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == CANVAS_EXTENSION))
+                .collect()
+        })
+        .unwrap_or_default();
+    canvases.sort();
+    canvases
+}
+
+/// JSON Canvas ids are free-form; 16 hex digits matches what other editors write.
+fn new_id() -> String {
+    Uuid::new_v4().simple().to_string()[..16].to_string()
+}
@@ -0,0 +1,116 @@
+/// One line of a line-by-line comparison of two texts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String), // Only in the old text
+    Added(String),   // Only in the new text
+}
+
+/// Compares two texts line by line, keeping as many lines in common as it
+/// can. Uses Myers' algorithm in its linear-space form, so memory grows with
+/// the length of the texts rather than their product.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let mut diff = Vec::with_capacity(old.len().max(new.len()));
+    diff_range(&old, &new, &mut diff);
+    diff
+}
+
+fn diff_range(old: &[&str], new: &[&str], diff: &mut Vec<DiffLine>) {
+    // Lines shared at both ends are never part of an edit
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+    
+    diff.extend(old[..prefix].iter().map(|line| DiffLine::Same(line.to_string())));
+    match middle_snake(old_middle, new_middle) {
+        Some((x, y)) => {
+            diff_range(&old_middle[..x], &new_middle[..y], diff);
+            diff_range(&old_middle[x..], &new_middle[y..], diff);
+        }
+        None => {
+            diff.extend(old_middle.iter().map(|line| DiffLine::Removed(line.to_string())));
+            diff.extend(new_middle.iter().map(|line| DiffLine::Added(line.to_string())));
+        }
+    }
+    diff.extend(old[old.len() - suffix..].iter().map(|line| DiffLine::Same(line.to_string())));
+}
+
+/// Where a shortest edit script from `old` to `new` crosses its middle,
+/// found by searching from both ends at once until the paths overlap.
+/// `None` when there is no point splitting: one side is empty or every line
+/// differs.
+fn middle_snake(old: &[&str], new: &[&str]) -> Option<(usize, usize)> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    if n == 0 || m == 0 {
+        return None;
+    }
+    let max_d = (n + m + 1) / 2;
+    let offset = max_d;
+    let width = 2 * max_d + 2;
+    // Furthest x reached on each diagonal k = x - y, from the start and from the end
+    let mut forward = vec![-1isize; width as usize];
+    let mut reverse = vec![-1isize; width as usize];
+    forward[(offset + 1) as usize] = 0;
+    reverse[(offset + 1) as usize] = 0;
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    // Diagonals that ran off the edge are skipped from then on
+    let (mut forward_start, mut forward_end, mut reverse_start, mut reverse_end) = (0, 0, 0, 0);
+    let split = |x: isize, y: isize| (x > 0 || y > 0) && (x < n || y < m);
+    
+    for d in 0..max_d {
+        let mut k = -d + forward_start;
+        while k <= d - forward_end {
+            let i = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && forward[i - 1] < forward[i + 1]) { forward[i + 1] } else { forward[i - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[i] = x;
+            if x > n {
+                forward_end += 2;
+            } else if y > m {
+                forward_start += 2;
+            } else if odd {
+                let j = offset + delta - k;
+                if (0..width).contains(&j) && reverse[j as usize] != -1 && x >= n - reverse[j as usize] {
+                    return split(x, y).then_some((x as usize, y as usize));
+                }
+            }
+            k += 2;
+        }
+        
+        let mut k = -d + reverse_start;
+        while k <= d - reverse_end {
+            let i = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && reverse[i - 1] < reverse[i + 1]) { reverse[i + 1] } else { reverse[i - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && old[(n - x - 1) as usize] == new[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            reverse[i] = x;
+            if x > n {
+                reverse_end += 2;
+            } else if y > m {
+                reverse_start += 2;
+            } else if !odd {
+                let j = offset + delta - k;
+                if (0..width).contains(&j) && forward[j as usize] != -1 {
+                    let forward_x = forward[j as usize];
+                    let forward_y = offset + forward_x - j;
+                    if forward_x >= n - x {
+                        return split(forward_x, forward_y).then_some((forward_x as usize, forward_y as usize));
+                    }
+                }
+            }
+            k += 2;
+        }
+    }
+    None
+}
@@ -0,0 +1,341 @@
+use petgraph::stable_graph::{NodeIndex, StableDiGraph};
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use petgraph::Direction;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use super::{LinkKind, NoteLink};
+
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_TOLERANCE: f64 = 1e-6;
+const PAGERANK_MAX_ITERATIONS: usize = 100;
+
+/// Directed graph of resolved links between notes, kept in sync with
+/// `note_links` so the graph view and sidebar can query it without
+/// touching the database.
+pub struct LinkGraph {
+    graph: StableDiGraph<String, LinkEdge>,
+    nodes: HashMap<String, NodeIndex>,
+    revision: u64,
+}
+
+/// The links from one note to another, collapsed into a single edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkEdge {
+    pub kind: LinkKind,
+    pub relation: Option<String>, // A typed relation wins over plain links between the same notes
+    pub label: Option<String>,    // The relation, or the link text when it differs from the target title
+}
+
+impl LinkEdge {
+    fn from_link(link: &NoteLink) -> Self {
+        let text = link.display_text();
+        let label = if link.relation.is_some() {
+            link.relation.clone()
+        } else if text.eq_ignore_ascii_case(&link.target_title) {
+            None
+        } else {
+            Some(text.to_string())
+        };
+        Self {
+            kind: link.kind,
+            relation: link.relation.clone(),
+            label,
+        }
+    }
+}
+
+impl LinkGraph {
+    pub fn new() -> Self {
+        Self {
+            graph: StableDiGraph::new(),
+            nodes: HashMap::new(),
+            revision: 0,
+        }
+    }
+    
+    /// Rebuilds the graph from scratch. Links whose source or target isn't
+    /// among `note_ids` are ignored.
+    pub fn rebuild<'a>(&mut self, note_ids: impl IntoIterator<Item = &'a str>, links: &[NoteLink]) {
+        self.graph.clear();
+        self.nodes.clear();
+        
+        for note_id in note_ids {
+            self.add_note(note_id);
+        }
+        for link in links {
+            self.add_link(link);
+        }
+        self.revision += 1;
+    }
+    
+    pub fn add_note(&mut self, note_id: &str) {
+        if !self.nodes.contains_key(note_id) {
+            let index = self.graph.add_node(note_id.to_string());
+            self.nodes.insert(note_id.to_string(), index);
+            self.revision += 1;
+        }
+    }
+    
+    pub fn remove_note(&mut self, note_id: &str) {
+        if let Some(index) = self.nodes.remove(note_id) {
+            self.graph.remove_node(index);
+            self.revision += 1;
+        }
+    }
+    
+    /// Replaces the note's outgoing edges with the given links. The revision
+    /// only changes if the edges do, so saving a note without touching its
+    /// links doesn't invalidate anything.
+    pub fn set_links(&mut self, note_id: &str, links: &[NoteLink]) {
+        self.add_note(note_id);
+        let index = self.nodes[note_id];
+        let revision = self.revision;
+        let before = self.outgoing_edges(note_id).into_iter().map(|(to, edge)| (to.to_string(), edge.clone())).collect::<Vec<_>>();
+        
+        let outgoing: Vec<_> = self.graph.edges_directed(index, Direction::Outgoing).map(|e| e.id()).collect();
+        for edge in outgoing {
+            self.graph.remove_edge(edge);
+        }
+        for link in links.iter().filter(|link| link.from_note_id == note_id) {
+            self.add_link(link);
+        }
+        
+        let after = self.outgoing_edges(note_id).into_iter().map(|(to, edge)| (to.to_string(), edge.clone())).collect::<Vec<_>>();
+        self.revision = if after == before { revision } else { revision + 1 };
+    }
+    
+    /// Adds a single edge; repeated links between the same pair of notes
+    /// collapse into one edge.
+    pub fn add_link(&mut self, link: &NoteLink) {
+        let to_note_id = match &link.to_note_id {
+            Some(id) => id,
+            None => return,
+        };
+        
+        if let (Some(&from), Some(&to)) = (self.nodes.get(&link.from_note_id), self.nodes.get(to_note_id)) {
+            match self.graph.find_edge(from, to) {
+                None => {
+                    self.graph.add_edge(from, to, LinkEdge::from_link(link));
+                    self.revision += 1;
+                }
+                Some(existing) if self.graph[existing].relation.is_none() && link.relation.is_some() => {
+                    self.graph[existing] = LinkEdge::from_link(link);
+                    self.revision += 1;
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    
+    /// Bumped on every change, so callers can cache derived metrics.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+    
+    pub fn contains(&self, note_id: &str) -> bool {
+        self.nodes.contains_key(note_id)
+    }
+    
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+    
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+    
+    /// Ids of the notes this note links to.
+    pub fn outgoing(&self, note_id: &str) -> Vec<&str> {
+        self.neighbors(note_id, Direction::Outgoing)
+    }
+    
+    /// Ids of the notes linking to this note.
+    pub fn incoming(&self, note_id: &str) -> Vec<&str> {
+        self.neighbors(note_id, Direction::Incoming)
+    }
+    
+    /// Every edge as `(from, to, kind)`.
+    pub fn edges(&self) -> Vec<(&str, &str, LinkKind)> {
+        self.graph
+            .edge_references()
+            .map(|edge| (self.graph[edge.source()].as_str(), self.graph[edge.target()].as_str(), edge.weight().kind))
+            .collect()
+    }
+    
+    /// The note's outgoing edges with the notes they point at, in target order.
+    pub fn outgoing_edges(&self, note_id: &str) -> Vec<(&str, &LinkEdge)> {
+        let mut edges: Vec<(&str, &LinkEdge)> = match self.nodes.get(note_id) {
+            Some(&index) => self.graph
+                .edges_directed(index, Direction::Outgoing)
+                .map(|edge| (self.graph[edge.target()].as_str(), edge.weight()))
+                .collect(),
+            None => Vec::new(),
+        };
+        edges.sort_by(|a, b| a.0.cmp(b.0));
+        edges
+    }
+    
+    /// Notes with no links in or out, ignoring links to themselves.
+    pub fn orphans(&self) -> Vec<&str> {
+        let mut orphans: Vec<&str> = self.graph
+            .node_indices()
+            .filter(|&index| self.graph.neighbors_undirected(index).all(|other| other == index))
+            .map(|index| self.graph[index].as_str())
+            .collect();
+        orphans.sort();
+        orphans
+    }
+    
+    /// PageRank score per note. Scores sum to 1; notes without outgoing
+    /// links spread their rank evenly over the whole vault.
+    pub fn pagerank(&self) -> HashMap<String, f64> {
+        let indices: Vec<NodeIndex> = self.graph.node_indices().collect();
+        let count = indices.len();
+        if count == 0 {
+            return HashMap::new();
+        }
+        
+        let position: HashMap<NodeIndex, usize> = indices.iter().enumerate().map(|(i, &index)| (index, i)).collect();
+        let out_degree: Vec<usize> = indices
+            .iter()
+            .map(|&index| self.graph.neighbors_directed(index, Direction::Outgoing).count())
+            .collect();
+        
+        let base = (1.0 - PAGERANK_DAMPING) / count as f64;
+        let mut ranks = vec![1.0 / count as f64; count];
+        
+        for _ in 0..PAGERANK_MAX_ITERATIONS {
+            let dangling: f64 = (0..count).filter(|&i| out_degree[i] == 0).map(|i| ranks[i]).sum();
+            let mut next = vec![base + PAGERANK_DAMPING * dangling / count as f64; count];
+            
+            for edge in self.graph.edge_references() {
+                let from = position[&edge.source()];
+                let to = position[&edge.target()];
+                next[to] += PAGERANK_DAMPING * ranks[from] / out_degree[from] as f64;
+            }
+            
+            let delta: f64 = ranks.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+            ranks = next;
+            if delta < PAGERANK_TOLERANCE {
+                break;
+            }
+        }
+        
+        indices
+            .iter()
+            .zip(ranks)
+            .map(|(&index, rank)| (self.graph[index].clone(), rank))
+            .collect()
+    }
+    
+    /// Groups of notes connected by links in either direction, largest first.
+    pub fn components(&self) -> Vec<Vec<String>> {
+        let mut seen = HashSet::new();
+        let mut components = Vec::new();
+        
+        for start in self.graph.node_indices() {
+            if !seen.insert(start) {
+                continue;
+            }
+            
+            let mut component = Vec::new();
+            let mut queue = VecDeque::from([start]);
+            while let Some(index) = queue.pop_front() {
+                component.push(self.graph[index].clone());
+                for other in self.graph.neighbors_undirected(index) {
+                    if seen.insert(other) {
+                        queue.push_back(other);
+                    }
+                }
+            }
+            
+            component.sort();
+            components.push(component);
+        }
+        
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        components
+    }
+    
+    /// Fewest-hops route between two notes, following links in either
+    /// direction. Includes both endpoints.
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let start = *self.nodes.get(from)?;
+        let goal = *self.nodes.get(to)?;
+        
+        let mut previous: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        let mut seen = HashSet::from([start]);
+        
+        while let Some(index) = queue.pop_front() {
+            if index == goal {
+                let mut path = vec![self.graph[goal].clone()];
+                let mut current = goal;
+                while let Some(&prev) = previous.get(&current) {
+                    path.push(self.graph[prev].clone());
+                    current = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            
+            for other in self.graph.neighbors_undirected(index) {
+                if seen.insert(other) {
+                    previous.insert(other, index);
+                    queue.push_back(other);
+                }
+            }
+        }
+        
+        None
+    }
+    
+    /// Notes within `hops` links of the given note (in either direction),
+    /// with their distance. The note itself is included at distance 0.
+    pub fn neighborhood(&self, note_id: &str, hops: usize) -> Vec<(String, usize)> {
+        let start = match self.nodes.get(note_id) {
+            Some(&index) => index,
+            None => return Vec::new(),
+        };
+        
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+        
+        while let Some(index) = queue.pop_front() {
+            let distance = distances[&index];
+            if distance == hops {
+                continue;
+            }
+            for other in self.graph.neighbors_undirected(index) {
+                if let Entry::Vacant(entry) = distances.entry(other) {
+                    entry.insert(distance + 1);
+                    queue.push_back(other);
+                }
+            }
+        }
+        
+        let mut result: Vec<(String, usize)> = distances
+            .into_iter()
+            .map(|(index, distance)| (self.graph[index].clone(), distance))
+            .collect();
+        result.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        result
+    }
+    
+    fn neighbors(&self, note_id: &str, direction: Direction) -> Vec<&str> {
+        match self.nodes.get(note_id) {
+            Some(&index) => self.graph
+                .neighbors_directed(index, direction)
+                .filter(|&other| other != index)
+                .map(|other| self.graph[other].as_str())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Default for LinkGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,9 +1,13 @@
+use aho_corasick::{AhoCorasick, MatchKind};
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use super::{BacklinkIndex, LinkKind, Note, NoteLink};
+use super::backlinks::context_snippet;
 
 pub struct LinkProcessor {
     wiki_link_regex: Regex,
     markdown_link_regex: Regex,
+    relation_regex: Regex,
 }
 
 impl LinkProcessor {
@@ -11,6 +15,8 @@ impl LinkProcessor {
         Self {
             wiki_link_regex: Regex::new(r"\[\[([^\]]+)\]\]").unwrap(),
             markdown_link_regex: Regex::new(r"\[([^\]]+)\]\(([^)]+)\)").unwrap(),
+            // `blocked-by:: [[Task]]`, optionally as a list item
+            relation_regex: Regex::new(r"(?m)^[ \t]*(?:[-*+][ \t]+)?([A-Za-z][\w-]*)::[ \t]*(.*)$").unwrap(),
         }
     }
     
@@ -70,29 +76,139 @@ impl LinkProcessor {
         result
     }
     
-    pub fn create_backlinks_map(&self, notes: &[(String, String)]) -> HashMap<String, Vec<String>> {
-        let mut backlinks: HashMap<String, Vec<String>> = HashMap::new();
+    /// Maps each note id to the ids of notes linking to it, resolving link
+    /// targets against titles and aliases case-insensitively.
+    pub fn create_backlinks_map(&self, notes: &[Note]) -> HashMap<String, Vec<String>> {
+        let mut index = BacklinkIndex::new();
+        index.rebuild(notes);
+        index.to_map()
+    }
+    
+    /// Rewrites every `[[old_title]]` reference to point at `new_title`,
+    /// keeping any `#heading` suffix and `|alias` intact. Returns the updated
+    /// content and the number of links that were rewritten.
+    pub fn rewrite_wiki_links(&self, content: &str, old_title: &str, new_title: &str) -> (String, usize) {
+        let mut result = content.to_string();
+        let mut rewritten = 0;
+        let old_title = old_title.trim().to_lowercase();
+        
+        // Process links in reverse order to maintain correct positions
+        for link in self.extract_wiki_links(content).iter().rev() {
+            if link.target().to_lowercase() != old_title {
+                continue;
+            }
+            
+            let replacement = format!("[[{}{}]]", new_title.trim(), link.suffix());
+            result.replace_range(link.start..link.end, &replacement);
+            rewritten += 1;
+        }
         
-        for (note_id, content) in notes {
-            let wiki_links = self.extract_wiki_links(content);
+        (result, rewritten)
+    }
+    
+    /// Rewrites the stored links that point at `old_title` using their
+    /// recorded byte ranges, so markdown links and embeds are renamed too.
+    /// Returns `None` if the content has changed since the links were stored.
+    pub fn rewrite_link_ranges(&self, content: &str, links: &[NoteLink], old_title: &str, new_title: &str) -> Option<(String, usize)> {
+        let mut result = content.to_string();
+        let mut rewritten = 0;
+        let old_title = old_title.trim().to_lowercase();
+        let new_title = new_title.trim();
+        
+        let mut links: Vec<&NoteLink> = links.iter().collect();
+        links.sort_by_key(|link| std::cmp::Reverse(link.start));
+        
+        for link in links {
+            if content.get(link.start..link.end) != Some(link.link_text.as_str()) {
+                return None;
+            }
+            if link.target_title.to_lowercase() != old_title {
+                continue;
+            }
             
-            for link in wiki_links {
-                // Find notes that match this link text
-                for (other_note_id, _) in notes {
-                    if other_note_id != note_id {
-                        // This is simplified - in practice you'd look up by title
-                        if link.text.to_lowercase().contains(&other_note_id.to_lowercase()) {
-                            backlinks
-                                .entry(other_note_id.clone())
-                                .or_insert_with(Vec::new)
-                                .push(note_id.clone());
-                        }
-                    }
+            let replacement = match link.kind {
+                LinkKind::Wiki | LinkKind::Embed => {
+                    let wiki = self.extract_wiki_links(&link.link_text).into_iter().next()?;
+                    let bang = if link.kind == LinkKind::Embed { "!" } else { "" };
+                    format!("{}[[{}{}]]", bang, new_title, wiki.suffix())
                 }
+                LinkKind::Markdown => {
+                    let markdown = self.extract_markdown_links(&link.link_text).into_iter().next()?;
+                    format!("[{}]({})", markdown.text, rename_markdown_target(&markdown.url, new_title))
+                }
+            };
+            
+            result.replace_range(link.start..link.end, &replacement);
+            rewritten += 1;
+        }
+        
+        Some((result, rewritten))
+    }
+    
+    /// Every link to another note in `content`, in document order: wiki
+    /// links, `![[embeds]]` and relative markdown links. Targets are left
+    /// unresolved.
+    pub fn extract_note_links(&self, from_note_id: &str, content: &str) -> Vec<NoteLink> {
+        let mut found: Vec<(LinkKind, String, usize, usize)> = Vec::new();
+        
+        for link in self.extract_wiki_links(content) {
+            if link.target().is_empty() {
+                continue;
             }
+            let (kind, start) = if content[..link.start].ends_with('!') {
+                (LinkKind::Embed, link.start - 1)
+            } else {
+                (LinkKind::Wiki, link.start)
+            };
+            found.push((kind, link.target().to_string(), start, link.end));
         }
         
-        backlinks
+        for link in self.extract_markdown_links(content) {
+            let overlaps_wiki = found.iter().any(|(_, _, start, end)| link.start < *end && *start < link.end);
+            if overlaps_wiki || content[..link.start].ends_with('!') {
+                continue;
+            }
+            if let Some(target) = markdown_link_target(&link.url) {
+                found.push((LinkKind::Markdown, target, link.start, link.end));
+            }
+        }
+        
+        found.sort_by_key(|(_, _, start, _)| *start);
+        
+        // Links inside a `relation:: value` field are typed by the field name
+        let relations: Vec<(String, usize, usize)> = self.relation_regex
+            .captures_iter(content)
+            .filter_map(|cap| {
+                let (name, value) = (cap.get(1)?, cap.get(2)?);
+                Some((name.as_str().to_lowercase(), value.start(), value.end()))
+            })
+            .collect();
+        
+        let mut line = 1;
+        let mut line_counted_to = 0;
+        found
+            .into_iter()
+            .enumerate()
+            .map(|(position, (kind, target_title, start, end))| {
+                line += content[line_counted_to..start].matches('\n').count();
+                line_counted_to = start;
+                let relation = relations.iter()
+                    .find(|(_, value_start, value_end)| *value_start <= start && end <= *value_end)
+                    .map(|(name, _, _)| name.clone());
+                NoteLink {
+                    from_note_id: from_note_id.to_string(),
+                    to_note_id: None,
+                    target_title,
+                    link_text: content[start..end].to_string(),
+                    position,
+                    start,
+                    end,
+                    line,
+                    kind,
+                    relation,
+                }
+            })
+            .collect()
     }
     
     pub fn highlight_links_in_content(&self, content: &str) -> String {
@@ -116,30 +232,34 @@ impl LinkProcessor {
     
     pub fn suggest_links(&self, content: &str, available_notes: &[String]) -> Vec<LinkSuggestion> {
         let mut suggestions = Vec::new();
-        let words: Vec<&str> = content.split_whitespace().collect();
         
+        // Exact title matches, found in a single pass over the content
+        if let Some(matcher) = TitleMatcher::new(available_notes) {
+            let linked = self.linked_ranges(content);
+            for found in matcher.find_unlinked(content, &linked) {
+                let note_title = &available_notes[found.pattern];
+                suggestions.push(LinkSuggestion {
+                    text: note_title.clone(),
+                    suggested_link: format!("[[{}]]", note_title),
+                    confidence: 1.0,
+                    reason: "Exact title match".to_string(),
+                });
+            }
+        }
+        
+        // Partial matches on longer words shared with a title
+        let mut titles_by_word: HashMap<String, Vec<&String>> = HashMap::new();
         for note_title in available_notes {
-            let title_words: Vec<&str> = note_title.split_whitespace().collect();
-            
-            // Look for exact title matches
-            for window in words.windows(title_words.len()) {
-                if window.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>() == 
-                   title_words.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>() {
-                    
-                    suggestions.push(LinkSuggestion {
-                        text: note_title.clone(),
-                        suggested_link: format!("[[{}]]", note_title),
-                        confidence: 1.0,
-                        reason: "Exact title match".to_string(),
-                    });
-                }
+            for word in note_title.split_whitespace().filter(|w| w.len() > 3) {
+                titles_by_word.entry(word.to_lowercase()).or_default().push(note_title);
             }
-            
-            // Look for partial matches
-            for word in &words {
-                if note_title.to_lowercase().contains(&word.to_lowercase()) && word.len() > 3 {
+        }
+        
+        for word in content.split_whitespace().filter(|w| w.len() > 3) {
+            if let Some(titles) = titles_by_word.get(&word.to_lowercase()) {
+                for note_title in titles {
                     suggestions.push(LinkSuggestion {
-                        text: note_title.clone(),
+                        text: (*note_title).clone(),
                         suggested_link: format!("[[{}]]", note_title),
                         confidence: 0.5,
                         reason: format!("Contains word: {}", word),
@@ -148,12 +268,156 @@ impl LinkProcessor {
             }
         }
         
-        // Remove duplicates and sort by confidence
+        // Remove duplicates, keeping the most confident suggestion per note
         suggestions.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
-        suggestions.dedup_by(|a, b| a.text == b.text);
+        let mut seen = HashSet::new();
+        suggestions.retain(|s| seen.insert(s.text.clone()));
         
         suggestions
     }
+    
+    /// Finds plain-text mentions of `target`'s title or aliases in other notes
+    /// that are not already inside a link.
+    pub fn find_unlinked_mentions(&self, target: &Note, notes: &[&Note]) -> Vec<UnlinkedMention> {
+        let mut names = vec![target.title.clone()];
+        names.extend(target.aliases());
+        
+        let matcher = match TitleMatcher::new(&names) {
+            Some(matcher) => matcher,
+            None => return Vec::new(),
+        };
+        
+        let mut mentions = Vec::new();
+        for note in notes {
+            if note.id == target.id || note.is_deleted {
+                continue;
+            }
+            
+            let linked = self.linked_ranges(&note.content);
+            for found in matcher.find_unlinked(&note.content, &linked) {
+                mentions.push(UnlinkedMention {
+                    source_note_id: note.id.clone(),
+                    start: found.start,
+                    end: found.end,
+                    matched_text: note.content[found.start..found.end].to_string(),
+                    context: context_snippet(&note.content, found.start, found.end, 60),
+                });
+            }
+        }
+        
+        mentions
+    }
+    
+    /// Turns an unlinked mention into a `[[link]]` in place. Returns `None` if
+    /// the content changed since the mention was found.
+    pub fn link_mention(&self, content: &str, mention: &UnlinkedMention, target_title: &str) -> Option<String> {
+        if content.get(mention.start..mention.end) != Some(mention.matched_text.as_str()) {
+            return None;
+        }
+        
+        let link = if mention.matched_text == target_title {
+            format!("[[{}]]", target_title)
+        } else {
+            format!("[[{}|{}]]", target_title, mention.matched_text)
+        };
+        
+        let mut result = content.to_string();
+        result.replace_range(mention.start..mention.end, &link);
+        Some(result)
+    }
+    
+    /// Byte ranges covered by wiki or markdown links.
+    fn linked_ranges(&self, content: &str) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> = self.wiki_link_regex
+            .find_iter(content)
+            .chain(self.markdown_link_regex.find_iter(content))
+            .map(|m| (m.start(), m.end()))
+            .collect();
+        ranges.sort();
+        ranges
+    }
+}
+
+/// Case-insensitive multi-pattern matcher over note titles and aliases.
+pub struct TitleMatcher {
+    automaton: AhoCorasick,
+}
+
+#[derive(Debug, Clone)]
+pub struct TitleMatch {
+    pub pattern: usize, // index into the names the matcher was built from
+    pub start: usize,
+    pub end: usize,
+}
+
+impl TitleMatcher {
+    pub fn new(names: &[String]) -> Option<Self> {
+        let patterns: Vec<String> = names.iter().map(|name| name.trim().to_lowercase()).collect();
+        if patterns.iter().all(|p| p.is_empty()) {
+            return None;
+        }
+        
+        // Prefer "Project X Plan" over "Project X" when both match
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(&patterns)
+            .ok()?;
+        
+        Some(Self { automaton })
+    }
+    
+    /// Whole-word matches in `content` outside the given (sorted) ranges.
+    pub fn find_unlinked(&self, content: &str, excluded: &[(usize, usize)]) -> Vec<TitleMatch> {
+        let folded = FoldedText::new(content);
+        let mut matches = Vec::new();
+        
+        for m in self.automaton.find_iter(&folded.text) {
+            if m.start() == m.end() {
+                continue;
+            }
+            
+            let start = folded.offsets[m.start()];
+            let end = folded.offsets[m.end()];
+            
+            let before = content[..start].chars().next_back();
+            let after = content[end..].chars().next();
+            if before.is_some_and(char::is_alphanumeric) || after.is_some_and(char::is_alphanumeric) {
+                continue;
+            }
+            
+            if excluded.iter().any(|&(from, to)| start < to && end > from) {
+                continue;
+            }
+            
+            matches.push(TitleMatch { pattern: m.pattern().as_usize(), start, end });
+        }
+        
+        matches
+    }
+}
+
+/// Lowercased copy of a text that remembers where each byte came from, since
+/// lowercasing can change the byte length of some characters.
+struct FoldedText {
+    text: String,
+    offsets: Vec<usize>,
+}
+
+impl FoldedText {
+    fn new(content: &str) -> Self {
+        let mut text = String::with_capacity(content.len());
+        let mut offsets = Vec::with_capacity(content.len() + 1);
+        
+        for (pos, ch) in content.char_indices() {
+            for lower in ch.to_lowercase() {
+                text.push(lower);
+                offsets.extend(std::iter::repeat_n(pos, lower.len_utf8()));
+            }
+        }
+        offsets.push(content.len());
+        
+        Self { text, offsets }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -164,6 +428,76 @@ pub struct WikiLink {
     pub full_match: String,
 }
 
+impl WikiLink {
+    /// The note title this link points at, without heading or alias.
+    pub fn target(&self) -> &str {
+        let end = self.text.find(['#', '|']).unwrap_or(self.text.len());
+        self.text[..end].trim()
+    }
+    
+    /// The `#heading` part of `[[Note#Heading|Alias]]`, if any.
+    pub fn heading(&self) -> Option<&str> {
+        let start = self.text.find('#')?;
+        let end = self.text.find('|').unwrap_or(self.text.len());
+        if start < end {
+            Some(self.text[start + 1..end].trim())
+        } else {
+            None
+        }
+    }
+    
+    /// The `|alias` part of `[[Note|Alias]]`, if any.
+    pub fn alias(&self) -> Option<&str> {
+        self.text.find('|').map(|pos| self.text[pos + 1..].trim())
+    }
+    
+    /// Everything after the target, e.g. `#Heading|Alias`, exactly as written.
+    pub fn suffix(&self) -> &str {
+        let start = self.text.find(['#', '|']).unwrap_or(self.text.len());
+        &self.text[start..]
+    }
+}
+
+/// The note title a relative markdown link such as `notes/Project%20X.md#plan`
+/// points at, or `None` for external URLs, anchors and non-note files.
+fn markdown_link_target(url: &str) -> Option<String> {
+    let url = url.trim();
+    if url.is_empty() || url.starts_with('#') || url.contains("://") || url.starts_with("mailto:") {
+        return None;
+    }
+    
+    let path = url.split('#').next().unwrap_or(url);
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let title = match file_name.strip_suffix(".md") {
+        Some(stem) => stem,
+        None if !file_name.contains('.') => file_name,
+        None => return None,
+    };
+    
+    let title = title.replace("%20", " ");
+    if title.trim().is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+/// Swaps the file name in a markdown link URL for `new_title`, keeping the
+/// directory, extension and `#fragment`.
+fn rename_markdown_target(url: &str, new_title: &str) -> String {
+    let (path, fragment) = match url.find('#') {
+        Some(pos) => url.split_at(pos),
+        None => (url, ""),
+    };
+    let (dir, file_name) = match path.rfind('/') {
+        Some(pos) => path.split_at(pos + 1),
+        None => ("", path),
+    };
+    let extension = if file_name.ends_with(".md") { ".md" } else { "" };
+    
+    format!("{}{}{}{}", dir, new_title.replace(' ', "%20"), extension, fragment)
+}
+
 #[derive(Debug, Clone)]
 pub struct MarkdownLink {
     pub text: String,
@@ -173,6 +507,15 @@ pub struct MarkdownLink {
     pub full_match: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct UnlinkedMention {
+    pub source_note_id: String,
+    pub start: usize,
+    pub end: usize,
+    pub matched_text: String,
+    pub context: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct LinkSuggestion {
     pub text: String,
@@ -1,17 +1,35 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 use crate::storage::Database;
+use crate::utils::MarkdownProcessor;
 
 pub mod note_manager;
 pub mod linking;
+pub mod backlinks;
+pub mod graph;
+pub mod tasks;
+pub mod reminders;
+pub mod query_blocks;
+pub mod board;
+pub mod canvas;
 pub mod search;
+pub mod diff;
 pub mod collaboration;
 
-pub use note_manager::NoteManager;
-pub use linking::LinkProcessor;
-pub use search::SearchEngine;
+pub use note_manager::{NoteManager, RenamePreview, LinkRewrite, RenameUndo, BrokenLink};
+pub use linking::{LinkProcessor, TitleMatcher, UnlinkedMention};
+pub use backlinks::{Backlink, BacklinkIndex};
+pub use graph::{LinkEdge, LinkGraph};
+pub use tasks::{Task, TaskPriority, TaskFilter, TaskStatusFilter, TaskDueFilter, TaskIndex, toggle_task_in_content};
+pub use reminders::{Reminder, ReminderSource, Recurrence, ReminderNotification, ReminderScheduler, derive_reminders, parse_reminder_time};
+pub use query_blocks::{QueryBlock, QueryFormat, QueryResult, QueryRow, SortOrder, FenceLine, FenceScanner, find_query_blocks, expand_query_blocks};
+pub use board::{Board, BoardColumn, BoardCard};
+pub use canvas::{Canvas, CanvasNode, CanvasNodeKind, CanvasEdge, CanvasSide, CanvasEdgeEnd, CANVAS_EXTENSION, list_canvases, note_file_path};
+pub use search::{SearchEngine, SearchFilter, SearchResult, MatchType, MatchField, MatchSpan, Snippet, IndexLoadReport, Facet, FacetCount, FacetCounts, FacetedResults, HistoryIndex, HistoryResult, HistoryScope, HistorySource, SearchWorker, SearchUpdate, EngineWriteGuard, Language, Token, Tokenizer, TokenizerConfig, EmbeddingModel, HashedEmbedding, cosine_similarity, SavedQuery, SavedSearch, SearchQuery, SearchField, CompareOp, FieldValue, QueryParseError, QueryPlan};
+pub use diff::{DiffLine, diff_lines};
 pub use collaboration::{CollaborationManager, CollaborativeEdit, CollaborationSession, User};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,14 +94,95 @@ impl Note {
         self.is_deleted = false;
         self.modified_at = Utc::now();
     }
+    
+    /// Frontmatter properties declared at the top of the note content.
+    pub fn properties(&self) -> HashMap<String, String> {
+        MarkdownProcessor::new()
+            .extract_frontmatter(&self.content)
+            .unwrap_or_default()
+    }
+    
+    /// Alternative titles from an `aliases: [A, B]` property.
+    pub fn aliases(&self) -> Vec<String> {
+        self.properties()
+            .get("aliases")
+            .map(|value| parse_list_property(value))
+            .unwrap_or_default()
+    }
+}
+
+/// Parses a frontmatter list value such as `[one, "two"]` or `one, two`.
+pub fn parse_list_property(value: &str) -> Vec<String> {
+    value
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|item| item.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkKind {
+    Wiki,     // [[Note]]
+    Markdown, // [text](Note.md)
+    Embed,    // ![[Note]]
+}
+
+impl LinkKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkKind::Wiki => "wiki",
+            LinkKind::Markdown => "markdown",
+            LinkKind::Embed => "embed",
+        }
+    }
+    
+    /// Reads back a name from `as_str`; anything else is a wiki link.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "markdown" => LinkKind::Markdown,
+            "embed" => LinkKind::Embed,
+            _ => LinkKind::Wiki,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NoteLink {
     pub from_note_id: String,
-    pub to_note_id: String,
-    pub link_text: String,
-    pub position: usize,
+    pub to_note_id: Option<String>, // None for links to notes that don't exist yet
+    pub target_title: String,
+    pub link_text: String,  // The link exactly as written, e.g. `[[Note|alias]]`
+    pub position: usize,    // Ordinal of the link within the source note
+    pub start: usize,       // Byte range of `link_text` in the source content
+    pub end: usize,
+    pub line: usize,        // 1-based line number of `start`
+    pub kind: LinkKind,
+    pub relation: Option<String>, // Lowercased field name for `parent:: [[Note]]` style links
+}
+
+impl NoteLink {
+    pub fn is_resolved(&self) -> bool {
+        self.to_note_id.is_some()
+    }
+    
+    /// The text a reader sees for this link: the alias or markdown label,
+    /// falling back to the target title.
+    pub fn display_text(&self) -> &str {
+        let inner = match self.kind {
+            LinkKind::Markdown => self.link_text
+                .strip_prefix('[')
+                .and_then(|rest| rest.split("](").next()),
+            LinkKind::Wiki | LinkKind::Embed => self.link_text
+                .trim_start_matches('!')
+                .strip_prefix("[[")
+                .and_then(|rest| rest.strip_suffix("]]"))
+                .and_then(|rest| rest.split_once('|').map(|(_, alias)| alias.trim())),
+        };
+        inner.filter(|text| !text.is_empty()).unwrap_or(&self.target_title)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
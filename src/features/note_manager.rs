@@ -1,21 +1,79 @@
-use super::{Note, NoteLink, NoteVersion, Folder};
+use super::{Note, NoteLink, NoteVersion, Folder, LinkProcessor, LinkGraph, Backlink, BacklinkIndex, UnlinkedMention, Task, TaskFilter, TaskIndex, toggle_task_in_content, Reminder, Recurrence, ReminderNotification, ReminderScheduler, derive_reminders, SearchEngine, SearchResult, SearchUpdate, EngineWriteGuard, HistoryResult, HistoryIndex, HistoryScope, DiffLine, diff_lines, SearchWorker, TokenizerConfig, Facet, SavedQuery, SavedSearch, QueryBlock, QueryResult, find_query_blocks, expand_query_blocks, Board};
 use crate::storage::Database;
-use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
+use std::collections::{HashMap, HashSet};
 use anyhow::Result;
 
 pub struct NoteManager {
     db: Arc<Mutex<Database>>,
     notes_cache: HashMap<String, Note>,
     folders_cache: HashMap<String, Folder>,
+    link_processor: LinkProcessor,
+    backlink_index: BacklinkIndex,
+    link_graph: LinkGraph,
+    task_index: TaskIndex,
+    reminders: ReminderScheduler,
+    search_engine: Arc<RwLock<SearchEngine>>, // Shared with the search worker
+    search_worker: SearchWorker,
+    saved_searches: Vec<SavedSearch>,
+    broken_links: Option<Vec<BrokenLink>>, // Report cache, dropped whenever stored links change
+    history_index: Option<((u64, usize, HistoryScope), HistoryIndex)>, // Keyed by (search revision, version count, scope)
+}
+
+/// A pending note rename together with the link rewrites it would cause.
+#[derive(Debug, Clone)]
+pub struct RenamePreview {
+    pub note_id: String,
+    pub old_title: String,
+    pub new_title: String,
+    pub rewrites: Vec<LinkRewrite>,
+}
+
+/// The effect of a rename on a single note that links to the renamed note.
+#[derive(Debug, Clone)]
+pub struct LinkRewrite {
+    pub note_id: String,
+    pub note_title: String,
+    pub link_count: usize,
+    pub original_content: String,
+    pub updated_content: String,
+}
+
+/// A link target that doesn't exist, with the notes referencing it.
+#[derive(Debug, Clone)]
+pub struct BrokenLink {
+    pub target_title: String,
+    pub sources: Vec<(String, String)>, // (note_id, note_title)
+    pub reference_count: usize,
+}
+
+/// Everything needed to revert an applied rename.
+#[derive(Debug, Clone)]
+pub struct RenameUndo {
+    pub note_id: String,
+    pub old_title: String,
+    pub new_title: String,
+    pub original_contents: Vec<(String, String)>, // note_id -> content before rewrite
 }
 
 impl NoteManager {
     pub fn new(db: Arc<Mutex<Database>>) -> Self {
+        let search_engine = Arc::new(RwLock::new(SearchEngine::new()));
+        let search_worker = SearchWorker::spawn(search_engine.clone(), super::search::DEFAULT_DEBOUNCE);
         let mut manager = Self {
             db,
             notes_cache: HashMap::new(),
             folders_cache: HashMap::new(),
+            link_processor: LinkProcessor::new(),
+            backlink_index: BacklinkIndex::new(),
+            link_graph: LinkGraph::new(),
+            task_index: TaskIndex::new(),
+            reminders: ReminderScheduler::new(),
+            search_engine,
+            search_worker,
+            saved_searches: Vec::new(),
+            broken_links: None,
+            history_index: None,
         };
         
         // Load initial data
@@ -26,10 +84,45 @@ impl NoteManager {
         manager
     }
     
+    fn search_engine(&self) -> RwLockReadGuard<'_, SearchEngine> {
+        self.search_engine.read().unwrap_or_else(|e| e.into_inner())
+    }
+    
+    fn search_engine_mut(&self) -> EngineWriteGuard<'_> {
+        // A background search holds the read lock until a writer shows up
+        self.search_worker.write()
+    }
+    
     pub fn refresh_cache(&mut self) -> Result<()> {
+        let mut links = Vec::new();
         if let Ok(db) = self.db.lock() {
             self.notes_cache = db.get_all_notes()?;
             self.folders_cache = db.get_all_folders()?;
+            links = db.get_resolved_links()?;
+            self.reminders.load(db.get_all_reminders()?);
+            self.saved_searches = db.get_saved_searches()?;
+        }
+        self.backlink_index.rebuild(self.notes_cache.values());
+        self.task_index.rebuild(self.notes_cache.values());
+        self.broken_links = None;
+        
+        let notes: Arc<[Note]> = self.notes_cache.values().cloned().collect();
+        if let Some(path) = embeddings_path() {
+            self.search_engine_mut().load_vectors(&path, Arc::clone(&notes));
+        }
+        match search_index_path() {
+            Some(path) => self.search_engine_mut().load_index(&path, notes),
+            None => self.search_engine_mut().update_index(&notes),
+        }
+        self.search_engine_mut().set_folders(self.folders_cache.values());
+        
+        let note_ids = self.notes_cache.values().filter(|note| !note.is_deleted).map(|note| note.id.as_str());
+        self.link_graph.rebuild(note_ids, &links);
+        
+        // Notes may have been edited outside the app since reminders were stored
+        let note_ids: Vec<String> = self.notes_cache.keys().cloned().collect();
+        for note_id in note_ids {
+            self.sync_reminders(&note_id)?;
         }
         Ok(())
     }
@@ -47,7 +140,14 @@ impl NoteManager {
         }
         
         let note_id = note.id.clone();
+        self.backlink_index.update_note(&note);
+        self.link_graph.add_note(&note_id);
+        self.search_engine_mut().update_note(&note);
         self.notes_cache.insert(note_id.clone(), note);
+        let names = self.backlink_index.names(&note_id).to_vec();
+        if let Err(e) = self.resolve_dangling_links(&names) {
+            log::error!("Failed to re-resolve links: {}", e);
+        }
         note_id
     }
     
@@ -60,11 +160,133 @@ impl NoteManager {
     }
     
     pub fn save_note(&mut self, note_id: &str) -> Result<()> {
+        let mut new_names = Vec::new();
         if let Some(note) = self.notes_cache.get(note_id) {
             if let Ok(db) = self.db.lock() {
                 db.save_note(note)?;
                 db.create_note_version(note)?;
             }
+            let previous_names = self.backlink_index.names(note_id).to_vec();
+            self.backlink_index.update_note(note);
+            new_names = self.backlink_index
+                .names(note_id)
+                .iter()
+                .filter(|name| !previous_names.contains(name))
+                .cloned()
+                .collect();
+            self.task_index.update_note(note);
+            self.search_engine_mut().update_note(note);
+        }
+        self.sync_reminders(note_id)?;
+        self.update_note_links(note_id)?;
+        // A new title or alias may be what dangling links were waiting for
+        self.resolve_dangling_links(&new_names)
+    }
+    
+    /// Computes which notes would have their `[[links]]` rewritten if the
+    /// given note were retitled, without changing anything.
+    pub fn preview_rename(&self, note_id: &str, new_title: &str) -> Option<RenamePreview> {
+        let note = self.notes_cache.get(note_id)?;
+        let new_title = new_title.trim();
+        if new_title.is_empty() {
+            return None;
+        }
+        
+        let mut rewrites = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        
+        for source in self.get_backlinks(note_id) {
+            if !seen.insert(source.id.clone()) {
+                continue;
+            }
+            
+            let (updated_content, link_count) = self.rewrite_incoming_links(source, note_id, &note.title, new_title);
+            
+            if link_count > 0 {
+                rewrites.push(LinkRewrite {
+                    note_id: source.id.clone(),
+                    note_title: source.title.clone(),
+                    link_count,
+                    original_content: source.content.clone(),
+                    updated_content,
+                });
+            }
+        }
+        
+        rewrites.sort_by(|a, b| a.note_title.cmp(&b.note_title));
+        
+        Some(RenamePreview {
+            note_id: note_id.to_string(),
+            old_title: note.title.clone(),
+            new_title: new_title.to_string(),
+            rewrites,
+        })
+    }
+    
+    /// Retitles the note and rewrites the incoming links listed in the preview.
+    /// Links are re-derived from the current content so edits made after the
+    /// preview was taken are not lost.
+    pub fn apply_rename(&mut self, preview: &RenamePreview) -> Result<RenameUndo> {
+        let mut undo = RenameUndo {
+            note_id: preview.note_id.clone(),
+            old_title: preview.old_title.clone(),
+            new_title: preview.new_title.clone(),
+            original_contents: Vec::new(),
+        };
+        
+        self.set_note_title(&preview.note_id, &preview.new_title, &preview.old_title)?;
+        
+        for rewrite in &preview.rewrites {
+            let (original, updated) = match self.notes_cache.get(&rewrite.note_id) {
+                Some(source) => {
+                    let (updated, count) = self.rewrite_incoming_links(
+                        source, &preview.note_id, &preview.old_title, &preview.new_title,
+                    );
+                    if count == 0 {
+                        continue;
+                    }
+                    (source.content.clone(), updated)
+                }
+                None => continue,
+            };
+            
+            if let Some(source) = self.notes_cache.get_mut(&rewrite.note_id) {
+                source.update_content(updated);
+            }
+            self.save_note(&rewrite.note_id)?;
+            undo.original_contents.push((rewrite.note_id.clone(), original));
+        }
+        
+        Ok(undo)
+    }
+    
+    /// Reverts a rename applied with [`apply_rename`](Self::apply_rename).
+    pub fn undo_rename(&mut self, undo: &RenameUndo) -> Result<()> {
+        self.set_note_title(&undo.note_id, &undo.old_title, &undo.new_title)?;
+        
+        for (note_id, content) in &undo.original_contents {
+            if let Some(source) = self.notes_cache.get_mut(note_id) {
+                source.update_content(content.clone());
+            }
+            self.save_note(note_id)?;
+        }
+        
+        Ok(())
+    }
+    
+    fn set_note_title(&mut self, note_id: &str, title: &str, previous_title: &str) -> Result<()> {
+        if let Some(note) = self.notes_cache.get_mut(note_id) {
+            note.title = title.to_string();
+            note.modified_at = chrono::Utc::now();
+        }
+        self.save_note(note_id)?;
+        
+        // The markdown file is named after the title, so drop the stale copy.
+        // Case-only renames map to the same file on case-insensitive filesystems.
+        if previous_title.to_lowercase() != title.to_lowercase() {
+            if let Ok(db) = self.db.lock() {
+                db.delete_note_file(previous_title)?;
+            }
         }
         Ok(())
     }
@@ -90,6 +312,12 @@ impl NoteManager {
             db.delete_note(note_id)?;
         }
         self.notes_cache.remove(note_id);
+        self.broken_links = None;
+        self.backlink_index.remove_note(note_id);
+        self.link_graph.remove_note(note_id);
+        self.task_index.remove_note(note_id);
+        self.reminders.remove_note(note_id);
+        self.search_engine_mut().remove_note(note_id);
         Ok(())
     }
     
@@ -168,6 +396,171 @@ impl NoteManager {
             .collect()
     }
     
+    /// Saves the search index and note vectors so the next launch only
+    /// re-indexes notes changed in between.
+    pub fn save_search_index(&self) -> Result<()> {
+        if let Some(path) = embeddings_path() {
+            self.search_engine().save_vectors(&path)?;
+        }
+        match search_index_path() {
+            Some(path) => self.search_engine().save_index(&path),
+            None => Ok(()),
+        }
+    }
+    
+    /// Changes how notes are split into search terms and re-indexes them.
+    pub fn set_search_tokenizer(&mut self, config: TokenizerConfig) {
+        self.search_engine_mut().set_tokenizer(config);
+    }
+    
+    /// Ranked search using the full query syntax (`tag:`, `/regex/`, relations).
+    pub fn search(&self, query: &str, max_results: usize) -> Vec<SearchResult> {
+        self.search_engine().search(query, max_results)
+    }
+    
+    /// Starts `query` on the search worker, narrowed to the `selected` facets,
+    /// replacing any query still running. Results arrive through `poll_search`.
+    pub fn submit_search(&mut self, query: &str, selected: &[Facet], max_results: usize) {
+        self.search_worker.submit(query, selected, max_results);
+    }
+    
+    /// The latest results for the submitted query, once some have arrived.
+    pub fn poll_search(&mut self) -> Option<SearchUpdate> {
+        self.search_worker.poll()
+    }
+    
+    pub fn cancel_search(&mut self) {
+        self.search_worker.cancel();
+    }
+    
+    pub fn is_searching(&self) -> bool {
+        self.search_worker.is_busy()
+    }
+    
+    /// Notes closest in meaning to a natural-language query.
+    pub fn semantic_search(&self, query: &str, max_results: usize) -> Vec<SearchResult> {
+        self.search_engine().semantic_search(query, max_results)
+    }
+    
+    /// Whether note vectors are still being made; see `SearchEngine::is_embedding`.
+    pub fn is_embedding(&self) -> bool {
+        self.search_engine().is_embedding()
+    }
+    
+    /// Notes similar in meaning to this one, most similar first.
+    pub fn get_related_notes(&self, note_id: &str, max_results: usize) -> Vec<SearchResult> {
+        self.search_engine().related_notes(note_id, max_results)
+    }
+    
+    /// Changes whenever indexed notes do; smart folders recompute on change.
+    pub fn search_revision(&self) -> u64 {
+        self.search_engine().revision()
+    }
+    
+    pub fn get_saved_searches(&self) -> &[SavedSearch] {
+        &self.saved_searches
+    }
+    
+    pub fn save_search(&mut self, name: String, query: SavedQuery) -> Result<String> {
+        let search = SavedSearch::new(name, query);
+        let search_id = search.id.clone();
+        if let Ok(db) = self.db.lock() {
+            db.save_saved_search(&search)?;
+        }
+        self.saved_searches.push(search);
+        self.saved_searches.sort_by_key(|a| a.name.to_lowercase());
+        Ok(search_id)
+    }
+    
+    pub fn rename_saved_search(&mut self, search_id: &str, name: String) -> Result<()> {
+        if let Some(search) = self.saved_searches.iter_mut().find(|search| search.id == search_id) {
+            search.name = name;
+            if let Ok(db) = self.db.lock() {
+                db.save_saved_search(search)?;
+            }
+        }
+        self.saved_searches.sort_by_key(|a| a.name.to_lowercase());
+        Ok(())
+    }
+    
+    pub fn delete_saved_search(&mut self, search_id: &str) -> Result<()> {
+        if let Ok(db) = self.db.lock() {
+            db.delete_saved_search(search_id)?;
+        }
+        self.saved_searches.retain(|search| search.id != search_id);
+        Ok(())
+    }
+    
+    /// Runs the body of a ```query block.
+    pub fn run_query(&self, source: &str) -> std::result::Result<QueryResult, String> {
+        QueryBlock::parse(source).map(|block| self.search_engine().run_query(&block))
+    }
+    
+    /// Results for each ```query block in the note, in order.
+    pub fn get_query_results(&self, note_id: &str) -> Vec<std::result::Result<QueryResult, String>> {
+        match self.notes_cache.get(note_id) {
+            Some(note) => find_query_blocks(&note.content)
+                .into_iter()
+                .map(|(_, source)| self.run_query(&source))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+    
+    /// The note's content with every ```query block replaced by its result.
+    pub fn render_query_blocks(&self, content: &str) -> String {
+        expand_query_blocks(content, |block| self.search_engine().run_query(block))
+    }
+    
+    /// Writes the note as HTML to the exports folder, with query blocks
+    /// rendered as they currently stand.
+    pub fn export_note_html(&self, note_id: &str) -> Result<std::path::PathBuf> {
+        let note = self.notes_cache
+            .get(note_id)
+            .ok_or_else(|| anyhow::anyhow!("Note not found: {}", note_id))?;
+        
+        let mut rendered = note.clone();
+        rendered.content = self.render_query_blocks(&note.content);
+        let html = crate::utils::Exporter::new().export_to_html(&rendered, true)?;
+        
+        let file_name = format!("{}.html", crate::utils::sanitize_filename(&note.title));
+        let path = crate::utils::get_exports_directory()?.join(file_name);
+        crate::utils::write_string_to_file(&path, &html)?;
+        Ok(path)
+    }
+    
+    /// Notes marked as kanban boards with a `board: true` property.
+    pub fn get_board_notes(&self) -> Vec<&Note> {
+        let mut notes: Vec<&Note> = self.notes_cache
+            .values()
+            .filter(|note| !note.is_deleted)
+            .filter(|note| note.properties().get("board").is_some_and(|value| value.trim().eq_ignore_ascii_case("true")))
+            .collect();
+        notes.sort_by(|a, b| a.title.cmp(&b.title));
+        notes
+    }
+    
+    pub fn get_board(&self, note_id: &str) -> Option<Board> {
+        self.notes_cache.get(note_id).map(|note| Board::parse(&note.content))
+    }
+    
+    /// Writes the board back into its note as markdown.
+    pub fn save_board(&mut self, note_id: &str, board: &Board) -> Result<()> {
+        match self.notes_cache.get_mut(note_id) {
+            Some(note) => note.update_content(board.to_markdown()),
+            None => return Err(anyhow::anyhow!("Note not found: {}", note_id)),
+        }
+        self.save_note(note_id)
+    }
+    
+    /// Notes currently matching a saved search.
+    pub fn run_saved_search(&self, search_id: &str, max_results: usize) -> Vec<SearchResult> {
+        match self.saved_searches.iter().find(|search| search.id == search_id) {
+            Some(search) => self.search_engine().run_saved_search(search, max_results),
+            None => Vec::new(),
+        }
+    }
+    
     pub fn get_backlinks(&self, note_id: &str) -> Vec<&Note> {
         if let Ok(db) = self.db.lock() {
             if let Ok(backlink_ids) = db.get_backlinks(note_id) {
@@ -180,6 +573,62 @@ impl NoteManager {
         Vec::new()
     }
     
+    /// Incoming links from the in-memory index, grouped by linking note.
+    pub fn get_backlink_mentions(&self, note_id: &str) -> Vec<(&Note, Vec<&Backlink>)> {
+        let mut grouped: Vec<(&Note, Vec<&Backlink>)> = Vec::new();
+        
+        for mention in self.backlink_index.backlinks(note_id) {
+            let source = match self.notes_cache.get(&mention.source_note_id) {
+                Some(source) => source,
+                None => continue,
+            };
+            
+            match grouped.last_mut() {
+                Some((note, mentions)) if note.id == source.id => mentions.push(mention),
+                _ => grouped.push((source, vec![mention])),
+            }
+        }
+        
+        grouped.sort_by_key(|a| a.0.title.to_lowercase());
+        grouped
+    }
+    
+    /// Plain-text mentions of the note's title or aliases in other notes.
+    pub fn get_unlinked_mentions(&self, note_id: &str) -> Vec<UnlinkedMention> {
+        match self.notes_cache.get(note_id) {
+            Some(target) => {
+                let notes: Vec<&Note> = self.get_all_notes();
+                self.link_processor.find_unlinked_mentions(target, &notes)
+            }
+            None => Vec::new(),
+        }
+    }
+    
+    /// Converts a mention into a `[[link]]` to `target_note_id` and saves the
+    /// source note. Returns `false` if the mention is stale.
+    pub fn link_mention(&mut self, target_note_id: &str, mention: &UnlinkedMention) -> Result<bool> {
+        let target_title = match self.notes_cache.get(target_note_id) {
+            Some(target) => target.title.clone(),
+            None => return Ok(false),
+        };
+        
+        let updated = match self.notes_cache.get(&mention.source_note_id) {
+            Some(source) => self.link_processor.link_mention(&source.content, mention, &target_title),
+            None => None,
+        };
+        
+        match updated {
+            Some(content) => {
+                if let Some(source) = self.notes_cache.get_mut(&mention.source_note_id) {
+                    source.update_content(content);
+                }
+                self.save_note(&mention.source_note_id)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+    
     pub fn get_note_links(&self, note_id: &str) -> Vec<String> {
         if let Ok(db) = self.db.lock() {
             if let Ok(links) = db.get_note_links(note_id) {
@@ -189,6 +638,16 @@ impl NoteManager {
         Vec::new()
     }
     
+    /// Stored links out of the note, with their text, ranges and kinds.
+    pub fn get_outgoing_links(&self, note_id: &str) -> Vec<NoteLink> {
+        if let Ok(db) = self.db.lock() {
+            if let Ok(links) = db.get_outgoing_links(note_id) {
+                return links;
+            }
+        }
+        Vec::new()
+    }
+    
     pub fn get_note_versions(&self, note_id: &str) -> Vec<NoteVersion> {
         if let Ok(db) = self.db.lock() {
             if let Ok(versions) = db.get_note_versions(note_id) {
@@ -198,6 +657,98 @@ impl NoteManager {
         Vec::new()
     }
     
+    pub fn get_note_version(&self, version_id: &str) -> Option<NoteVersion> {
+        let db = self.db.lock().ok()?;
+        db.get_note_version(version_id).ok().flatten()
+    }
+    
+    /// Searches trashed notes and text removed from notes since an earlier version.
+    pub fn search_history(&mut self, query: &str, scope: HistoryScope, max_results: usize) -> Vec<HistoryResult> {
+        if query.trim().is_empty() || (!scope.versions && !scope.trash) {
+            return Vec::new();
+        }
+        // Building the index diffs every version; keep it while nothing changes
+        let revision = self.search_revision();
+        let version_count = match self.db.lock() {
+            Ok(db) => db.count_note_versions().unwrap_or_else(|e| {
+                log::error!("Failed to count note versions: {}", e);
+                0
+            }),
+            Err(_) => 0,
+        };
+        let key = (revision, version_count, scope);
+        if self.history_index.as_ref().map(|(cached, _)| cached) != Some(&key) {
+            let index = self.build_history_index(scope);
+            self.history_index = Some((key, index));
+        }
+        match &self.history_index {
+            Some((_, index)) => index.search(query, max_results),
+            None => Vec::new(),
+        }
+    }
+    
+    fn build_history_index(&self, scope: HistoryScope) -> HistoryIndex {
+        let mut versions = Vec::new();
+        if scope.versions {
+            if let Ok(db) = self.db.lock() {
+                match db.get_all_note_versions() {
+                    Ok(all) => versions = all,
+                    Err(e) => log::error!("Failed to load note versions: {}", e),
+                }
+            }
+        }
+        let mut notes: Vec<Note> = self.notes_cache.values().cloned().collect();
+        // Notes trashed before this session aren't cached
+        if let Ok(db) = self.db.lock() {
+            match db.get_deleted_notes() {
+                Ok(deleted) => notes.extend(deleted.into_iter().filter(|note| !self.notes_cache.contains_key(&note.id))),
+                Err(e) => log::error!("Failed to load trashed notes: {}", e),
+            }
+        }
+        self.search_engine().history_index(scope, &notes, &versions)
+    }
+    
+    /// How the note changed from an earlier version to now.
+    pub fn diff_version(&self, version_id: &str) -> Option<Vec<DiffLine>> {
+        let version = self.get_note_version(version_id)?;
+        match self.notes_cache.get(&version.note_id) {
+            Some(note) => Some(diff_lines(&version.content, &note.content)),
+            None => {
+                // Trashed before this session, so only in the database
+                let note = self.db.lock().ok()?.get_note(&version.note_id).ok().flatten()?;
+                Some(diff_lines(&version.content, &note.content))
+            }
+        }
+    }
+    
+    /// Puts an earlier version's content back, taking the note out of the
+    /// trash if needed. The content it replaces stays in the version history.
+    pub fn restore_version(&mut self, version_id: &str) -> Result<()> {
+        let version = self
+            .get_note_version(version_id)
+            .ok_or_else(|| anyhow::anyhow!("Version not found: {}", version_id))?;
+        if !self.notes_cache.contains_key(&version.note_id) {
+            // Trashed before this session, so only in the database
+            let stored = match self.db.lock() {
+                Ok(db) => db.get_note(&version.note_id)?,
+                Err(_) => None,
+            };
+            if let Some(note) = stored {
+                self.notes_cache.insert(note.id.clone(), note);
+            }
+        }
+        match self.notes_cache.get_mut(&version.note_id) {
+            Some(note) => {
+                if note.is_deleted {
+                    note.restore_from_trash();
+                }
+                note.update_content(version.content);
+            }
+            None => return Err(anyhow::anyhow!("Note not found: {}", version.note_id)),
+        }
+        self.save_note(&version.note_id)
+    }
+    
     pub fn create_folder(&mut self, name: String, parent_id: Option<String>) -> String {
         let folder = Folder::new(name, parent_id);
         let folder_id = folder.id.clone();
@@ -210,6 +761,7 @@ impl NoteManager {
         }
         
         self.folders_cache.insert(folder_id.clone(), folder);
+        self.search_engine_mut().set_folders(self.folders_cache.values());
         folder_id
     }
     
@@ -218,63 +770,292 @@ impl NoteManager {
     }
     
     pub fn update_note_links(&mut self, note_id: &str) -> Result<()> {
+        self.broken_links = None;
         if let Some(note) = self.notes_cache.get(note_id) {
-            let links = self.extract_wiki_links(&note.content);
+            let links = self.extract_links(note_id, &note.content);
+            
+            if note.is_deleted {
+                self.link_graph.remove_note(note_id);
+            } else if !self.link_graph.contains(note_id) {
+                // Restored from trash: bring back the links pointing at it too
+                self.link_graph.add_note(note_id);
+                if let Ok(db) = self.db.lock() {
+                    for link in db.get_incoming_links(note_id)? {
+                        self.link_graph.add_link(&link);
+                    }
+                }
+            }
             
             if let Ok(db) = self.db.lock() {
                 db.update_note_links(note_id, &links)?;
             }
+            if self.link_graph.contains(note_id) {
+                self.link_graph.set_links(note_id, &links);
+            }
         }
         Ok(())
     }
     
-    fn extract_wiki_links(&self, content: &str) -> Vec<String> {
-        let mut links = Vec::new();
-        let mut chars = content.chars().peekable();
-        let mut current_pos = 0;
-        
-        while let Some(ch) = chars.next() {
-            if ch == '[' {
-                if chars.peek() == Some(&'[') {
-                    chars.next(); // consume second '['
-                    current_pos += 2;
-                    
-                    let mut link_text = String::new();
-                    let mut found_closing = false;
-                    
-                    while let Some(ch) = chars.next() {
-                        current_pos += 1;
-                        if ch == ']' {
-                            if chars.peek() == Some(&']') {
-                                chars.next(); // consume second ']'
-                                current_pos += 1;
-                                found_closing = true;
-                                break;
-                            } else {
-                                link_text.push(ch);
-                            }
-                        } else {
-                            link_text.push(ch);
-                        }
-                    }
-                    
-                    if found_closing && !link_text.trim().is_empty() {
-                        // Try to find note by title
-                        if let Some(target_note) = self.find_note_by_title(&link_text.trim()) {
-                            links.push(target_note.id.clone());
-                        }
-                    }
+    /// The vault's link graph, for orphan, centrality and path queries.
+    pub fn link_graph(&self) -> &LinkGraph {
+        &self.link_graph
+    }
+    
+    /// Checkbox tasks across the vault matching the filter.
+    pub fn get_tasks(&self, filter: &TaskFilter) -> Vec<&Task> {
+        self.task_index.query(filter, chrono::Local::now().date_naive())
+    }
+    
+    pub fn task_index(&self) -> &TaskIndex {
+        &self.task_index
+    }
+    
+    /// Flips a task's checkbox in its source note and saves it. Returns
+    /// `false` if the note changed since the task was indexed.
+    pub fn toggle_task(&mut self, task: &Task) -> Result<bool> {
+        let updated = match self.notes_cache.get(&task.note_id) {
+            Some(note) => toggle_task_in_content(&note.content, task),
+            None => None,
+        };
+        
+        match updated {
+            Some(content) => {
+                if let Some(note) = self.notes_cache.get_mut(&task.note_id) {
+                    note.update_content(content);
+                }
+                self.save_note(&task.note_id)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+    
+    /// Reminders that went off since the last call. Notes in the trash stay quiet.
+    pub fn poll_reminders(&mut self, now: chrono::DateTime<chrono::Utc>) -> Vec<ReminderNotification> {
+        let notes_cache = &self.notes_cache;
+        self.reminders
+            .poll(now)
+            .into_iter()
+            .filter(|notification| {
+                notes_cache.get(&notification.reminder.note_id).is_some_and(|note| !note.is_deleted)
+            })
+            .collect()
+    }
+    
+    pub fn get_upcoming_reminders(&self) -> Vec<&Reminder> {
+        self.reminders.upcoming()
+    }
+    
+    pub fn get_note_reminders(&self, note_id: &str) -> Vec<&Reminder> {
+        self.reminders.for_note(note_id)
+    }
+    
+    pub fn add_reminder(&mut self, note_id: &str, remind_at: chrono::DateTime<chrono::Utc>, recurrence: Recurrence) -> Result<String> {
+        let message = self.notes_cache
+            .get(note_id)
+            .map(|note| note.title.clone())
+            .ok_or_else(|| anyhow::anyhow!("Note not found: {}", note_id))?;
+        
+        let reminder = Reminder::new(note_id.to_string(), message, remind_at, recurrence);
+        let reminder_id = reminder.id.clone();
+        if let Ok(db) = self.db.lock() {
+            db.save_reminder(&reminder)?;
+        }
+        self.reminders.insert(reminder);
+        Ok(reminder_id)
+    }
+    
+    pub fn snooze_reminder(&mut self, reminder_id: &str, until: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        if let Some(reminder) = self.reminders.snooze(reminder_id, until) {
+            if let Ok(db) = self.db.lock() {
+                db.save_reminder(reminder)?;
+            }
+        }
+        Ok(())
+    }
+    
+    /// Dismisses a reminder; recurring ones are rescheduled.
+    pub fn complete_reminder(&mut self, reminder_id: &str) -> Result<()> {
+        if let Some(reminder) = self.reminders.complete(reminder_id, chrono::Utc::now()) {
+            if let Ok(db) = self.db.lock() {
+                db.save_reminder(reminder)?;
+            }
+        }
+        Ok(())
+    }
+    
+    pub fn delete_reminder(&mut self, reminder_id: &str) -> Result<()> {
+        if let Ok(db) = self.db.lock() {
+            db.delete_reminder(reminder_id)?;
+        }
+        self.reminders.remove(reminder_id);
+        Ok(())
+    }
+    
+    /// Brings the note's property and task reminders in line with its content.
+    fn sync_reminders(&mut self, note_id: &str) -> Result<()> {
+        let derived = match self.notes_cache.get(note_id) {
+            Some(note) => derive_reminders(note, self.task_index.tasks_for_note(note_id)),
+            None => Vec::new(),
+        };
+        
+        let changes = self.reminders.sync_derived(note_id, derived);
+        if let Ok(db) = self.db.lock() {
+            for reminder in &changes.saved {
+                db.save_reminder(reminder)?;
+            }
+            for reminder_id in &changes.removed {
+                db.delete_reminder(reminder_id)?;
+            }
+        }
+        Ok(())
+    }
+    
+    /// Resolves a link target (title or alias, any case) to a note id.
+    pub fn resolve_link(&self, target: &str) -> Option<String> {
+        self.backlink_index.resolve(target).map(str::to_string)
+    }
+    
+    /// Lowercased targets of the note's links that don't resolve to a note.
+    pub fn unresolved_targets(&self, note_id: &str) -> HashSet<String> {
+        match self.notes_cache.get(note_id) {
+            Some(note) => self.extract_links(note_id, &note.content)
+                .into_iter()
+                .filter(|link| !link.is_resolved())
+                .map(|link| link.target_title.to_lowercase())
+                .collect(),
+            None => HashSet::new(),
+        }
+    }
+    
+    /// Vault-wide report of links pointing at notes that don't exist,
+    /// grouped by target title.
+    pub fn get_broken_links(&mut self) -> &[BrokenLink] {
+        if self.broken_links.is_none() {
+            self.broken_links = Some(self.find_broken_links());
+        }
+        self.broken_links.as_deref().unwrap_or_default()
+    }
+    
+    fn find_broken_links(&self) -> Vec<BrokenLink> {
+        let dangling = match self.db.lock() {
+            Ok(db) => db.get_dangling_links().unwrap_or_default(),
+            Err(_) => return Vec::new(),
+        };
+        
+        let mut grouped: Vec<BrokenLink> = Vec::new();
+        for link in dangling {
+            let source_title = match self.notes_cache.get(&link.from_note_id) {
+                Some(source) if !source.is_deleted => source.title.clone(),
+                _ => continue,
+            };
+            
+            let key = link.target_title.to_lowercase();
+            let group = match grouped.iter_mut().position(|g| g.target_title.to_lowercase() == key) {
+                Some(pos) => &mut grouped[pos],
+                None => {
+                    grouped.push(BrokenLink {
+                        target_title: link.target_title.clone(),
+                        sources: Vec::new(),
+                        reference_count: 0,
+                    });
+                    grouped.last_mut().unwrap()
                 }
+            };
+            
+            group.reference_count += 1;
+            if !group.sources.iter().any(|(id, _)| *id == link.from_note_id) {
+                group.sources.push((link.from_note_id.clone(), source_title));
             }
-            current_pos += 1;
         }
         
+        grouped.sort_by(|a, b| b.reference_count.cmp(&a.reference_count)
+            .then_with(|| a.target_title.to_lowercase().cmp(&b.target_title.to_lowercase())));
+        grouped
+    }
+    
+    /// Notes tagged `template` that can seed new notes.
+    pub fn get_templates(&self) -> Vec<&Note> {
+        let mut templates = self.get_notes_by_tag("template");
+        templates.sort_by(|a, b| a.title.cmp(&b.title));
+        templates
+    }
+    
+    /// Creates the note a dangling link points at, optionally from a template,
+    /// and re-resolves every note that referenced it.
+    pub fn create_note_from_link(&mut self, title: &str, template_id: Option<&str>) -> Result<String> {
+        let title = title.trim();
+        if let Some(existing) = self.resolve_link(title) {
+            return Ok(existing);
+        }
+        
+        let mut note = Note::new(title.to_string());
+        if let Some(template) = template_id.and_then(|id| self.notes_cache.get(id)) {
+            let content = template.content
+                .replace("{{title}}", title)
+                .replace("{{date}}", &chrono::Utc::now().format("%Y-%m-%d").to_string());
+            note.update_content(content);
+        }
+        
+        // Saving re-resolves the sources that referenced the missing note
+        let note_id = note.id.clone();
+        self.notes_cache.insert(note_id.clone(), note);
+        self.save_note(&note_id)?;
+        Ok(note_id)
+    }
+    
+    /// Re-resolves notes with dangling links to any of `names` (lowercased),
+    /// once a note has been created, renamed or aliased to match them.
+    fn resolve_dangling_links(&mut self, names: &[String]) -> Result<()> {
+        if names.is_empty() {
+            return Ok(());
+        }
+        let dangling = match self.db.lock() {
+            Ok(db) => db.get_dangling_links()?,
+            Err(_) => return Ok(()),
+        };
+        let mut sources: Vec<String> = dangling
+            .into_iter()
+            .filter(|link| names.contains(&link.target_title.trim().to_lowercase()))
+            .map(|link| link.from_note_id)
+            .collect();
+        sources.sort();
+        sources.dedup();
+        for source_id in sources {
+            self.update_note_links(&source_id)?;
+        }
+        Ok(())
+    }
+    
+    fn extract_links(&self, note_id: &str, content: &str) -> Vec<NoteLink> {
+        let mut links = self.link_processor.extract_note_links(note_id, content);
+        for link in &mut links {
+            link.to_note_id = self.resolve_link(&link.target_title);
+        }
         links
     }
     
-    fn find_note_by_title(&self, title: &str) -> Option<&Note> {
-        self.notes_cache
-            .values()
-            .find(|note| !note.is_deleted && note.title.to_lowercase() == title.to_lowercase())
+    /// Rewrites `source`'s links to the renamed note at their stored ranges,
+    /// re-parsing the content instead if it has changed since the last save.
+    fn rewrite_incoming_links(&self, source: &Note, target_id: &str, old_title: &str, new_title: &str) -> (String, usize) {
+        let stored: Vec<NoteLink> = self.get_outgoing_links(&source.id)
+            .into_iter()
+            .filter(|link| link.to_note_id.as_deref() == Some(target_id))
+            .collect();
+        
+        self.link_processor
+            .rewrite_link_ranges(&source.content, &stored, old_title, new_title)
+            .filter(|(_, count)| *count > 0)
+            .unwrap_or_else(|| self.link_processor.rewrite_wiki_links(&source.content, old_title, new_title))
     }
-}
\ No newline at end of file
+}
+
+/// Where the search index is kept between launches, beside the notes folder.
+fn search_index_path() -> Option<std::path::PathBuf> {
+    crate::utils::get_app_data_directory().ok().map(|dir| dir.join("search.index"))
+}
+
+/// Note vectors live in the vault, beside the notes they were made from.
+fn embeddings_path() -> Option<std::path::PathBuf> {
+    crate::utils::get_notes_directory().ok().map(|dir| dir.join(".edison").join("embeddings.json"))
+}
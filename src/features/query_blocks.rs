@@ -0,0 +1,287 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+use super::{QueryPlan, SearchQuery};
+
+const QUERY_INFO: &str = "query";
+
+/// A parsed ```query block, e.g.
+/// `tag:project AND status:active SORT modified DESC FIELDS title,owner,due LIMIT 10`.
+///
+/// Everything before the clauses is a search box query (see [`SearchQuery`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryBlock {
+    pub filter: Option<SearchQuery>, // None lists every note
+    pub sort: Option<(String, SortOrder)>,
+    pub fields: Vec<String>,
+    pub limit: Option<usize>,
+    pub format: QueryFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueryFormat {
+    Table,
+    List,
+}
+
+impl QueryBlock {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let words = words(source);
+        let clause_start = words.iter().position(|(_, word)| is_clause(word)).unwrap_or(words.len());
+        let format = match words.first().map(|(_, word)| *word) {
+            Some("TABLE") => Some(QueryFormat::Table),
+            Some("LIST") => Some(QueryFormat::List),
+            _ => None,
+        };
+        
+        let offset = |i: usize| words.get(i).map_or(source.len(), |(offset, _)| *offset);
+        let from = offset(if format.is_some() { 1 } else { 0 });
+        let filter = source[from..offset(clause_start).max(from)].trim();
+        let filter = if filter.is_empty() {
+            None
+        } else {
+            let query = SearchQuery::parse(filter).map_err(|e| e.to_string())?;
+            QueryPlan::compile(&query).map_err(|e| e.to_string())?;
+            Some(query)
+        };
+        
+        let mut block = QueryBlock {
+            filter,
+            sort: None,
+            fields: Vec::new(),
+            limit: None,
+            format: QueryFormat::List,
+        };
+        
+        let mut rest = words[clause_start..].iter().map(|(_, word)| *word).peekable();
+        while let Some(keyword) = rest.next() {
+            match keyword {
+                "SORT" => {
+                    let field = rest.next().ok_or("SORT needs a field")?.to_lowercase();
+                    let order = match rest.peek() {
+                        Some(&"DESC") => SortOrder::Desc,
+                        _ => SortOrder::Asc,
+                    };
+                    if matches!(rest.peek(), Some(&"ASC") | Some(&"DESC")) {
+                        rest.next();
+                    }
+                    block.sort = Some((field, order));
+                }
+                "FIELDS" => {
+                    // Fields may be written `a,b,c` or `a, b, c`
+                    while let Some(token) = rest.peek() {
+                        if is_clause(token) {
+                            break;
+                        }
+                        block.fields.extend(
+                            token.split(',').map(|field| field.trim().to_lowercase()).filter(|field| !field.is_empty()),
+                        );
+                        rest.next();
+                    }
+                    if block.fields.is_empty() {
+                        return Err("FIELDS needs at least one field".to_string());
+                    }
+                }
+                "LIMIT" => {
+                    let limit = rest.next().ok_or("LIMIT needs a number")?;
+                    block.limit = Some(limit.parse().map_err(|_| format!("Invalid LIMIT \"{}\"", limit))?);
+                }
+                other => return Err(format!("Unexpected \"{}\"", other)),
+            }
+        }
+        
+        block.format = format.unwrap_or(if block.fields.is_empty() { QueryFormat::List } else { QueryFormat::Table });
+        Ok(block)
+    }
+    
+    /// Table columns: the requested fields, always led by the title.
+    pub fn columns(&self) -> Vec<String> {
+        let mut columns = vec!["title".to_string()];
+        columns.extend(self.fields.iter().filter(|field| *field != "title").cloned());
+        columns
+    }
+}
+
+/// Rows a query block produced, one per matching note.
+#[derive(Debug, Clone)]
+pub struct QueryResult {
+    pub format: QueryFormat,
+    pub columns: Vec<String>,
+    pub rows: Vec<QueryRow>,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryRow {
+    pub note_id: String,
+    pub title: String,
+    pub values: Vec<String>, // One per column
+}
+
+impl QueryResult {
+    /// Renders the result as markdown: a table, or a bullet list of links.
+    pub fn to_markdown(&self) -> String {
+        if self.rows.is_empty() {
+            return "_No matching notes._\n".to_string();
+        }
+        
+        let mut markdown = String::new();
+        if self.format == QueryFormat::Table {
+            markdown.push_str(&format!("| {} |\n", self.columns.join(" | ")));
+            markdown.push_str(&format!("|{}\n", " --- |".repeat(self.columns.len())));
+            for row in &self.rows {
+                let cells: Vec<String> = row.values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| if i == 0 { format!("[[{}]]", value) } else { value.replace('|', "\\|") })
+                    .collect();
+                markdown.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+        } else {
+            for row in &self.rows {
+                markdown.push_str(&format!("- [[{}]]\n", row.title));
+            }
+        }
+        markdown
+    }
+}
+
+/// Orders two field values: numbers numerically, everything else
+/// case-insensitively (which sorts ISO dates correctly). Empty values go last.
+pub fn compare_field_values(a: &str, b: &str, order: SortOrder) -> Ordering {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        _ => {}
+    }
+    
+    let ordering = match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    };
+    match order {
+        SortOrder::Asc => ordering,
+        SortOrder::Desc => ordering.reverse(),
+    }
+}
+
+/// Where a line sits relative to ``` code fences.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FenceLine<'a> {
+    Text,
+    Open(&'a str),   // Opening fence, with its info string
+    Inside(&'a str), // A line of code inside a fence
+    Close(&'a str),  // Closing fence of the block with this info string
+}
+
+/// Tracks code fences line by line, so the preview and `find_query_blocks`
+/// agree on where blocks start and end. A fence opens on any line starting
+/// with ``` and closes on a bare ```.
+#[derive(Debug, Default)]
+pub struct FenceScanner<'a> {
+    open: Option<&'a str>,
+}
+
+impl<'a> FenceScanner<'a> {
+    pub fn new() -> Self {
+        Self { open: None }
+    }
+    
+    pub fn classify(&mut self, line: &'a str) -> FenceLine<'a> {
+        let trimmed = line.trim();
+        match self.open {
+            Some(info) if trimmed == "```" => {
+                self.open = None;
+                FenceLine::Close(info)
+            }
+            Some(info) => FenceLine::Inside(info),
+            None => match trimmed.strip_prefix("```") {
+                Some(info) => {
+                    let info = info.trim();
+                    self.open = Some(info);
+                    FenceLine::Open(info)
+                }
+                None => FenceLine::Text,
+            },
+        }
+    }
+}
+
+/// Byte range and body of every ```query block in the content.
+pub fn find_query_blocks(content: &str) -> Vec<(Range<usize>, String)> {
+    let mut blocks = Vec::new();
+    let mut scanner = FenceScanner::new();
+    let mut offset = 0;
+    let mut open: Option<(usize, String)> = None;
+    
+    for line in content.split_inclusive('\n') {
+        match scanner.classify(line) {
+            FenceLine::Open(QUERY_INFO) => open = Some((offset, String::new())),
+            FenceLine::Inside(QUERY_INFO) => {
+                if let Some((_, body)) = &mut open {
+                    body.push_str(line);
+                }
+            }
+            FenceLine::Close(QUERY_INFO) => {
+                if let Some((start, body)) = open.take() {
+                    blocks.push((start..offset + line.len(), body.trim().to_string()));
+                }
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    
+    blocks
+}
+
+/// Replaces every ```query block with its rendered result, for export.
+pub fn expand_query_blocks(content: &str, mut run: impl FnMut(&QueryBlock) -> QueryResult) -> String {
+    let mut expanded = String::with_capacity(content.len());
+    let mut last_end = 0;
+    
+    for (range, source) in find_query_blocks(content) {
+        expanded.push_str(&content[last_end..range.start]);
+        match QueryBlock::parse(&source) {
+            Ok(block) => expanded.push_str(&run(&block).to_markdown()),
+            Err(error) => expanded.push_str(&format!("> Query error: {}\n", error)),
+        }
+        last_end = range.end;
+    }
+    
+    expanded.push_str(&content[last_end..]);
+    expanded
+}
+
+fn is_clause(word: &str) -> bool {
+    matches!(word, "SORT" | "FIELDS" | "LIMIT")
+}
+
+/// Words with their byte offsets. Quoted text stays in one word, so a
+/// phrase like `"SORT order"` isn't taken for a clause.
+fn words(source: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut in_quotes = false;
+    for (i, c) in source.char_indices() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        if c.is_whitespace() && !in_quotes {
+            if let Some(start) = start.take() {
+                words.push((start, &source[start..i]));
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(start) = start {
+        words.push((start, &source[start..]));
+    }
+    words
+}
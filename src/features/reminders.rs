@@ -0,0 +1,377 @@
+use chrono::{DateTime, Days, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use cron::Schedule;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use super::{Note, Task};
+
+// Date-only reminders and task due dates fire at this local hour
+const DEFAULT_REMINDER_HOUR: u32 = 9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recurrence {
+    Once,
+    Daily,
+    Weekly,
+    Cron(String), // 5-field crontab, or 6/7 fields with seconds
+}
+
+impl Recurrence {
+    /// Parses `daily`, `weekly`, `once`/empty, or a cron expression.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        match value.to_lowercase().as_str() {
+            "" | "once" | "none" => Some(Recurrence::Once),
+            "daily" => Some(Recurrence::Daily),
+            "weekly" => Some(Recurrence::Weekly),
+            _ => {
+                let expression = cron_expression(value);
+                Schedule::from_str(&expression).ok()?;
+                Some(Recurrence::Cron(value.to_string()))
+            }
+        }
+    }
+    
+    pub fn as_str(&self) -> &str {
+        match self {
+            Recurrence::Once => "",
+            Recurrence::Daily => "daily",
+            Recurrence::Weekly => "weekly",
+            Recurrence::Cron(expression) => expression,
+        }
+    }
+    
+    pub fn is_recurring(&self) -> bool {
+        *self != Recurrence::Once
+    }
+    
+    /// First occurrence strictly after `after`, counting from `anchor`.
+    pub fn next_after(&self, anchor: DateTime<Utc>, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.next_after_in(anchor, after, &Local)
+    }
+    
+    /// `next_after` in the given timezone. Daily and weekly reminders step by
+    /// calendar days, so they keep their local time of day across DST changes.
+    pub fn next_after_in<Tz: TimeZone>(&self, anchor: DateTime<Utc>, after: DateTime<Utc>, timezone: &Tz) -> Option<DateTime<Utc>> {
+        let days = match self {
+            Recurrence::Once => return None,
+            Recurrence::Daily => 1,
+            Recurrence::Weekly => 7,
+            Recurrence::Cron(expression) => {
+                let schedule = Schedule::from_str(&cron_expression(expression)).ok()?;
+                let start = anchor.max(after).with_timezone(timezone);
+                return schedule.after(&start).next().map(|next| next.with_timezone(&Utc));
+            }
+        };
+        
+        if anchor > after {
+            return Some(anchor);
+        }
+        let start = anchor.with_timezone(timezone).naive_local();
+        let elapsed = (after.with_timezone(timezone).date_naive() - start.date()).num_days();
+        let mut steps = u64::try_from(elapsed / days).ok()?;
+        loop {
+            // A time DST skips fires an hour later that day
+            let local = start.checked_add_days(Days::new(steps * days as u64))?;
+            let next = timezone
+                .from_local_datetime(&local)
+                .earliest()
+                .or_else(|| timezone.from_local_datetime(&(local + Duration::hours(1))).earliest())?
+                .with_timezone(&Utc);
+            if next > after {
+                return Some(next);
+            }
+            steps += 1;
+        }
+    }
+}
+
+/// The `cron` crate expects a seconds field; plain crontab lines don't have one.
+fn cron_expression(value: &str) -> String {
+    if value.split_whitespace().count() == 5 {
+        format!("0 {}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReminderSource {
+    Manual,   // Added from the reminder dialog
+    Property, // `reminder:` frontmatter property
+    Task,     // Due date of an open checkbox task
+}
+
+impl ReminderSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReminderSource::Manual => "manual",
+            ReminderSource::Property => "property",
+            ReminderSource::Task => "task",
+        }
+    }
+    
+    /// Reads back a name from `as_str`; anything else was added by hand.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "property" => ReminderSource::Property,
+            "task" => ReminderSource::Task,
+            _ => ReminderSource::Manual,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Reminder {
+    pub id: String,
+    pub note_id: String,
+    pub source: ReminderSource,
+    pub message: String,
+    pub remind_at: DateTime<Utc>,
+    pub recurrence: Recurrence,
+    pub snoozed_until: Option<DateTime<Utc>>,
+    pub is_done: bool,
+}
+
+impl Reminder {
+    pub fn new(note_id: String, message: String, remind_at: DateTime<Utc>, recurrence: Recurrence) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            note_id,
+            source: ReminderSource::Manual,
+            message,
+            remind_at,
+            recurrence,
+            snoozed_until: None,
+            is_done: false,
+        }
+    }
+    
+    /// When the reminder next goes off, taking a snooze into account.
+    pub fn fire_at(&self) -> DateTime<Utc> {
+        self.snoozed_until.unwrap_or(self.remind_at)
+    }
+    
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        !self.is_done && self.fire_at() <= now
+    }
+}
+
+/// A reminder that just went off. `missed` is set when it was due before
+/// the app started.
+#[derive(Debug, Clone)]
+pub struct ReminderNotification {
+    pub reminder: Reminder,
+    pub missed: bool,
+}
+
+/// Reminders that need writing to or deleting from the database after a sync.
+#[derive(Debug, Clone, Default)]
+pub struct ReminderChanges {
+    pub saved: Vec<Reminder>,
+    pub removed: Vec<String>,
+}
+
+/// Keeps every reminder in memory and reports the ones that come due.
+pub struct ReminderScheduler {
+    reminders: HashMap<String, Reminder>,
+    notified: HashSet<(String, i64)>, // Reminder id and fire time already shown this session
+    started_at: DateTime<Utc>,
+}
+
+impl ReminderScheduler {
+    pub fn new() -> Self {
+        Self::starting_at(Utc::now())
+    }
+    
+    pub fn starting_at(started_at: DateTime<Utc>) -> Self {
+        Self {
+            reminders: HashMap::new(),
+            notified: HashSet::new(),
+            started_at,
+        }
+    }
+    
+    pub fn load(&mut self, reminders: Vec<Reminder>) {
+        self.reminders = reminders.into_iter().map(|reminder| (reminder.id.clone(), reminder)).collect();
+    }
+    
+    pub fn get(&self, id: &str) -> Option<&Reminder> {
+        self.reminders.get(id)
+    }
+    
+    pub fn insert(&mut self, reminder: Reminder) {
+        self.reminders.insert(reminder.id.clone(), reminder);
+    }
+    
+    pub fn remove(&mut self, id: &str) -> Option<Reminder> {
+        self.reminders.remove(id)
+    }
+    
+    /// Drops every reminder attached to the note, returning their ids.
+    pub fn remove_note(&mut self, note_id: &str) -> Vec<String> {
+        let ids: Vec<String> = self.reminders
+            .values()
+            .filter(|reminder| reminder.note_id == note_id)
+            .map(|reminder| reminder.id.clone())
+            .collect();
+        for id in &ids {
+            self.reminders.remove(id);
+        }
+        ids
+    }
+    
+    /// Replaces the note's property and task reminders with `derived`.
+    /// Reminders that didn't change keep their snooze and completion state.
+    pub fn sync_derived(&mut self, note_id: &str, derived: Vec<Reminder>) -> ReminderChanges {
+        let mut changes = ReminderChanges::default();
+        let derived_ids: HashSet<&String> = derived.iter().map(|reminder| &reminder.id).collect();
+        
+        changes.removed = self.reminders
+            .values()
+            .filter(|reminder| reminder.note_id == note_id && reminder.source != ReminderSource::Manual)
+            .filter(|reminder| !derived_ids.contains(&reminder.id))
+            .map(|reminder| reminder.id.clone())
+            .collect();
+        for id in &changes.removed {
+            self.reminders.remove(id);
+        }
+        
+        for reminder in derived {
+            let unchanged = self.reminders.get(&reminder.id).is_some_and(|existing| {
+                // Completing a recurring reminder moves it forward, so a later
+                // time than the property's still counts as the same reminder
+                existing.recurrence == reminder.recurrence
+                    && existing.message == reminder.message
+                    && (existing.remind_at == reminder.remind_at
+                        || (existing.recurrence.is_recurring() && existing.remind_at > reminder.remind_at))
+            });
+            
+            if !unchanged {
+                changes.saved.push(reminder.clone());
+                self.reminders.insert(reminder.id.clone(), reminder);
+            }
+        }
+        
+        changes
+    }
+    
+    /// Reminders that came due since the last poll, oldest first. Each
+    /// occurrence is reported once; snoozing makes it fire again later.
+    pub fn poll(&mut self, now: DateTime<Utc>) -> Vec<ReminderNotification> {
+        let mut due: Vec<ReminderNotification> = Vec::new();
+        for reminder in self.reminders.values().filter(|reminder| reminder.is_due(now)) {
+            let key = (reminder.id.clone(), reminder.fire_at().timestamp());
+            if self.notified.insert(key) {
+                due.push(ReminderNotification {
+                    reminder: reminder.clone(),
+                    missed: reminder.fire_at() < self.started_at,
+                });
+            }
+        }
+        due.sort_by_key(|notification| notification.reminder.fire_at());
+        due
+    }
+    
+    pub fn snooze(&mut self, id: &str, until: DateTime<Utc>) -> Option<&Reminder> {
+        let reminder = self.reminders.get_mut(id)?;
+        reminder.snoozed_until = Some(until);
+        Some(reminder)
+    }
+    
+    /// Marks the reminder handled. Recurring reminders move on to their next
+    /// occurrence after `now`; others are finished.
+    pub fn complete(&mut self, id: &str, now: DateTime<Utc>) -> Option<&Reminder> {
+        let reminder = self.reminders.get_mut(id)?;
+        reminder.snoozed_until = None;
+        match reminder.recurrence.next_after(reminder.remind_at, now) {
+            Some(next) => reminder.remind_at = next,
+            None => reminder.is_done = true,
+        }
+        Some(reminder)
+    }
+    
+    /// Pending reminders, soonest first.
+    pub fn upcoming(&self) -> Vec<&Reminder> {
+        let mut reminders: Vec<&Reminder> = self.reminders.values().filter(|reminder| !reminder.is_done).collect();
+        reminders.sort_by_key(|reminder| reminder.fire_at());
+        reminders
+    }
+    
+    pub fn for_note(&self, note_id: &str) -> Vec<&Reminder> {
+        let mut reminders: Vec<&Reminder> = self.reminders.values().filter(|reminder| reminder.note_id == note_id).collect();
+        reminders.sort_by_key(|reminder| reminder.fire_at());
+        reminders
+    }
+}
+
+impl Default for ReminderScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reminders implied by the note itself: a `reminder:` property (with an
+/// optional `repeat:`), and the due date of every open task.
+pub fn derive_reminders(note: &Note, tasks: &[Task]) -> Vec<Reminder> {
+    let mut reminders = Vec::new();
+    if note.is_deleted {
+        return reminders;
+    }
+    
+    let properties = note.properties();
+    if let Some(remind_at) = properties.get("reminder").and_then(|value| parse_reminder_time(value)) {
+        let recurrence = properties
+            .get("repeat")
+            .and_then(|value| Recurrence::parse(value))
+            .unwrap_or(Recurrence::Once);
+        
+        reminders.push(Reminder {
+            id: format!("{}:property", note.id),
+            source: ReminderSource::Property,
+            ..Reminder::new(note.id.clone(), note.title.clone(), remind_at, recurrence)
+        });
+    }
+    
+    let mut seen = HashSet::new();
+    for task in tasks.iter().filter(|task| !task.done) {
+        let remind_at = match task.due.and_then(|due| local_time(due, DEFAULT_REMINDER_HOUR)) {
+            Some(remind_at) => remind_at,
+            None => continue,
+        };
+        
+        // Keyed by text rather than line so edits above the task keep its state
+        let id = format!("{}:task:{}", note.id, task.description);
+        if seen.insert(id.clone()) {
+            reminders.push(Reminder {
+                id,
+                source: ReminderSource::Task,
+                ..Reminder::new(note.id.clone(), task.description.clone(), remind_at, Recurrence::Once)
+            });
+        }
+    }
+    
+    reminders
+}
+
+/// Parses `2026-10-20 14:30`, `2026-10-20T14:30`, RFC 3339, or a bare date
+/// (which fires at 9:00). Times without an offset are local.
+pub fn parse_reminder_time(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+    
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(value, format) {
+            return Local.from_local_datetime(&time).earliest().map(|time| time.with_timezone(&Utc));
+        }
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| local_time(date, DEFAULT_REMINDER_HOUR))
+}
+
+fn local_time(date: NaiveDate, hour: u32) -> Option<DateTime<Utc>> {
+    let time = date.and_time(NaiveTime::from_hms_opt(hour, 0, 0)?);
+    Local.from_local_datetime(&time).earliest().map(|time| time.with_timezone(&Utc))
+}
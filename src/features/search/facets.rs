@@ -0,0 +1,138 @@
+use chrono::{Datelike, Local, NaiveDate};
+use std::collections::{HashMap, HashSet};
+use crate::features::parse_list_property;
+use super::{IndexedNote, MatchType, SearchIndex, SearchResult};
+
+/// A value search results can be narrowed to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Facet {
+    Tag(String),              // Lowercased, so `#Work` and `#work` count together
+    Folder(Option<String>),   // Folder id; `None` is notes outside any folder
+    Month(i32, u32),          // Year and month the note was created, in local time
+    Property(String, String), // Frontmatter key (lowercased) and one of its values
+    MatchType(MatchType),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FacetCount {
+    pub facet: Facet,
+    pub label: String,
+    pub count: usize,
+}
+
+/// How many results fall under each facet value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FacetCounts {
+    pub tags: Vec<FacetCount>,
+    pub folders: Vec<FacetCount>,
+    pub months: Vec<FacetCount>, // Newest first
+    pub properties: Vec<FacetCount>,
+    pub match_types: Vec<FacetCount>,
+}
+
+/// Results narrowed to the selected facets, with facet counts over all of them.
+#[derive(Debug, Clone, Default)]
+pub struct FacetedResults {
+    pub results: Vec<SearchResult>,
+    pub total: usize, // Matching notes before `max_results` was applied
+    pub facets: FacetCounts,
+}
+
+impl Facet {
+    pub(super) fn matches(&self, note: &IndexedNote, result: &SearchResult) -> bool {
+        match self {
+            Facet::Tag(tag) => note.all_tags().iter().any(|t| t.to_lowercase() == tag.to_lowercase()),
+            Facet::Folder(folder_id) => note.folder_id == *folder_id,
+            Facet::Month(year, month) => {
+                let created = note.created_at.with_timezone(&Local);
+                created.year() == *year && created.month() == *month
+            }
+            Facet::Property(key, value) => note
+                .properties
+                .get(key)
+                .is_some_and(|property| parse_list_property(property).iter().any(|v| v == value)),
+            Facet::MatchType(match_type) => result.match_type == *match_type,
+        }
+    }
+}
+
+impl MatchType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MatchType::TitleExact => "Title",
+            MatchType::TitleFuzzy => "Title (approximate)",
+            MatchType::ContentExact => "Content",
+            MatchType::ContentFuzzy => "Content (approximate)",
+            MatchType::Tag => "Tag",
+            MatchType::Regex => "Pattern",
+            MatchType::DateRange => "Date",
+            MatchType::TagCombination => "Tags",
+            MatchType::Relation => "Relation",
+            MatchType::Semantic => "Meaning",
+        }
+    }
+}
+
+/// Facet counts gathered one matching note at a time, so a search can count
+/// every match while only keeping its best results.
+#[derive(Default)]
+pub(super) struct FacetTally {
+    counts: HashMap<Facet, usize>,
+}
+
+impl FacetTally {
+    pub(super) fn add(&mut self, note: &IndexedNote, match_type: &MatchType) {
+        // A note counts once per value, however often it repeats one
+        let mut facets: HashSet<Facet> = HashSet::new();
+        facets.extend(note.all_tags().into_iter().map(|tag| Facet::Tag(tag.to_lowercase())));
+        facets.insert(Facet::Folder(note.folder_id.clone()));
+        let created = note.created_at.with_timezone(&Local);
+        facets.insert(Facet::Month(created.year(), created.month()));
+        for (key, value) in note.properties.iter().filter(|(key, _)| key.as_str() != "tags") {
+            facets.extend(parse_list_property(value).into_iter().map(|value| Facet::Property(key.clone(), value)));
+        }
+        facets.insert(Facet::MatchType(match_type.clone()));
+        for facet in facets {
+            *self.counts.entry(facet).or_default() += 1;
+        }
+    }
+    
+    pub(super) fn finish(self, index: &SearchIndex) -> FacetCounts {
+        let mut facets = FacetCounts::default();
+        for (facet, count) in self.counts {
+            let (group, label) = match &facet {
+                Facet::Tag(tag) => (&mut facets.tags, format!("#{}", tag)),
+                Facet::Folder(Some(id)) => (&mut facets.folders, index.folders.get(id).cloned().unwrap_or_else(|| id.clone())),
+                Facet::Folder(None) => (&mut facets.folders, "No folder".to_string()),
+                Facet::Month(year, month) => (
+                    &mut facets.months,
+                    NaiveDate::from_ymd_opt(*year, *month, 1).map_or_else(String::new, |date| date.format("%B %Y").to_string()),
+                ),
+                Facet::Property(key, value) => (&mut facets.properties, format!("{}: {}", key, value)),
+                Facet::MatchType(match_type) => (&mut facets.match_types, match_type.label().to_string()),
+            };
+            group.push(FacetCount { facet, label, count });
+        }
+        
+        for group in [&mut facets.tags, &mut facets.folders, &mut facets.properties, &mut facets.match_types] {
+            group.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.label.cmp(&b.label)));
+        }
+        facets.months.sort_by(|a, b| match (&a.facet, &b.facet) {
+            (Facet::Month(ay, am), Facet::Month(by, bm)) => (by, bm).cmp(&(ay, am)),
+            _ => std::cmp::Ordering::Equal,
+        });
+        facets
+    }
+}
+
+impl FacetCounts {
+    pub(super) fn count(results: &[SearchResult], index: &SearchIndex) -> Self {
+        let mut tally = FacetTally::default();
+        for result in results {
+            if let Some(note) = index.notes.get(&result.note_id) {
+                tally.add(note, &result.match_type);
+            }
+        }
+        tally.finish(index)
+    }
+}
@@ -0,0 +1,173 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::ops::Range;
+use crate::features::{diff_lines, DiffLine, Note, NoteVersion};
+use super::{MatchField, SearchEngine, SearchIndex, SearchResult};
+
+/// Which past text a history search looks through.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryScope {
+    pub versions: bool, // Earlier versions of notes
+    pub trash: bool,    // Notes in the trash
+}
+
+/// Where a history search result was found.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistorySource {
+    Trash,
+    Version {
+        version_id: String,
+        version_number: i32,
+        timestamp: DateTime<Utc>, // When the version was saved
+    },
+}
+
+/// A match in a trashed note or an earlier version. `result.note_id` is the
+/// note; its snippet and matches point into the version's content.
+#[derive(Debug, Clone)]
+pub struct HistoryResult {
+    pub result: SearchResult,
+    pub source: HistorySource,
+}
+
+/// Trashed notes and removed text, indexed for history searches. Building
+/// it diffs every version, so it is worth keeping while nothing changes.
+pub struct HistoryIndex {
+    engine: SearchEngine,
+    sources: HashMap<String, (String, HistorySource)>, // Indexed id -> (note id, source)
+    removed: HashMap<String, Vec<Range<usize>>>,       // Version id -> lines since removed
+}
+
+impl SearchEngine {
+    /// Runs a query over trashed notes and earlier versions of notes; see
+    /// `HistoryIndex::search`.
+    pub fn search_history(
+        &self,
+        query: &str,
+        scope: HistoryScope,
+        notes: &[Note],
+        versions: &[NoteVersion],
+        max_results: usize,
+    ) -> Vec<HistoryResult> {
+        if query.trim().is_empty() || (!scope.versions && !scope.trash) {
+            return Vec::new();
+        }
+        self.history_index(scope, notes, versions).search(query, max_results)
+    }
+    
+    /// Indexes what `scope` covers of trashed notes and earlier versions of
+    /// notes, which the index leaves out.
+    pub fn history_index(&self, scope: HistoryScope, notes: &[Note], versions: &[NoteVersion]) -> HistoryIndex {
+        // Index each past text as a note of its own, under an id of its own
+        let mut index = SearchIndex::new(self.tokenizer.clone());
+        index.folders = self.index().folders.clone();
+        let mut sources: HashMap<String, (String, HistorySource)> = HashMap::new();
+        let mut removed: HashMap<String, Vec<Range<usize>>> = HashMap::new();
+        let notes_by_id: HashMap<&str, &Note> = notes.iter().map(|note| (note.id.as_str(), note)).collect();
+        if scope.trash {
+            for note in notes.iter().filter(|note| note.is_deleted) {
+                index.add_note(note, &self.link_processor);
+                sources.insert(note.id.clone(), (note.id.clone(), HistorySource::Trash));
+            }
+        }
+        if scope.versions {
+            for version in versions {
+                let note = match notes_by_id.get(version.note_id.as_str()) {
+                    Some(note) if note.content != version.content => note,
+                    _ => continue,
+                };
+                let lines = removed_lines(&version.content, &note.content);
+                if lines.is_empty() {
+                    continue;
+                }
+                removed.insert(version.id.clone(), lines);
+                let mut past = (*note).clone();
+                past.id = version.id.clone();
+                past.content = version.content.clone();
+                past.modified_at = version.timestamp;
+                past.is_deleted = false;
+                index.add_note(&past, &self.link_processor);
+                sources.insert(version.id.clone(), (note.id.clone(), HistorySource::Version {
+                    version_id: version.id.clone(),
+                    version_number: version.version_number,
+                    timestamp: version.timestamp,
+                }));
+            }
+        }
+        
+        let engine = SearchEngine {
+            tokenizer: self.tokenizer.clone(),
+            ..SearchEngine::new()
+        };
+        let _ = engine.index.set(index);
+        HistoryIndex { engine, sources, removed }
+    }
+}
+
+impl HistoryIndex {
+    /// A version only matches on lines the note no longer has, and each note
+    /// only gives its newest matching version, so results show text that has
+    /// since been removed.
+    pub fn search(&self, query: &str, max_results: usize) -> Vec<HistoryResult> {
+        if query.trim().is_empty() {
+            return Vec::new();
+        }
+        
+        // Newest matching version per note, then the trashed note itself
+        let mut best: HashMap<(String, bool), HistoryResult> = HashMap::new();
+        for mut result in self.engine.search(query, usize::MAX) {
+            let (note_id, source) = match self.sources.get(&result.note_id) {
+                Some(found) => found.clone(),
+                None => continue,
+            };
+            // Title and filter matches, and text the note still has, aren't history
+            if let Some(lines) = self.removed.get(&result.note_id) {
+                let in_removed = result.matches.iter().any(|span| {
+                    MatchField::CONTENT.contains(&span.field) && lines.iter().any(|line| span.start < line.end && line.start < span.end)
+                });
+                if !in_removed {
+                    continue;
+                }
+            }
+            result.note_id = note_id.clone();
+            let key = (note_id, source == HistorySource::Trash);
+            let newer = match (best.get(&key).map(|found| &found.source), &source) {
+                (Some(HistorySource::Version { timestamp: kept, .. }), HistorySource::Version { timestamp, .. }) => timestamp > kept,
+                (Some(_), _) => false,
+                (None, _) => true,
+            };
+            if newer {
+                best.insert(key, HistoryResult { result, source });
+            }
+        }
+        
+        let mut results: Vec<HistoryResult> = best.into_values().collect();
+        results.sort_by(|a, b| {
+            b.result.score
+                .partial_cmp(&a.result.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.result.note_id.cmp(&b.result.note_id))
+        });
+        results.truncate(max_results);
+        results
+    }
+}
+
+/// Byte ranges of the lines of `old` that `new` no longer has.
+fn removed_lines(old: &str, new: &str) -> Vec<Range<usize>> {
+    let mut lines = old.split_inclusive('\n');
+    let mut start = 0;
+    let mut removed = Vec::new();
+    for line in diff_lines(old, new) {
+        if let DiffLine::Added(_) = line {
+            continue;
+        }
+        // Same and removed lines walk through `old` in order
+        let length = lines.next().map_or(0, str::len);
+        if let DiffLine::Removed(_) = line {
+            removed.push(start..start + length);
+        }
+        start += length;
+    }
+    removed
+}
@@ -0,0 +1,363 @@
+use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use super::tokenizer::{Token, Tokenizer, TokenizerConfig};
+use super::{IndexedNote, MatchType};
+
+// BM25 term saturation and length normalisation
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+
+// How much a word counts when it only matches the start of an indexed word,
+// or only looks like a typo of one
+const PREFIX_WEIGHT: f32 = 0.8;
+const FUZZY_WEIGHT: f32 = 0.5;
+const FUZZY_THRESHOLD: i64 = 40;
+
+/// Which part of a note a word was found in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MatchField {
+    Title,
+    Heading,
+    Tag,
+    Body,
+}
+
+impl MatchField {
+    pub const ALL: [MatchField; 4] = [MatchField::Title, MatchField::Heading, MatchField::Tag, MatchField::Body];
+    pub const CONTENT: [MatchField; 2] = [MatchField::Heading, MatchField::Body];
+    
+    /// Relative weight of a hit in this field.
+    fn boost(self) -> f32 {
+        match self {
+            MatchField::Title => 3.0,
+            MatchField::Heading => 2.0,
+            MatchField::Tag => 2.0,
+            MatchField::Body => 1.0,
+        }
+    }
+    
+    fn slot(self) -> usize {
+        self as usize
+    }
+}
+
+/// A match: a byte range in the note title (`Title`), its content
+/// (`Heading`, `Body`) or its tags joined by spaces (`Tag`), and where it
+/// starts in that text as a 1-based line and character column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchSpan {
+    pub field: MatchField,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl MatchSpan {
+    /// A span over `text[start..end]`, locating its line and column.
+    pub fn locate(field: MatchField, text: &str, start: usize, end: usize) -> Self {
+        let before = &text[..start];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Self {
+            field,
+            start,
+            end,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+    
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.start..self.end
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Posting {
+    frequencies: [u32; 4], // Per field
+    spans: Vec<MatchSpan>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DocumentStats {
+    lengths: [u32; 4], // Words per field
+    terms: Vec<String>, // So a note can be removed without scanning every term
+}
+
+/// Word -> note postings, updated one note at a time.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(super) struct InvertedIndex {
+    postings: BTreeMap<String, HashMap<String, Posting>>,
+    documents: HashMap<String, DocumentStats>,
+    total_lengths: [u64; 4],
+    field_documents: [u64; 4], // Notes with any words in each field
+    tokenizer: Tokenizer,      // Saved with the index, which is only valid for it
+}
+
+/// One note's combined match for a query.
+#[derive(Debug, Clone, Default)]
+pub(super) struct TextHit {
+    pub(super) score: f32,
+    pub(super) spans: Vec<MatchSpan>,
+    pub(super) fuzzy: bool, // Some word only matched as a likely typo
+}
+
+impl TextHit {
+    pub(super) fn match_type(&self) -> MatchType {
+        let found_in = |fields: &[MatchField]| self.spans.iter().any(|span| fields.contains(&span.field));
+        match (found_in(&[MatchField::Title]), found_in(&MatchField::CONTENT), self.fuzzy) {
+            (true, _, false) => MatchType::TitleExact,
+            (true, _, true) => MatchType::TitleFuzzy,
+            (false, true, false) => MatchType::ContentExact,
+            (false, true, true) => MatchType::ContentFuzzy,
+            (false, false, _) => MatchType::Tag,
+        }
+    }
+    
+    fn merge(&mut self, other: TextHit) {
+        self.score += other.score;
+        self.fuzzy |= other.fuzzy;
+        self.spans.extend(other.spans);
+    }
+}
+
+impl InvertedIndex {
+    pub(super) fn new(config: TokenizerConfig) -> Self {
+        Self {
+            tokenizer: Tokenizer::new(config),
+            ..Self::default()
+        }
+    }
+    
+    pub(super) fn clear(&mut self) {
+        *self = Self::new(self.tokenizer.config().clone());
+    }
+    
+    pub(super) fn tokenizer(&self) -> &Tokenizer {
+        &self.tokenizer
+    }
+    
+    /// Indexes a note's title, tags, headings and body, replacing any earlier version.
+    pub(super) fn add_note(&mut self, note: &IndexedNote) {
+        self.remove_note(&note.id);
+        
+        let mut postings: HashMap<String, Posting> = HashMap::new();
+        let mut lengths = [0u32; 4];
+        // `text` is a single line starting `offset` bytes into its field
+        let mut record = |field: MatchField, text: &str, offset: usize, line: usize| {
+            for token in self.tokenizer.tokenize(text) {
+                let span = MatchSpan {
+                    field,
+                    start: offset + token.start,
+                    end: offset + token.end,
+                    line,
+                    column: text[..token.start].chars().count() + 1,
+                };
+                // Unstemmed words are indexed too, so prefixes of them still match
+                let surface = (token.surface != token.term).then_some(token.surface);
+                for term in std::iter::once(token.term).chain(surface) {
+                    let posting = postings.entry(term).or_default();
+                    posting.frequencies[field.slot()] += 1;
+                    posting.spans.push(span);
+                }
+                lengths[field.slot()] += 1;
+            }
+        };
+        record(MatchField::Title, &note.title, 0, 1);
+        record(MatchField::Tag, &note.all_tags().join(" "), 0, 1);
+        for (index, (line, offset, is_heading)) in content_lines(&note.content).into_iter().enumerate() {
+            record(if is_heading { MatchField::Heading } else { MatchField::Body }, line, offset, index + 1);
+        }
+        
+        for (slot, length) in lengths.iter().enumerate().filter(|(_, length)| **length > 0) {
+            self.total_lengths[slot] += *length as u64;
+            self.field_documents[slot] += 1;
+        }
+        let terms = postings.keys().cloned().collect();
+        for (term, posting) in postings {
+            self.postings.entry(term).or_default().insert(note.id.clone(), posting);
+        }
+        self.documents.insert(note.id.clone(), DocumentStats { lengths, terms });
+    }
+    
+    pub(super) fn remove_note(&mut self, note_id: &str) {
+        let document = match self.documents.remove(note_id) {
+            Some(document) => document,
+            None => return,
+        };
+        
+        for (slot, length) in document.lengths.iter().enumerate().filter(|(_, length)| **length > 0) {
+            self.total_lengths[slot] -= *length as u64;
+            self.field_documents[slot] -= 1;
+        }
+        for term in &document.terms {
+            if let Some(notes) = self.postings.get_mut(term) {
+                notes.remove(note_id);
+                if notes.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+    }
+    
+    /// Ranks notes for free text. Every word must match, either exactly, as
+    /// the start of a longer word, or (when nothing closer is indexed) as a
+    /// likely typo; each note gets one hit with all its matched spans.
+    pub(super) fn search(&self, text: &str, fields: &[MatchField], matcher: &SkimMatcherV2) -> HashMap<String, TextHit> {
+        let mut hits: Option<HashMap<String, TextHit>> = None;
+        
+        for token in self.tokenizer.tokenize(text) {
+            let mut token_hits: HashMap<String, TextHit> = HashMap::new();
+            for (term, weight, fuzzy) in self.expand(&token, matcher) {
+                let notes = &self.postings[term];
+                for (note_id, posting) in notes {
+                    let score = weight * self.bm25(notes, note_id, posting, fields);
+                    if score <= 0.0 {
+                        continue; // Only found in fields that weren't asked for
+                    }
+                    
+                    // The best matching form of the word decides the score
+                    let hit = token_hits.entry(note_id.clone()).or_default();
+                    if score > hit.score {
+                        hit.score = score;
+                        hit.fuzzy = fuzzy;
+                    }
+                    hit.spans.extend(posting.spans.iter().filter(|span| fields.contains(&span.field)));
+                }
+            }
+            
+            hits = Some(match hits {
+                None => token_hits,
+                Some(mut hits) => {
+                    hits.retain(|note_id, _| token_hits.contains_key(note_id));
+                    for (note_id, hit) in hits.iter_mut() {
+                        if let Some(token_hit) = token_hits.remove(note_id) {
+                            hit.merge(token_hit);
+                        }
+                    }
+                    hits
+                }
+            });
+        }
+        
+        let mut hits = hits.unwrap_or_default();
+        for hit in hits.values_mut() {
+            hit.spans.sort_by_key(|span| (span.field.slot(), span.start));
+            hit.spans.dedup();
+        }
+        hits
+    }
+    
+    /// Notes that can contain `text`, even inside longer words: every word
+    /// of it has to be part of an indexed word. Only the vocabulary is
+    /// scanned, not the notes. `None` when the index can't tell, because
+    /// `text` has no indexed words or a word could hide in a stop word.
+    pub(super) fn candidates(&self, text: &str) -> Option<HashSet<&str>> {
+        let tokens = self.tokenizer.tokenize(text);
+        if tokens.is_empty() || tokens.iter().any(|token| self.tokenizer.in_stop_word(&token.surface)) {
+            return None;
+        }
+        
+        let mut candidates: Option<HashSet<&str>> = None;
+        for token in tokens {
+            let notes: HashSet<&str> = self.postings
+                .iter()
+                .filter(|(term, _)| **term == token.term || term.contains(token.surface.as_str()))
+                .flat_map(|(_, notes)| notes.keys().map(String::as_str))
+                .collect();
+            candidates = Some(match candidates {
+                None => notes,
+                Some(candidates) => candidates.intersection(&notes).copied().collect(),
+            });
+        }
+        candidates
+    }
+    
+    /// BM25 score of one note for the words in `text`, which must all be
+    /// indexed for it exactly.
+    pub(super) fn score_note(&self, note_id: &str, text: &str, fields: &[MatchField]) -> Option<TextHit> {
+        let mut hit = TextHit::default();
+        for token in self.tokenizer.tokenize(text) {
+            let notes = self.postings.get(&token.term)?;
+            let posting = notes.get(note_id)?;
+            hit.score += self.bm25(notes, note_id, posting, fields);
+            hit.spans.extend(posting.spans.iter().filter(|span| fields.contains(&span.field)));
+        }
+        
+        hit.spans.sort_by_key(|span| (span.field.slot(), span.start));
+        hit.spans.dedup();
+        (hit.score > 0.0).then_some(hit)
+    }
+    
+    /// Indexed words a query word can stand for, with how much each counts:
+    /// its stem, words starting with it as typed, or likely typos of it.
+    fn expand(&self, token: &Token, matcher: &SkimMatcherV2) -> Vec<(&str, f32, bool)> {
+        let surface = token.surface.as_str();
+        let mut terms: Vec<(&str, f32, bool)> = self.postings
+            .get_key_value(&token.term)
+            .map(|(term, _)| (term.as_str(), 1.0, false))
+            .into_iter()
+            .collect();
+        terms.extend(self.postings
+            .range(token.surface.clone()..)
+            .take_while(|(term, _)| term.starts_with(surface))
+            .filter(|(term, _)| **term != token.term)
+            .map(|(term, _)| (term.as_str(), if term == surface { 1.0 } else { PREFIX_WEIGHT }, false)));
+        
+        let length = surface.chars().count();
+        if terms.is_empty() && length >= 3 {
+            terms = self.postings
+                .keys()
+                .filter(|term| term.chars().count() <= length + 2)
+                .filter(|term| matcher.fuzzy_match(term, surface).is_some_and(|score| score > FUZZY_THRESHOLD))
+                .map(|term| (term.as_str(), FUZZY_WEIGHT, true))
+                .collect();
+        }
+        terms
+    }
+    
+    /// BM25F: field frequencies are boosted and length-normalised per field,
+    /// then saturated together. Field lengths are compared with notes that
+    /// have that field, so a lone heading isn't penalised as long.
+    fn bm25(&self, notes: &HashMap<String, Posting>, note_id: &str, posting: &Posting, fields: &[MatchField]) -> f32 {
+        let document = match self.documents.get(note_id) {
+            Some(document) => document,
+            None => return 0.0,
+        };
+        let count = self.documents.len() as f32;
+        let frequency = notes.len() as f32;
+        let idf = (1.0 + (count - frequency + 0.5) / (frequency + 0.5)).ln();
+        
+        let mut weighted = 0.0;
+        for field in fields {
+            let slot = field.slot();
+            if posting.frequencies[slot] == 0 {
+                continue;
+            }
+            let average = self.total_lengths[slot] as f32 / self.field_documents[slot] as f32;
+            let normalisation = 1.0 - B + B * document.lengths[slot] as f32 / average;
+            weighted += field.boost() * posting.frequencies[slot] as f32 / normalisation;
+        }
+        idf * weighted / (K1 + weighted)
+    }
+}
+
+/// Content lines with their byte offsets, flagged when they are headings.
+/// Lines inside code fences are never headings.
+fn content_lines(content: &str) -> Vec<(&str, usize, bool)> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    let mut in_fence = false;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+        let is_heading = !in_fence && (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ');
+        lines.push((line, offset, is_heading));
+        offset += line.len();
+    }
+    lines
+}
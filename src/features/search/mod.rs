@@ -0,0 +1,1079 @@
+use fuzzy_matcher::skim::SkimMatcherV2;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::JoinHandle;
+use crate::features::{Folder, LinkProcessor, Note, parse_list_property};
+use crate::features::query_blocks::{compare_field_values, QueryBlock, QueryResult, QueryRow, SortOrder};
+use regex::Regex;
+use chrono::{DateTime, Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+mod facets;
+mod history;
+mod inverted;
+pub mod query;
+mod semantic;
+mod store;
+mod tokenizer;
+mod worker;
+
+pub use facets::{Facet, FacetCount, FacetCounts, FacetedResults};
+pub use history::{HistoryIndex, HistoryResult, HistoryScope, HistorySource};
+pub use inverted::{MatchField, MatchSpan};
+pub use query::{CompareOp, FieldValue, QueryParseError, QueryPlan, SearchField, SearchQuery};
+pub use semantic::{cosine_similarity, EmbeddingModel, HashedEmbedding};
+pub use tokenizer::{Language, Token, Tokenizer, TokenizerConfig};
+pub use worker::{EngineWriteGuard, SearchUpdate, SearchWorker, DEFAULT_DEBOUNCE};
+use facets::FacetTally;
+use inverted::InvertedIndex;
+use semantic::SemanticIndex;
+
+// Notes less similar than this aren't worth showing; a short query shares
+// fewer words with a note than another note does
+const MIN_SIMILARITY: f32 = 0.15;
+const MIN_QUERY_SIMILARITY: f32 = 0.05;
+
+// Notes checked between progress reports and cancellation checks
+const SEARCH_BATCH_SIZE: usize = 256;
+
+pub struct SearchEngine {
+    matcher: SkimMatcherV2,
+    index: OnceLock<SearchIndex>,       // Filled on first use, from `pending` if set
+    pending: Mutex<Option<PendingLoad>>, // A saved index being read and brought up to date
+    load_report: OnceLock<IndexLoadReport>,
+    link_processor: LinkProcessor,
+    tokenizer: TokenizerConfig,
+    semantic: SemanticIndex,
+    revision: u64,
+}
+
+/// A saved index `load_index` is reading on a background thread, picked up
+/// when the index is first needed.
+struct PendingLoad {
+    loading: JoinHandle<(SearchIndex, IndexLoadReport)>,
+    folders: HashMap<String, String>,
+}
+
+/// How a saved index was brought up to date when it was loaded.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexLoadReport {
+    pub from_disk: bool,  // False when the file was missing, stale or damaged
+    pub reused: usize,    // Notes unchanged since the index was written
+    pub reindexed: usize, // New or modified notes
+    pub dropped: usize,   // Notes deleted since
+}
+
+pub struct SearchIndex {
+    notes: HashMap<String, IndexedNote>,
+    tags: HashMap<String, Vec<String>>, // tag -> note_ids
+    terms: InvertedIndex,
+    folders: HashMap<String, String>, // folder_id -> name, for folder: queries and facets
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct IndexedNote {
+    id: String,
+    title: String,
+    content: String,
+    tags: Vec<String>,
+    word_count: usize,
+    relations: Vec<(String, String)>, // (relation, lowercased target title)
+    properties: HashMap<String, String>, // Frontmatter, keys lowercased
+    folder_id: Option<String>,
+    created_at: DateTime<Utc>,
+    modified_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub note_id: String,
+    pub title: String,
+    pub score: f32,
+    pub match_type: MatchType,
+    pub snippet: Snippet,
+    pub title_highlights: Vec<Range<usize>>, // Byte ranges in `title`
+    pub matches: Vec<MatchSpan>,             // Every match, with its line and column
+}
+
+/// A short excerpt of a note around its first match.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snippet {
+    pub text: String,
+    pub highlights: Vec<Range<usize>>, // Byte ranges in `text`
+}
+
+impl SearchResult {
+    /// The first match in the note content, where opening the result should
+    /// put the cursor.
+    pub fn first_content_match(&self) -> Option<&MatchSpan> {
+        self.matches
+            .iter()
+            .filter(|span| MatchField::CONTENT.contains(&span.field))
+            .min_by_key(|span| span.start)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MatchType {
+    TitleExact,
+    TitleFuzzy,
+    ContentExact,
+    ContentFuzzy,
+    Tag,
+    Regex,
+    DateRange,
+    TagCombination,
+    Relation,
+    Semantic,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchFilter {
+    pub query: String,
+    pub tags: Vec<String>,
+    pub date_from: Option<DateTime<Utc>>,
+    pub date_to: Option<DateTime<Utc>>,
+    pub regex_pattern: Option<String>,
+    pub match_any_tag: bool, // If false, must match all tags
+    pub include_content: bool,
+    pub include_titles: bool,
+}
+
+impl Default for SearchFilter {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            tags: Vec::new(),
+            date_from: None,
+            date_to: None,
+            regex_pattern: None,
+            match_any_tag: false,
+            include_content: true,
+            include_titles: true,
+        }
+    }
+}
+
+/// What a saved search runs: query text in the search box syntax, or a
+/// structured filter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum SavedQuery {
+    Text(String),
+    Filter(SearchFilter),
+}
+
+/// A named query shown as a smart folder.
+#[derive(Debug, Clone)]
+pub struct SavedSearch {
+    pub id: String,
+    pub name: String,
+    pub query: SavedQuery,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SavedSearch {
+    pub fn new(name: String, query: SavedQuery) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            query,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+impl SearchEngine {
+    pub fn new() -> Self {
+        Self {
+            matcher: SkimMatcherV2::default(),
+            index: OnceLock::new(),
+            pending: Mutex::new(None),
+            load_report: OnceLock::new(),
+            link_processor: LinkProcessor::new(),
+            tokenizer: TokenizerConfig::default(),
+            semantic: SemanticIndex::new(Box::new(HashedEmbedding::default())),
+            revision: 0,
+        }
+    }
+    
+    pub fn tokenizer(&self) -> &TokenizerConfig {
+        &self.tokenizer
+    }
+    
+    /// Changes how text is split into words, re-indexing every note with it.
+    /// A saved index built with other settings is rebuilt when it is loaded.
+    pub fn set_tokenizer(&mut self, config: TokenizerConfig) {
+        if config == self.tokenizer {
+            return;
+        }
+        self.tokenizer = config.clone();
+        if let Some(index) = self.index.get_mut() {
+            index.terms = InvertedIndex::new(config);
+            for note in index.notes.values() {
+                index.terms.add_note(note);
+            }
+        }
+        self.revision += 1;
+    }
+    
+    pub fn update_index(&mut self, notes: &[Note]) {
+        if let Ok(pending) = self.pending.get_mut() {
+            *pending = None;
+        }
+        self.index_mut().clear();
+        let index = self.index.get_mut().expect("search index is loaded");
+        
+        for note in notes {
+            if !note.is_deleted {
+                index.add_note(note, &self.link_processor);
+            }
+        }
+        self.semantic.sync(notes);
+        
+        self.revision += 1;
+    }
+    
+    /// Uses the index saved at `path` instead of rebuilding: only notes whose
+    /// `modified_at` changed since it was written are re-indexed. The file is
+    /// read on a background thread; if the index is already in memory it is
+    /// brought up to date in place.
+    pub fn load_index(&mut self, path: &Path, notes: Arc<[Note]>) {
+        if let Some(index) = self.index.get_mut() {
+            index.sync(&notes, &self.link_processor);
+        } else {
+            // A later call replaces an earlier one that was never needed
+            let folders = self.pending.get_mut().ok().and_then(|pending| pending.take()).map(|pending| pending.folders);
+            let path: PathBuf = path.to_path_buf();
+            let tokenizer = self.tokenizer.clone();
+            let loading = std::thread::spawn(move || SearchIndex::load(&path, notes, &tokenizer));
+            if let Ok(pending) = self.pending.get_mut() {
+                *pending = Some(PendingLoad { loading, folders: folders.unwrap_or_default() });
+            }
+        }
+        self.revision += 1;
+    }
+    
+    /// Writes the index to `path` for `load_index`. An index that was never
+    /// needed hasn't changed, so there is nothing to write.
+    pub fn save_index(&self, path: &Path) -> anyhow::Result<()> {
+        match self.index.get() {
+            Some(index) => store::write_index(path, index),
+            None => Ok(()),
+        }
+    }
+    
+    /// Uses note vectors saved at `path` by `save_vectors`, embedding only
+    /// notes changed since they were written. Both happen on a background
+    /// thread; see `is_embedding`.
+    pub fn load_vectors(&mut self, path: &Path, notes: Arc<[Note]>) {
+        self.semantic.load(path.to_path_buf(), notes);
+        self.revision += 1;
+    }
+    
+    /// Whether note vectors are still being loaded. Related notes and
+    /// semantic search wait for them until then.
+    pub fn is_embedding(&self) -> bool {
+        self.semantic.is_loading()
+    }
+    
+    pub fn save_vectors(&self, path: &Path) -> anyhow::Result<()> {
+        self.semantic.save(path)
+    }
+    
+    /// Swaps the model behind related notes and semantic search, embedding
+    /// every note again with it.
+    pub fn set_embedding_model(&mut self, model: Box<dyn EmbeddingModel>) {
+        self.semantic.set_model(model);
+        self.index();
+        let index = self.index.get().expect("search index is loaded");
+        for note in index.notes.values() {
+            self.semantic.embed_note(&note.id, &note.title, &note.content, note.modified_at);
+        }
+        self.revision += 1;
+    }
+    
+    /// Whether the index has been loaded or built yet.
+    pub fn is_loaded(&self) -> bool {
+        self.index.get().is_some()
+    }
+    
+    /// How the saved index was brought up to date, once it has been loaded.
+    pub fn load_report(&self) -> Option<&IndexLoadReport> {
+        self.load_report.get()
+    }
+    
+    /// Re-indexes and re-embeds a single note; trashed notes are dropped from the index.
+    pub fn update_note(&mut self, note: &Note) {
+        self.semantic.update_note(note);
+        self.index_mut().remove_note(&note.id);
+        if !note.is_deleted {
+            let index = self.index.get_mut().expect("search index is loaded");
+            index.add_note(note, &self.link_processor);
+        }
+        self.revision += 1;
+    }
+    
+    pub fn remove_note(&mut self, note_id: &str) {
+        self.semantic.remove_note(note_id);
+        self.index_mut().remove_note(note_id);
+        self.revision += 1;
+    }
+    
+    /// Folder names used by `folder:` queries.
+    pub fn set_folders<'a>(&mut self, folders: impl IntoIterator<Item = &'a Folder>) {
+        let folders = folders
+            .into_iter()
+            .map(|folder| (folder.id.clone(), folder.name.clone()))
+            .collect();
+        // Don't force a pending load just to set folder names
+        if self.index.get().is_none() {
+            if let Ok(Some(pending)) = self.pending.get_mut() {
+                pending.folders = folders;
+                self.revision += 1;
+                return;
+            }
+        }
+        self.index_mut().folders = folders;
+        self.revision += 1;
+    }
+    
+    fn index(&self) -> &SearchIndex {
+        self.index.get_or_init(|| {
+            let pending = self.pending.lock().ok().and_then(|mut pending| pending.take());
+            match pending.map(|pending| (pending.loading.join(), pending.folders)) {
+                Some((Ok((mut index, report)), folders)) => {
+                    index.folders = folders;
+                    let _ = self.load_report.set(report);
+                    index
+                }
+                Some((Err(_), _)) => {
+                    log::error!("Loading the search index failed; starting with an empty one");
+                    SearchIndex::new(self.tokenizer.clone())
+                }
+                None => SearchIndex::new(self.tokenizer.clone()),
+            }
+        })
+    }
+    
+    fn index_mut(&mut self) -> &mut SearchIndex {
+        self.index();
+        self.index.get_mut().expect("search index is loaded")
+    }
+    
+    /// Bumped whenever the index changes, so callers can cache results.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+    
+    /// Evaluates a ```query block against the index. The filter narrows
+    /// notes through the word and tag indexes like any other query.
+    pub fn run_query(&self, block: &QueryBlock) -> QueryResult {
+        let index = self.index();
+        let mut notes: Vec<&IndexedNote> = match block.filter.as_ref().map(QueryPlan::compile) {
+            Some(Ok(plan)) => plan
+                .candidates(index)
+                .into_iter()
+                .filter(|note| plan.evaluate_note(note, index).is_some())
+                .collect(),
+            Some(Err(e)) => {
+                log::warn!("Could not compile query block: {}", e);
+                Vec::new()
+            }
+            None => index.notes.values().collect(),
+        };
+        
+        match &block.sort {
+            // Dates compare as timestamps rather than their display text
+            Some((field, order)) if field == "modified" || field == "created" => {
+                notes.sort_by_key(|note| if field == "modified" { note.modified_at } else { note.created_at });
+                if *order == SortOrder::Desc {
+                    notes.reverse();
+                }
+            }
+            Some((field, order)) => notes.sort_by(|a, b| {
+                compare_field_values(&a.field(field).unwrap_or_default(), &b.field(field).unwrap_or_default(), *order)
+                    .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+            }),
+            None => notes.sort_by_key(|a| a.title.to_lowercase()),
+        }
+        
+        if let Some(limit) = block.limit {
+            notes.truncate(limit);
+        }
+        
+        let columns = block.columns();
+        let rows = notes
+            .into_iter()
+            .map(|note| QueryRow {
+                note_id: note.id.clone(),
+                title: note.title.clone(),
+                values: columns.iter().map(|column| note.field(column).unwrap_or_default()).collect(),
+            })
+            .collect();
+        
+        QueryResult {
+            format: block.format,
+            columns,
+            rows,
+        }
+    }
+    
+    /// Narrows results that were already found to the selected facets and
+    /// counts facets over them, e.g. once a background search completes.
+    pub fn facet_results(&self, results: Vec<SearchResult>, selected: &[Facet], max_results: usize) -> FacetedResults {
+        let index = self.index();
+        let mut results: Vec<SearchResult> = results
+            .into_iter()
+            .filter(|result| {
+                index.notes
+                    .get(&result.note_id)
+                    .is_some_and(|note| selected.iter().all(|facet| facet.matches(note, result)))
+            })
+            .collect();
+        
+        let facets = FacetCounts::count(&results, index);
+        let total = results.len();
+        results.truncate(max_results);
+        FacetedResults { results, total, facets }
+    }
+    
+    pub fn run_saved_search(&self, search: &SavedSearch, max_results: usize) -> Vec<SearchResult> {
+        match &search.query {
+            SavedQuery::Text(query) => self.search(query, max_results),
+            SavedQuery::Filter(filter) => self.advanced_search(filter, max_results),
+        }
+    }
+    
+    /// Runs a query in the search box language (see [`SearchQuery`]). A
+    /// single word is ranked with fuzzy matching; queries that don't parse
+    /// return nothing.
+    pub fn search(&self, query: &str, max_results: usize) -> Vec<SearchResult> {
+        if query.trim().is_empty() {
+            return Vec::new();
+        }
+        
+        match SearchQuery::parse(query) {
+            Ok(parsed) if parsed.is_plain_term() => self.search_text(query.trim(), max_results),
+            Ok(parsed) => self.execute(&parsed, max_results),
+            Err(_) => Vec::new(),
+        }
+    }
+    
+    /// Evaluates a parsed query against the index, best matches first.
+    pub fn execute(&self, query: &SearchQuery, max_results: usize) -> Vec<SearchResult> {
+        self.execute_at(query, max_results, &Local::now())
+    }
+    
+    /// Like `execute`, with relative dates such as `last-7-days` counted from
+    /// `now` and days starting at midnight in its time zone.
+    pub fn execute_at<Tz: TimeZone>(&self, query: &SearchQuery, max_results: usize, now: &DateTime<Tz>) -> Vec<SearchResult> {
+        self.execute_progressive(query, None, max_results, now, || false, |_, _| {})
+            .map(|faceted| faceted.results)
+            .unwrap_or_default()
+    }
+    
+    /// Like `search`, narrowed to the `selected` facets, but notes are checked
+    /// in batches: `progress` gets the best results so far and how many notes
+    /// matched after each batch but the last. Only the top `max_results` are
+    /// kept, while facets are counted over every match. Once `cancelled`
+    /// returns true the search stops and returns `None`.
+    pub fn search_progressive(
+        &self,
+        query: &str,
+        selected: &[Facet],
+        max_results: usize,
+        cancelled: impl Fn() -> bool,
+        progress: impl FnMut(&[SearchResult], usize),
+    ) -> Option<FacetedResults> {
+        if query.trim().is_empty() {
+            return Some(FacetedResults::default());
+        }
+        
+        match SearchQuery::parse(query) {
+            // A single pass over the inverted index, with nothing to split up
+            Ok(parsed) if parsed.is_plain_term() => {
+                let faceted = self.facet_results(self.search_text(query.trim(), usize::MAX), selected, max_results);
+                (!cancelled()).then_some(faceted)
+            }
+            Ok(parsed) => self.execute_progressive(&parsed, Some(selected), max_results, &Local::now(), cancelled, progress),
+            Err(_) => Some(FacetedResults::default()),
+        }
+    }
+    
+    /// Facets are only counted when some are passed in, even none selected.
+    fn execute_progressive<Tz: TimeZone>(
+        &self,
+        query: &SearchQuery,
+        selected: Option<&[Facet]>,
+        max_results: usize,
+        now: &DateTime<Tz>,
+        cancelled: impl Fn() -> bool,
+        mut progress: impl FnMut(&[SearchResult], usize),
+    ) -> Option<FacetedResults> {
+        let plan = match QueryPlan::compile_at(query, now) {
+            Ok(plan) => plan,
+            Err(e) => {
+                log::warn!("Could not compile search query: {}", e);
+                return Some(FacetedResults::default());
+            }
+        };
+        let term_patterns: Vec<Regex> = plan.highlight_terms().iter().filter_map(|term| literal_regex(term)).collect();
+        let index = self.index();
+        let candidates = plan.candidates(index);
+        
+        let mut results = Vec::new();
+        let mut total = 0;
+        let mut tally = selected.map(|_| FacetTally::default());
+        let mut checked = 0;
+        for batch in candidates.chunks(SEARCH_BATCH_SIZE) {
+            if cancelled() {
+                return None;
+            }
+            let found = batch.iter().filter_map(|note| {
+                let hit = plan.evaluate_note(note, index)?;
+                // Text found inside longer words, relations and filters have no
+                // indexed spans, so look for the query's terms directly
+                let mut matches = hit.spans;
+                if matches.is_empty() {
+                    for pattern in &term_patterns {
+                        matches.extend(find_spans(MatchField::Title, &note.title, pattern));
+                        matches.extend(find_spans(MatchField::Body, &note.content, pattern));
+                    }
+                }
+                
+                let result = SearchResult {
+                    note_id: note.id.clone(),
+                    title: note.title.clone(),
+                    score: hit.score,
+                    match_type: hit.match_type.unwrap_or(MatchType::ContentExact),
+                    snippet: Snippet::around(&note.content, &matches, 150),
+                    title_highlights: title_highlights(&matches),
+                    matches,
+                };
+                let wanted = match selected {
+                    Some(selected) => selected.iter().all(|facet| facet.matches(note, &result)),
+                    None => true,
+                };
+                wanted.then_some((note, result))
+            });
+            for (note, result) in found {
+                if let Some(tally) = &mut tally {
+                    tally.add(note, &result.match_type);
+                }
+                total += 1;
+                results.push(result);
+            }
+            rank(&mut results, max_results);
+            
+            checked += batch.len();
+            if checked < candidates.len() {
+                progress(&results, total);
+            }
+        }
+        let facets = tally.map(|tally| tally.finish(index)).unwrap_or_default();
+        Some(FacetedResults { results, total, facets })
+    }
+    
+    /// Ranks notes for a single word with BM25 over the inverted index,
+    /// allowing prefixes and typos.
+    fn search_text(&self, query: &str, max_results: usize) -> Vec<SearchResult> {
+        let mut results: Vec<SearchResult> = self.index().terms
+            .search(query, &MatchField::ALL, &self.matcher)
+            .into_iter()
+            .filter_map(|(note_id, hit)| {
+                let note = self.index().notes.get(&note_id)?;
+                Some(SearchResult {
+                    note_id,
+                    title: note.title.clone(),
+                    score: hit.score,
+                    match_type: hit.match_type(),
+                    snippet: Snippet::around(&note.content, &hit.spans, 150),
+                    title_highlights: title_highlights(&hit.spans),
+                    matches: hit.spans,
+                })
+            })
+            .collect();
+        
+        rank(&mut results, max_results);
+        results
+    }
+    
+    pub fn search_by_tag(&self, tag: &str) -> Vec<SearchResult> {
+        if let Some(note_ids) = self.index().tags.get(tag) {
+            note_ids.iter()
+                .filter_map(|note_id| self.index().notes.get(note_id))
+                .map(|note| SearchResult {
+                    note_id: note.id.clone(),
+                    title: note.title.clone(),
+                    score: 100.0,
+                    match_type: MatchType::Tag,
+                    snippet: Snippet {
+                        text: format!("Tagged with: #{}", tag),
+                        highlights: std::iter::once("Tagged with: ".len().."Tagged with: #".len() + tag.len()).collect(),
+                    },
+                    title_highlights: Vec::new(),
+                    matches: Vec::new(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+    
+    pub fn suggest_tags(&self, query: &str, limit: usize) -> Vec<String> {
+        let query_lower = query.to_lowercase();
+        let mut matching_tags: Vec<String> = self.index().tags.keys()
+            .filter(|tag| tag.to_lowercase().contains(&query_lower))
+            .cloned()
+            .collect();
+        
+        matching_tags.sort();
+        matching_tags.truncate(limit);
+        matching_tags
+    }
+    
+    pub fn search_regex(&self, pattern: &str, max_results: usize) -> Vec<SearchResult> {
+        let regex = match Regex::new(pattern) {
+            Ok(r) => r,
+            Err(_) => return Vec::new(), // Invalid regex pattern
+        };
+        
+        let mut results: Vec<SearchResult> = self.index().notes
+            .values()
+            .filter_map(|note| {
+                let mut matches: Vec<MatchSpan> = find_spans(MatchField::Title, &note.title, &regex).collect();
+                let in_title = !matches.is_empty();
+                matches.extend(find_spans(MatchField::Body, &note.content, &regex));
+                if matches.is_empty() {
+                    return None;
+                }
+                
+                Some(SearchResult {
+                    note_id: note.id.clone(),
+                    title: note.title.clone(),
+                    score: if in_title { 100.0 } else { 80.0 },
+                    match_type: MatchType::Regex,
+                    snippet: Snippet::around(&note.content, &matches, 150),
+                    title_highlights: title_highlights(&matches),
+                    matches,
+                })
+            })
+            .collect();
+        
+        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+        results.truncate(max_results);
+        results
+    }
+    
+    /// Notes closest in meaning to `note_id`, by the cosine similarity of
+    /// their vectors.
+    pub fn related_notes(&self, note_id: &str, max_results: usize) -> Vec<SearchResult> {
+        self.semantic_results(self.semantic.similar_to(note_id, MIN_SIMILARITY, max_results))
+    }
+    
+    /// Ranks notes by how close they are in meaning to a natural-language
+    /// query, rather than by the words they share with it.
+    pub fn semantic_search(&self, query: &str, max_results: usize) -> Vec<SearchResult> {
+        if query.trim().is_empty() {
+            return Vec::new();
+        }
+        self.semantic_results(self.semantic.search(query, MIN_QUERY_SIMILARITY, max_results))
+    }
+    
+    fn semantic_results(&self, similar: Vec<(String, f32)>) -> Vec<SearchResult> {
+        similar
+            .into_iter()
+            .filter_map(|(note_id, similarity)| {
+                let note = self.index().notes.get(&note_id)?;
+                Some(SearchResult {
+                    note_id,
+                    title: note.title.clone(),
+                    score: similarity,
+                    match_type: MatchType::Semantic,
+                    snippet: Snippet::around(&note.content, &[], 150),
+                    title_highlights: Vec::new(),
+                    matches: Vec::new(),
+                })
+            })
+            .collect()
+    }
+    
+    /// Notes with a `relation:: [[target]]` link. An empty target matches
+    /// every note using the relation.
+    pub fn search_relation(&self, relation: &str, target: &str, max_results: usize) -> Vec<SearchResult> {
+        let relation = relation.to_lowercase();
+        let target = target.trim().to_lowercase();
+        
+        let pattern = literal_regex(&format!("{}::", relation));
+        let mut results: Vec<SearchResult> = self.index().notes.values()
+            .filter_map(|note| {
+                let targets: Vec<&String> = note.relations.iter()
+                    .filter(|(name, to)| *name == relation && (target.is_empty() || *to == target))
+                    .map(|(_, to)| to)
+                    .collect();
+                if targets.is_empty() {
+                    return None;
+                }
+                
+                let matches: Vec<MatchSpan> = pattern
+                    .iter()
+                    .flat_map(|pattern| find_spans(MatchField::Body, &note.content, pattern))
+                    .collect();
+                Some(SearchResult {
+                    note_id: note.id.clone(),
+                    title: note.title.clone(),
+                    score: 90.0,
+                    match_type: MatchType::Relation,
+                    snippet: Snippet::around(&note.content, &matches, 150),
+                    title_highlights: Vec::new(),
+                    matches,
+                })
+            })
+            .collect();
+        
+        results.sort_by(|a, b| a.title.cmp(&b.title));
+        results.truncate(max_results);
+        results
+    }
+    
+    pub fn advanced_search(&self, filter: &SearchFilter, max_results: usize) -> Vec<SearchResult> {
+        let mut results = Vec::new();
+        
+        for note in self.index().notes.values() {
+            let mut score = 0.0;
+            let mut matches = true;
+            let mut spans = Vec::new();
+            
+            // Apply text query filter
+            if let Some(query) = literal_regex(&filter.query) {
+                let mut text_match = false;
+                
+                if filter.include_titles && query.is_match(&note.title) {
+                    score += 100.0;
+                    text_match = true;
+                    spans.extend(find_spans(MatchField::Title, &note.title, &query));
+                }
+                
+                if filter.include_content && query.is_match(&note.content) {
+                    score += 80.0;
+                    text_match = true;
+                    spans.extend(find_spans(MatchField::Body, &note.content, &query));
+                }
+                
+                if !text_match {
+                    matches = false;
+                }
+            }
+            
+            // Apply tag filters
+            if !filter.tags.is_empty() {
+                let note_tags: Vec<&str> = note.tags.iter().map(|s| s.as_str()).collect();
+                
+                let tag_match = if filter.match_any_tag {
+                    filter.tags.iter().any(|tag| note_tags.contains(&tag.as_str()))
+                } else {
+                    filter.tags.iter().all(|tag| note_tags.contains(&tag.as_str()))
+                };
+                
+                if !tag_match {
+                    matches = false;
+                } else {
+                    score += 50.0;
+                }
+            }
+            
+            // Apply regex filter
+            if let Some(ref pattern) = filter.regex_pattern {
+                if let Ok(regex) = Regex::new(pattern) {
+                    if !regex.is_match(&note.content) && !regex.is_match(&note.title) {
+                        matches = false;
+                    } else {
+                        score += 90.0;
+                        spans.extend(find_spans(MatchField::Title, &note.title, &regex));
+                        spans.extend(find_spans(MatchField::Body, &note.content, &regex));
+                    }
+                }
+            }
+            
+            // Date bounds are inclusive and apply to the last modification
+            if filter.date_from.is_some_and(|from| note.modified_at < from)
+                || filter.date_to.is_some_and(|to| note.modified_at > to)
+            {
+                matches = false;
+            }
+            
+            if matches {
+                results.push(SearchResult {
+                    note_id: note.id.clone(),
+                    title: note.title.clone(),
+                    score,
+                    match_type: MatchType::ContentExact,
+                    snippet: Snippet::around(&note.content, &spans, 150),
+                    title_highlights: title_highlights(&spans),
+                    matches: spans,
+                });
+            }
+        }
+        
+        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+        results.truncate(max_results);
+        results
+    }
+}
+
+impl Snippet {
+    /// Up to about `max_length` bytes of `content` around its first match,
+    /// highlighting every match that falls inside. Without matches in the
+    /// content it is the start of the note.
+    fn around(content: &str, spans: &[MatchSpan], max_length: usize) -> Self {
+        let mut spans: Vec<&MatchSpan> = spans.iter().filter(|span| MatchField::CONTENT.contains(&span.field)).collect();
+        spans.sort_by_key(|span| span.start);
+        
+        let from = spans.first().map_or(0, |first| floor_char_boundary(content, first.start.saturating_sub(50)));
+        let limit = match spans.first() {
+            Some(first) => (first.end + 50).min(from + max_length.saturating_sub(6)),
+            None => max_length,
+        };
+        let to = floor_char_boundary(content, limit).max(from);
+        
+        let prefix = if from > 0 { "..." } else { "" };
+        let suffix = if to < content.len() { "..." } else { "" };
+        let highlights = spans
+            .iter()
+            .filter(|span| span.start >= from && span.start < to)
+            .map(|span| span.start - from + prefix.len()..span.end.min(to) - from + prefix.len())
+            .collect();
+        
+        Self {
+            text: format!("{}{}{}", prefix, &content[from..to], suffix),
+            highlights,
+        }
+    }
+}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Best first, ties by title, keeping the top `max_results`.
+fn rank(results: &mut Vec<SearchResult>, max_results: usize) {
+    results.sort_by(|a, b| {
+        b.score.partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+    });
+    results.truncate(max_results);
+}
+
+/// Matches `text` literally, ignoring case.
+fn literal_regex(text: &str) -> Option<Regex> {
+    if text.is_empty() {
+        return None;
+    }
+    Regex::new(&format!("(?i){}", regex::escape(text))).ok()
+}
+
+fn find_spans<'a>(field: MatchField, text: &'a str, regex: &'a Regex) -> impl Iterator<Item = MatchSpan> + 'a {
+    regex
+        .find_iter(text)
+        .filter(|found| !found.as_str().is_empty())
+        .map(move |found| MatchSpan::locate(field, text, found.start(), found.end()))
+}
+
+/// Sorted, non-overlapping byte ranges of the title matches.
+fn title_highlights(spans: &[MatchSpan]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = spans.iter().filter(|span| span.field == MatchField::Title).map(MatchSpan::range).collect();
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+impl SearchIndex {
+    fn new(tokenizer: TokenizerConfig) -> Self {
+        Self {
+            notes: HashMap::new(),
+            tags: HashMap::new(),
+            terms: InvertedIndex::new(tokenizer),
+            folders: HashMap::new(),
+        }
+    }
+    
+    /// Reads a saved index, or starts empty if it can't be used or was built
+    /// with other tokenizer settings, then brings it up to date with the notes.
+    /// Unchanged notes keep their saved postings and are not tokenized again.
+    fn load(path: &Path, notes: Arc<[Note]>, tokenizer: &TokenizerConfig) -> (Self, IndexLoadReport) {
+        let saved = match store::read_index(path) {
+            Ok(Some(saved)) if saved.terms.tokenizer().config() != tokenizer => {
+                log::info!("Rebuilding search index {}: tokenizer settings changed", path.display());
+                None
+            }
+            Ok(saved) => saved,
+            Err(e) => {
+                log::warn!("Rebuilding search index {}: {}", path.display(), e);
+                None
+            }
+        };
+        let mut report = IndexLoadReport { from_disk: saved.is_some(), ..IndexLoadReport::default() };
+        let mut index = Self::new(tokenizer.clone());
+        let mut modified = HashMap::new();
+        if let Some(saved) = saved {
+            index.terms = saved.terms;
+            modified = saved.modified;
+        }
+        
+        let link_processor = LinkProcessor::new();
+        for note in notes.iter().filter(|note| !note.is_deleted) {
+            match modified.remove(&note.id) {
+                Some(modified_at) if modified_at == note.modified_at => {
+                    let indexed_note = IndexedNote::new(note, &link_processor);
+                    index.index_tags(&indexed_note);
+                    index.notes.insert(note.id.clone(), indexed_note);
+                    report.reused += 1;
+                }
+                _ => {
+                    index.add_note(note, &link_processor);
+                    report.reindexed += 1;
+                }
+            }
+        }
+        // Whatever is left was deleted or trashed since the index was saved
+        for note_id in modified.keys() {
+            index.terms.remove_note(note_id);
+            report.dropped += 1;
+        }
+        
+        log::info!(
+            "Search index loaded: {} notes reused, {} re-indexed, {} dropped",
+            report.reused, report.reindexed, report.dropped
+        );
+        (index, report)
+    }
+    
+    /// Re-indexes notes modified since they were indexed and drops notes that
+    /// are gone or trashed.
+    fn sync(&mut self, notes: &[Note], link_processor: &LinkProcessor) -> IndexLoadReport {
+        let mut report = IndexLoadReport::default();
+        let live: HashSet<&str> = notes.iter().filter(|note| !note.is_deleted).map(|note| note.id.as_str()).collect();
+        
+        let gone: Vec<String> = self.notes.keys().filter(|id| !live.contains(id.as_str())).cloned().collect();
+        for note_id in gone {
+            self.remove_note(&note_id);
+            report.dropped += 1;
+        }
+        
+        for note in notes.iter().filter(|note| !note.is_deleted) {
+            match self.notes.get(&note.id) {
+                Some(indexed) if indexed.modified_at == note.modified_at => report.reused += 1,
+                _ => {
+                    self.remove_note(&note.id);
+                    self.add_note(note, link_processor);
+                    report.reindexed += 1;
+                }
+            }
+        }
+        report
+    }
+    
+    fn clear(&mut self) {
+        self.notes.clear();
+        self.tags.clear();
+        self.terms.clear();
+    }
+    
+    fn add_note(&mut self, note: &Note, link_processor: &LinkProcessor) {
+        let indexed_note = IndexedNote::new(note, link_processor);
+        self.index_tags(&indexed_note);
+        self.terms.add_note(&indexed_note);
+        self.notes.insert(note.id.clone(), indexed_note);
+    }
+    
+    /// Adds a note to the tag index, including `tags:` from frontmatter.
+    fn index_tags(&mut self, note: &IndexedNote) {
+        for tag in note.all_tags() {
+            let note_ids = self.tags.entry(tag).or_default();
+            if !note_ids.contains(&note.id) {
+                note_ids.push(note.id.clone());
+            }
+        }
+    }
+    
+    fn remove_note(&mut self, note_id: &str) {
+        if self.notes.remove(note_id).is_none() {
+            return;
+        }
+        
+        for note_ids in self.tags.values_mut() {
+            note_ids.retain(|id| id != note_id);
+        }
+        self.tags.retain(|_, note_ids| !note_ids.is_empty());
+        
+        self.terms.remove_note(note_id);
+    }
+}
+
+impl IndexedNote {
+    fn new(note: &Note, link_processor: &LinkProcessor) -> Self {
+        Self {
+            id: note.id.clone(),
+            title: note.title.clone(),
+            content: note.content.clone(),
+            tags: note.tags.clone(),
+            word_count: note.content.split_whitespace().count(),
+            relations: link_processor.extract_note_links(&note.id, &note.content)
+                .into_iter()
+                .filter_map(|link| Some((link.relation?, link.target_title.to_lowercase())))
+                .collect(),
+            properties: note.properties()
+                .into_iter()
+                .map(|(key, value)| (key.to_lowercase(), value))
+                .collect(),
+            folder_id: note.folder_id.clone(),
+            created_at: note.created_at,
+            modified_at: note.modified_at,
+        }
+    }
+    
+    /// Note tags followed by any `tags:` listed in the frontmatter.
+    fn all_tags(&self) -> Vec<String> {
+        let mut tags = self.tags.clone();
+        for tag in self.properties.get("tags").map(|value| parse_list_property(value)).unwrap_or_default() {
+            let tag = tag.trim_start_matches('#').to_string();
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+    
+    /// Display value of a property or built-in field such as `title` or
+    /// `modified`, for query block columns and sorting.
+    fn field(&self, name: &str) -> Option<String> {
+        match name {
+            "title" => Some(self.title.clone()),
+            "created" => Some(self.created_at.format("%Y-%m-%d %H:%M").to_string()),
+            "modified" => Some(self.modified_at.format("%Y-%m-%d %H:%M").to_string()),
+            "words" => Some(self.word_count.to_string()),
+            "tags" | "tag" if !self.tags.is_empty() => Some(self.tags.join(", ")),
+            _ => self.properties.get(name).cloned(),
+        }
+    }
+}
+
+impl Default for SearchEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,968 @@
+use chrono::{DateTime, Datelike, Days, Duration, Local, Months, NaiveDate, TimeZone, Utc};
+use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use super::{IndexedNote, MatchField, MatchSpan, MatchType, SearchIndex};
+use crate::features::parse_list_property;
+use crate::features::query_blocks::{compare_field_values, SortOrder};
+
+/// A parsed search box query, e.g.
+/// `title:meeting (tag:work OR tag:urgent) -"draft" modified:>=2024-01-01`.
+///
+/// Dates may also be relative (`today`, `this-month`, `last-7-days`); they
+/// are resolved in the local time zone when the query is compiled.
+///
+/// Words next to each other must all match; `OR` binds looser than `AND`,
+/// and `NOT`/`-` binds tightest.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchQuery {
+    Term(String),   // Bare word, matched against title, content and tags
+    Phrase(String), // "quoted phrase"
+    Regex(String),  // /pattern/ over title and content
+    Field {
+        field: SearchField,
+        op: CompareOp,
+        value: FieldValue,
+    },
+    Relation {
+        name: String,
+        target: String, // Empty for any target
+    },
+    Not(Box<SearchQuery>),
+    And(Vec<SearchQuery>),
+    Or(Vec<SearchQuery>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchField {
+    Title,
+    Content,
+    Tag,
+    Folder,
+    Created,
+    Modified,
+    Property(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Matches, // field:value
+    Eq,      // field:=value
+    Ne,      // field:!=value
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Text(String),
+    Regex(String),
+}
+
+/// Where a query stopped making sense; `position` is a character offset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryParseError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl fmt::Display for QueryParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at column {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for QueryParseError {}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Result<Self, QueryParseError> {
+        let tokens = Lexer::new(input).tokenize()?;
+        if tokens.is_empty() {
+            return Err(QueryParseError { message: "Empty query".to_string(), position: 0 });
+        }
+        
+        let mut parser = Parser { tokens: &tokens, position: 0, end: input.chars().count() };
+        let query = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(query),
+            Some(Spanned { token: Token::RParen, position }) => Err(error("Unmatched ')'", *position)),
+            Some(Spanned { position, .. }) => Err(error("Unexpected input", *position)),
+        }
+    }
+    
+    /// A single bare word, which the search box ranks with fuzzy matching.
+    pub fn is_plain_term(&self) -> bool {
+        matches!(self, SearchQuery::Term(_))
+    }
+}
+
+impl SearchField {
+    fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "title" => SearchField::Title,
+            "content" | "body" => SearchField::Content,
+            "tag" | "tags" => SearchField::Tag,
+            "folder" => SearchField::Folder,
+            "created" => SearchField::Created,
+            "modified" | "updated" | "date" => SearchField::Modified,
+            other => SearchField::Property(other.to_string()),
+        }
+    }
+    
+    fn is_date(&self) -> bool {
+        matches!(self, SearchField::Created | SearchField::Modified)
+    }
+}
+
+fn error(message: &str, position: usize) -> QueryParseError {
+    QueryParseError { message: message.to_string(), position }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Term(String),
+    Phrase(String),
+    Regex(String),
+    Field(SearchField, CompareOp, FieldValue),
+    Relation(String, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Spanned {
+    token: Token,
+    position: usize,
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Lexer {
+    fn new(input: &str) -> Self {
+        Self { chars: input.chars().collect(), position: 0 }
+    }
+    
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+    
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).copied()
+    }
+    
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars().enumerate().all(|(i, c)| self.peek_at(i) == Some(c))
+    }
+    
+    fn tokenize(mut self) -> Result<Vec<Spanned>, QueryParseError> {
+        let mut tokens = Vec::new();
+        
+        while let Some(c) = self.peek() {
+            let start = self.position;
+            let token = match c {
+                c if c.is_whitespace() => {
+                    self.position += 1;
+                    continue;
+                }
+                '(' => {
+                    self.position += 1;
+                    Token::LParen
+                }
+                ')' => {
+                    self.position += 1;
+                    Token::RParen
+                }
+                '"' => Token::Phrase(self.read_quoted()?),
+                '/' => Token::Regex(self.read_regex()?),
+                '-' if self.peek_at(1).is_some_and(|next| !next.is_whitespace() && next != ')') => {
+                    self.position += 1;
+                    Token::Not
+                }
+                _ => self.read_word()?,
+            };
+            tokens.push(Spanned { token, position: start });
+        }
+        
+        Ok(tokens)
+    }
+    
+    fn read_quoted(&mut self) -> Result<String, QueryParseError> {
+        let start = self.position;
+        self.position += 1;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.position += 1;
+            match c {
+                '"' => return Ok(text),
+                '\\' if self.peek() == Some('"') => {
+                    text.push('"');
+                    self.position += 1;
+                }
+                c => text.push(c),
+            }
+        }
+        Err(error("Unterminated quote", start))
+    }
+    
+    fn read_regex(&mut self) -> Result<String, QueryParseError> {
+        let start = self.position;
+        self.position += 1;
+        let mut pattern = String::new();
+        while let Some(c) = self.peek() {
+            self.position += 1;
+            match c {
+                '/' => {
+                    if pattern.is_empty() {
+                        return Err(error("Empty regex", start));
+                    }
+                    return Regex::new(&pattern)
+                        .map(|_| pattern)
+                        .map_err(|e| error(&format!("Invalid regex: {}", e.to_string().lines().last().unwrap_or_default()), start));
+                }
+                '\\' if self.peek() == Some('/') => {
+                    pattern.push('/');
+                    self.position += 1;
+                }
+                c => pattern.push(c),
+            }
+        }
+        Err(error("Unterminated regex", start))
+    }
+    
+    /// Reads up to the next space or parenthesis; `[[links with spaces]]` stay whole.
+    fn read_plain(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == '(' || c == ')' {
+                break;
+            }
+            if self.starts_with("[[") {
+                while let Some(c) = self.peek() {
+                    text.push(c);
+                    self.position += 1;
+                    if text.ends_with("]]") {
+                        break;
+                    }
+                }
+                continue;
+            }
+            text.push(c);
+            self.position += 1;
+        }
+        text
+    }
+    
+    fn read_word(&mut self) -> Result<Token, QueryParseError> {
+        let start = self.position;
+        
+        // Field prefixes and relations start with a name: `title:`, `blocked-by::`
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_alphanumeric() || *c == '_' || *c == '-' || *c == '.') {
+            name.push(c);
+            self.position += 1;
+        }
+        let is_name = name.chars().next().is_some_and(|c| c.is_alphabetic());
+        
+        if is_name && self.starts_with("::") {
+            self.position += 2;
+            return Ok(Token::Relation(name.to_lowercase(), self.read_relation_target()?));
+        }
+        
+        if is_name {
+            let had_colon = self.peek() == Some(':');
+            if had_colon {
+                self.position += 1;
+            }
+            let op = self.read_operator();
+            if had_colon || op.is_some() {
+                let value_start = self.position;
+                match self.read_field_value(op)? {
+                    // `https://...` is a word, not a field
+                    Some(FieldValue::Text(text)) if text.starts_with("//") => {}
+                    Some(value) => return field_token(&name, op.unwrap_or(CompareOp::Matches), value, value_start),
+                    // A trailing colon is only a mistake after a known field
+                    None if !matches!(SearchField::from_name(&name), SearchField::Property(property) if property != "property" && property != "prop") => {
+                        return Err(error(&format!("Missing value after '{}:'", name), start));
+                    }
+                    None => {}
+                }
+            }
+        }
+        
+        // A plain word after all
+        self.position = start;
+        let word = self.read_plain();
+        Ok(match word.as_str() {
+            "AND" => Token::And,
+            "OR" => Token::Or,
+            "NOT" => Token::Not,
+            _ => Token::Term(word),
+        })
+    }
+    
+    fn read_operator(&mut self) -> Option<CompareOp> {
+        for (text, op) in [(">=", CompareOp::Ge), ("<=", CompareOp::Le), ("!=", CompareOp::Ne), (">", CompareOp::Gt), ("<", CompareOp::Lt), ("=", CompareOp::Eq)] {
+            if self.starts_with(text) {
+                self.position += text.chars().count();
+                return Some(op);
+            }
+        }
+        None
+    }
+    
+    fn read_field_value(&mut self, op: Option<CompareOp>) -> Result<Option<FieldValue>, QueryParseError> {
+        match self.peek() {
+            Some('"') => Ok(Some(FieldValue::Text(self.read_quoted()?))),
+            Some('/') if op.is_none() && self.peek_at(1) != Some('/') => Ok(Some(FieldValue::Regex(self.read_regex()?))),
+            _ => {
+                let value = self.read_plain();
+                Ok(if value.is_empty() { None } else { Some(FieldValue::Text(value)) })
+            }
+        }
+    }
+    
+    /// `[[Target]]`, `"Target"` or a plain word; may follow `::` after a space.
+    fn read_relation_target(&mut self) -> Result<String, QueryParseError> {
+        let mut lookahead = self.position;
+        while self.chars.get(lookahead).is_some_and(|c| *c == ' ') {
+            lookahead += 1;
+        }
+        let next = self.chars.get(lookahead).copied();
+        let target = if next == Some('"') || (next == Some('[') && self.chars.get(lookahead + 1) == Some(&'[')) {
+            self.position = lookahead;
+            if next == Some('"') { self.read_quoted()? } else { self.read_plain() }
+        } else {
+            self.read_plain()
+        };
+        
+        let target = target.trim().trim_start_matches("[[").trim_end_matches("]]").trim_matches('"');
+        let target = target.split(['|', '#']).next().unwrap_or(target).trim();
+        Ok(target.to_lowercase())
+    }
+}
+
+fn field_token(name: &str, op: CompareOp, value: FieldValue, value_start: usize) -> Result<Token, QueryParseError> {
+    let mut field = SearchField::from_name(name);
+    let mut op = op;
+    let mut value = value;
+    
+    // `property:status=active` names the property inside the value
+    if field == SearchField::Property("property".to_string()) || field == SearchField::Property("prop".to_string()) {
+        let text = match &value {
+            FieldValue::Text(text) => text.clone(),
+            FieldValue::Regex(_) => return Err(error("Expected property:name=value", value_start)),
+        };
+        let mut inner = Lexer::new(&text);
+        let mut property = String::new();
+        while let Some(c) = inner.peek().filter(|c| c.is_alphanumeric() || *c == '_' || *c == '-' || *c == '.') {
+            property.push(c);
+            inner.position += 1;
+        }
+        let had_colon = inner.peek() == Some(':');
+        if had_colon {
+            inner.position += 1;
+        }
+        let inner_op = inner.read_operator();
+        let rest: String = inner.chars[inner.position..].iter().collect();
+        if property.is_empty() || rest.is_empty() || (!had_colon && inner_op.is_none()) {
+            return Err(error("Expected property:name=value", value_start));
+        }
+        field = SearchField::Property(property.to_lowercase());
+        op = inner_op.unwrap_or(CompareOp::Matches);
+        value = FieldValue::Text(rest);
+    }
+    
+    if matches!(value, FieldValue::Regex(_)) && !matches!(field, SearchField::Title | SearchField::Content) {
+        return Err(error("Regex values only work with title: and content:", value_start));
+    }
+    
+    if let SearchField::Tag = field {
+        if let FieldValue::Text(text) = &value {
+            value = FieldValue::Text(text.trim_start_matches('#').to_string());
+        }
+    }
+    
+    if field.is_date() {
+        match &value {
+            FieldValue::Text(text) if parse_date_range(text).is_some() => {}
+            _ => return Err(error(
+                "Invalid date (expected YYYY, YYYY-MM, YYYY-MM-DD, today, this-week, last-7-days or a range like 2024-01..2024-03)",
+                value_start,
+            )),
+        }
+        // Ranges only make sense with `:`, `=` and `!=`
+        let is_range = matches!(&value, FieldValue::Text(text) if text.contains(".."));
+        if is_range && !matches!(op, CompareOp::Matches | CompareOp::Eq | CompareOp::Ne) {
+            return Err(error("Comparisons take a single date, not a range", value_start));
+        }
+    }
+    
+    Ok(Token::Field(field, op, value))
+}
+
+/// A date as written in a query. Relative dates only become days once the
+/// plan is compiled against "today".
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DateSpec {
+    Span(NaiveDate, NaiveDate), // First and last day of `2024`, `2024-03` or `2024-03-15`
+    Calendar(Period, i32),      // `today`, `last-week`, `this-month`: whole periods, 0 is the current one
+    Trailing(u32, Period),      // `last-7-days`: the N periods up to and including today
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Period {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// A single date or a `from..to` range of them (either end may be left open).
+fn parse_date_range(text: &str) -> Option<(Option<DateSpec>, Option<DateSpec>)> {
+    if let Some((from, to)) = text.split_once("..") {
+        let from = if from.is_empty() { None } else { Some(parse_date_spec(from)?) };
+        let to = if to.is_empty() { None } else { Some(parse_date_spec(to)?) };
+        if from.is_none() && to.is_none() {
+            return None;
+        }
+        return Some((from, to));
+    }
+    parse_date_spec(text).map(|spec| (Some(spec), Some(spec)))
+}
+
+fn parse_date_spec(text: &str) -> Option<DateSpec> {
+    let text = text.to_lowercase();
+    match text.as_str() {
+        "today" => return Some(DateSpec::Calendar(Period::Day, 0)),
+        "yesterday" => return Some(DateSpec::Calendar(Period::Day, -1)),
+        "tomorrow" => return Some(DateSpec::Calendar(Period::Day, 1)),
+        _ => {}
+    }
+    
+    let parts: Vec<&str> = text.split('-').collect();
+    let period = |unit: &str| match unit.trim_end_matches('s') {
+        "day" => Some(Period::Day),
+        "week" => Some(Period::Week),
+        "month" => Some(Period::Month),
+        "year" => Some(Period::Year),
+        _ => None,
+    };
+    match parts.as_slice() {
+        ["this", unit] => return Some(DateSpec::Calendar(period(unit)?, 0)),
+        ["last", unit] => return Some(DateSpec::Calendar(period(unit)?, -1)),
+        ["next", unit] => return Some(DateSpec::Calendar(period(unit)?, 1)),
+        ["last", count, unit] => {
+            let count: u32 = count.parse().ok().filter(|count| *count > 0)?;
+            return Some(DateSpec::Trailing(count, period(unit)?));
+        }
+        _ => {}
+    }
+    
+    let year: i32 = parts.first().filter(|year| year.len() == 4)?.parse().ok()?;
+    match parts.len() {
+        1 => Some(DateSpec::Span(NaiveDate::from_ymd_opt(year, 1, 1)?, NaiveDate::from_ymd_opt(year, 12, 31)?)),
+        2 => {
+            let first = NaiveDate::from_ymd_opt(year, parts[1].parse().ok()?, 1)?;
+            Some(DateSpec::Span(first, first.checked_add_months(Months::new(1))?.pred_opt()?))
+        }
+        3 => {
+            let date = NaiveDate::parse_from_str(&text, "%Y-%m-%d").ok()?;
+            Some(DateSpec::Span(date, date))
+        }
+        _ => None,
+    }
+}
+
+impl DateSpec {
+    /// First and last day covered, counting from `today`, or `None` if that
+    /// falls outside the dates chrono can represent. Weeks start on Monday.
+    fn resolve(self, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        let shift_days = |date: NaiveDate, days: i64| {
+            let by = Days::new(days.unsigned_abs());
+            if days < 0 { date.checked_sub_days(by) } else { date.checked_add_days(by) }
+        };
+        let shift_months = |date: NaiveDate, months: i64| {
+            let by = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+            if months < 0 { date.checked_sub_months(by) } else { date.checked_add_months(by) }
+        };
+        match self {
+            DateSpec::Span(first, last) => Some((first, last)),
+            DateSpec::Calendar(Period::Day, offset) => {
+                let day = shift_days(today, offset.into())?;
+                Some((day, day))
+            }
+            DateSpec::Calendar(Period::Week, offset) => {
+                let this_monday = shift_days(today, -i64::from(today.weekday().num_days_from_monday()))?;
+                let monday = shift_days(this_monday, 7 * i64::from(offset))?;
+                Some((monday, shift_days(monday, 6)?))
+            }
+            DateSpec::Calendar(Period::Month, offset) => {
+                let first = shift_months(today.with_day(1)?, offset as i64)?;
+                Some((first, first.checked_add_months(Months::new(1))?.pred_opt()?))
+            }
+            DateSpec::Calendar(Period::Year, offset) => {
+                let year = today.year().checked_add(offset)?;
+                Some((NaiveDate::from_ymd_opt(year, 1, 1)?, NaiveDate::from_ymd_opt(year, 12, 31)?))
+            }
+            DateSpec::Trailing(count, period) => {
+                let before = match period {
+                    Period::Day => today.checked_sub_days(Days::new(count.into()))?,
+                    Period::Week => today.checked_sub_days(Days::new(7 * u64::from(count)))?,
+                    Period::Month => shift_months(today, -i64::from(count))?,
+                    Period::Year => shift_months(today, -12 * i64::from(count))?,
+                };
+                Some((before.succ_opt()?, today))
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Spanned],
+    position: usize,
+    end: usize, // Input length, for errors at the end of the query
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Spanned> {
+        self.tokens.get(self.position)
+    }
+    
+    fn parse_or(&mut self) -> Result<SearchQuery, QueryParseError> {
+        let mut terms = vec![self.parse_and()?];
+        while let Some(Spanned { token: Token::Or, .. }) = self.peek() {
+            self.position += 1;
+            terms.push(self.parse_and()?);
+        }
+        Ok(if terms.len() == 1 { terms.pop().unwrap() } else { SearchQuery::Or(terms) })
+    }
+    
+    fn parse_and(&mut self) -> Result<SearchQuery, QueryParseError> {
+        let mut terms = vec![self.parse_unary()?];
+        loop {
+            match self.peek().map(|spanned| &spanned.token) {
+                Some(Token::And) => {
+                    self.position += 1;
+                    terms.push(self.parse_unary()?);
+                }
+                Some(Token::Or) | Some(Token::RParen) | None => break,
+                Some(_) => terms.push(self.parse_unary()?),
+            }
+        }
+        Ok(if terms.len() == 1 { terms.pop().unwrap() } else { SearchQuery::And(terms) })
+    }
+    
+    fn parse_unary(&mut self) -> Result<SearchQuery, QueryParseError> {
+        let spanned = match self.peek() {
+            Some(spanned) => spanned,
+            None => {
+                let message = match self.position.checked_sub(1).and_then(|i| self.tokens.get(i)).map(|s| &s.token) {
+                    Some(Token::And) => "Expected a search term after AND",
+                    Some(Token::Or) => "Expected a search term after OR",
+                    Some(Token::Not) => "Expected a search term after NOT",
+                    Some(Token::LParen) => "Expected a search term after '('",
+                    _ => "Expected a search term",
+                };
+                return Err(error(message, self.end));
+            }
+        };
+        self.position += 1;
+        
+        match &spanned.token {
+            Token::Not => Ok(SearchQuery::Not(Box::new(self.parse_unary()?))),
+            Token::LParen => {
+                if let Some(Spanned { token: Token::RParen, position }) = self.peek() {
+                    return Err(error("Empty parentheses", *position));
+                }
+                let inner = self.parse_or()?;
+                match self.peek() {
+                    Some(Spanned { token: Token::RParen, .. }) => {
+                        self.position += 1;
+                        Ok(inner)
+                    }
+                    _ => Err(error("Missing ')' to close this '('", spanned.position)),
+                }
+            }
+            Token::RParen => Err(error("Unmatched ')'", spanned.position)),
+            Token::And | Token::Or => Err(error("Expected a search term before this operator", spanned.position)),
+            Token::Term(word) => Ok(SearchQuery::Term(word.clone())),
+            Token::Phrase(phrase) => Ok(SearchQuery::Phrase(phrase.clone())),
+            Token::Regex(pattern) => Ok(SearchQuery::Regex(pattern.clone())),
+            Token::Field(field, op, value) => Ok(SearchQuery::Field {
+                field: field.clone(),
+                op: *op,
+                value: value.clone(),
+            }),
+            Token::Relation(name, target) => Ok(SearchQuery::Relation {
+                name: name.clone(),
+                target: target.clone(),
+            }),
+        }
+    }
+}
+
+/// A query compiled for evaluation: text lowercased, regexes built and
+/// dates resolved to instants.
+#[derive(Debug, Clone)]
+pub struct QueryPlan {
+    root: PlanNode,
+}
+
+#[derive(Debug, Clone)]
+enum PlanNode {
+    Text(String, TextScope),
+    Regex(Regex, TextScope),
+    Compare(PlanField, CompareOp, String),
+    Date {
+        field: SearchField,
+        from: Option<DateTime<Utc>>,  // Inclusive
+        until: Option<DateTime<Utc>>, // Exclusive
+        negate: bool,
+    },
+    Relation(String, String),
+    Not(Box<PlanNode>),
+    And(Vec<PlanNode>),
+    Or(Vec<PlanNode>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextScope {
+    Any, // Title, content or tags
+    Title,
+    Content,
+}
+
+impl TextScope {
+    fn fields(self) -> &'static [MatchField] {
+        match self {
+            TextScope::Any => &MatchField::ALL,
+            TextScope::Title => &[MatchField::Title],
+            TextScope::Content => &MatchField::CONTENT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PlanField {
+    Title,
+    Content,
+    Tag,
+    Folder,
+    Property(String),
+}
+
+/// How well a note matched, and which part of the query it matched on.
+#[derive(Debug, Clone)]
+pub(super) struct Hit {
+    pub(super) score: f32,
+    pub(super) match_type: Option<MatchType>,
+    pub(super) spans: Vec<MatchSpan>,
+}
+
+impl Hit {
+    fn empty() -> Self {
+        Self { score: 0.0, match_type: None, spans: Vec::new() }
+    }
+}
+
+impl QueryPlan {
+    /// Compiles with relative dates counted from now, in the local time zone.
+    pub fn compile(query: &SearchQuery) -> Result<Self, QueryParseError> {
+        Self::compile_at(query, &Local::now())
+    }
+    
+    /// Compiles as if it were `now`: relative dates count from its day, and
+    /// every day starts at midnight in its time zone.
+    pub fn compile_at<Tz: TimeZone>(query: &SearchQuery, now: &DateTime<Tz>) -> Result<Self, QueryParseError> {
+        Ok(Self { root: compile_node(query, now)? })
+    }
+    
+    /// Lowercased words and phrases the query looks for, for snippets and highlighting.
+    pub fn highlight_terms(&self) -> Vec<String> {
+        let mut terms = Vec::new();
+        collect_terms(&self.root, false, &mut terms);
+        terms
+    }
+    
+    /// Notes that still have to be checked one by one: text and tag terms
+    /// narrow them through the word and tag indexes first.
+    pub(super) fn candidates<'a>(&self, index: &'a SearchIndex) -> Vec<&'a IndexedNote> {
+        match candidates(&self.root, index) {
+            Some(ids) => ids.into_iter().filter_map(|id| index.notes.get(id)).collect(),
+            None => index.notes.values().collect(),
+        }
+    }
+    
+    pub(super) fn evaluate_note(&self, note: &IndexedNote, index: &SearchIndex) -> Option<Hit> {
+        evaluate_node(&self.root, note, index)
+    }
+}
+
+fn compile_node<Tz: TimeZone>(query: &SearchQuery, now: &DateTime<Tz>) -> Result<PlanNode, QueryParseError> {
+    let compile_all = |terms: &[SearchQuery]| terms.iter().map(|term| compile_node(term, now)).collect::<Result<_, _>>();
+    Ok(match query {
+        SearchQuery::Term(text) | SearchQuery::Phrase(text) => PlanNode::Text(text.to_lowercase(), TextScope::Any),
+        SearchQuery::Regex(pattern) => PlanNode::Regex(compile_regex(pattern)?, TextScope::Any),
+        SearchQuery::Relation { name, target } => PlanNode::Relation(name.to_lowercase(), target.to_lowercase()),
+        SearchQuery::Not(inner) => PlanNode::Not(Box::new(compile_node(inner, now)?)),
+        SearchQuery::And(terms) => PlanNode::And(compile_all(terms)?),
+        SearchQuery::Or(terms) => PlanNode::Or(compile_all(terms)?),
+        SearchQuery::Field { field, op, value } => {
+            let scope = match field {
+                SearchField::Title => Some(TextScope::Title),
+                SearchField::Content => Some(TextScope::Content),
+                _ => None,
+            };
+            let text = match value {
+                FieldValue::Regex(pattern) => {
+                    return match scope {
+                        Some(scope) => Ok(PlanNode::Regex(compile_regex(pattern)?, scope)),
+                        None => Err(error("Regex values only work with title: and content:", 0)),
+                    };
+                }
+                FieldValue::Text(text) => text,
+            };
+            
+            if field.is_date() {
+                return compile_date(field, *op, text, now);
+            }
+            match (scope, op) {
+                (Some(scope), CompareOp::Matches) => PlanNode::Text(text.to_lowercase(), scope),
+                _ => {
+                    let field = match field {
+                        SearchField::Title => PlanField::Title,
+                        SearchField::Content => PlanField::Content,
+                        SearchField::Tag => PlanField::Tag,
+                        SearchField::Folder => PlanField::Folder,
+                        SearchField::Property(name) => PlanField::Property(name.to_lowercase()),
+                        SearchField::Created | SearchField::Modified => unreachable!(),
+                    };
+                    PlanNode::Compare(field, *op, text.to_lowercase())
+                }
+            }
+        }
+    })
+}
+
+fn compile_regex(pattern: &str) -> Result<Regex, QueryParseError> {
+    // Case-insensitive unless the pattern asks otherwise
+    Regex::new(&format!("(?i){}", pattern))
+        .map_err(|e| error(&format!("Invalid regex: {}", e), 0))
+}
+
+fn compile_date<Tz: TimeZone>(field: &SearchField, op: CompareOp, text: &str, now: &DateTime<Tz>) -> Result<PlanNode, QueryParseError> {
+    let (first, last) = parse_date_range(text).ok_or_else(|| error("Invalid date", 0))?;
+    let today = now.date_naive();
+    let resolve = |spec: DateSpec| spec.resolve(today).ok_or_else(|| error("Date out of range", 0));
+    let first = first.map(|spec| resolve(spec).map(|(first, _)| first)).transpose()?;
+    let last = last.map(|spec| resolve(spec).map(|(_, last)| last)).transpose()?;
+    
+    // Days start at local midnight, which DST can skip; then the day starts an hour later
+    let timezone = now.timezone();
+    let start_of = |date: NaiveDate| {
+        let midnight = date.and_hms_opt(0, 0, 0)?;
+        timezone
+            .from_local_datetime(&midnight)
+            .earliest()
+            .or_else(|| timezone.from_local_datetime(&(midnight + Duration::hours(1))).earliest())
+            .map(|start| start.with_timezone(&Utc))
+    };
+    let from = first.and_then(start_of);
+    let until = last.and_then(|last| start_of(last.succ_opt()?));
+    
+    let (from, until, negate) = match op {
+        CompareOp::Matches | CompareOp::Eq => (from, until, false),
+        CompareOp::Ne => (from, until, true),
+        CompareOp::Gt => (until, None, false),
+        CompareOp::Ge => (from, None, false),
+        CompareOp::Lt => (None, from, false),
+        CompareOp::Le => (None, until, false),
+    };
+    Ok(PlanNode::Date { field: field.clone(), from, until, negate })
+}
+
+fn collect_terms(node: &PlanNode, negated: bool, terms: &mut Vec<String>) {
+    match node {
+        PlanNode::Text(text, _) if !negated => terms.push(text.clone()),
+        PlanNode::Relation(name, _) if !negated => terms.push(format!("{}::", name)),
+        PlanNode::Not(inner) => collect_terms(inner, !negated, terms),
+        PlanNode::And(children) | PlanNode::Or(children) => {
+            for child in children {
+                collect_terms(child, negated, terms);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Notes that could match, from the word and tag indexes; `None` means
+/// every note, as for regexes and negations.
+fn candidates<'a>(node: &PlanNode, index: &'a SearchIndex) -> Option<HashSet<&'a str>> {
+    match node {
+        PlanNode::Text(text, _) | PlanNode::Compare(PlanField::Title | PlanField::Content, CompareOp::Eq, text) => index.terms.candidates(text),
+        PlanNode::Compare(PlanField::Tag, op @ (CompareOp::Matches | CompareOp::Eq), tag) => Some(
+            index.tags
+                .iter()
+                .filter(|(candidate, _)| tag_matches(candidate, tag, *op))
+                .flat_map(|(_, note_ids)| note_ids.iter().map(|id| id.as_str()))
+                .collect(),
+        ),
+        PlanNode::And(children) => children
+            .iter()
+            .filter_map(|child| candidates(child, index))
+            .reduce(|a, b| a.intersection(&b).copied().collect()),
+        PlanNode::Or(children) => {
+            let mut union = HashSet::new();
+            for child in children {
+                union.extend(candidates(child, index)?);
+            }
+            Some(union)
+        }
+        _ => None,
+    }
+}
+
+fn tag_matches(candidate: &str, tag: &str, op: CompareOp) -> bool {
+    let candidate = candidate.trim_start_matches('#').to_lowercase();
+    candidate == tag || (op == CompareOp::Matches && candidate.starts_with(&format!("{}/", tag)))
+}
+
+fn hit(score: f32, match_type: MatchType) -> Option<Hit> {
+    Some(Hit { score, match_type: Some(match_type), spans: Vec::new() })
+}
+
+fn evaluate_node(node: &PlanNode, note: &IndexedNote, index: &SearchIndex) -> Option<Hit> {
+    match node {
+        PlanNode::Text(text, scope) => {
+            // Whole words (by stem) rank by BM25; text found only inside longer words ranks below them
+            if let Some(text_hit) = index.terms.score_note(&note.id, text, scope.fields()) {
+                return Some(Hit { score: text_hit.score, match_type: Some(text_hit.match_type()), spans: text_hit.spans });
+            }
+            
+            let in_title = *scope != TextScope::Content && note.title.to_lowercase().contains(text.as_str());
+            let in_content = *scope != TextScope::Title && note.content.to_lowercase().contains(text.as_str());
+            let in_tags = *scope == TextScope::Any && note.all_tags().iter().any(|tag| tag.to_lowercase().contains(text.as_str()));
+            if in_title {
+                hit(0.3, MatchType::TitleExact)
+            } else if in_content {
+                hit(0.2, MatchType::ContentExact)
+            } else if in_tags {
+                hit(0.2, MatchType::Tag)
+            } else {
+                None
+            }
+        }
+        PlanNode::Regex(regex, scope) => {
+            let mut spans = Vec::new();
+            if *scope != TextScope::Content {
+                spans.extend(regex.find_iter(&note.title).map(|found| MatchSpan::locate(MatchField::Title, &note.title, found.start(), found.end())));
+            }
+            let in_title = !spans.is_empty();
+            if *scope != TextScope::Title {
+                spans.extend(regex.find_iter(&note.content).map(|found| MatchSpan::locate(MatchField::Body, &note.content, found.start(), found.end())));
+            }
+            
+            match (in_title, spans.is_empty()) {
+                (_, true) => None,
+                (true, false) => Some(Hit { score: 2.0, match_type: Some(MatchType::Regex), spans }),
+                (false, false) => Some(Hit { score: 1.0, match_type: Some(MatchType::Regex), spans }),
+            }
+        }
+        PlanNode::Compare(field, op, value) => {
+            let matched = match field {
+                PlanField::Tag => {
+                    let has_tag = note.all_tags().iter().any(|tag| tag_matches(tag, value, *op));
+                    if *op == CompareOp::Ne { !has_tag } else { has_tag }
+                }
+                PlanField::Folder => {
+                    let folder = note.folder_id.as_ref().and_then(|id| index.folders.get(id));
+                    compare_text(folder.map(|name| name.as_str()), *op, value)
+                }
+                PlanField::Title => compare_text(Some(&note.title), *op, value),
+                PlanField::Content => compare_text(Some(&note.content), *op, value),
+                PlanField::Property(name) => {
+                    let property = note.properties.get(name);
+                    match (property, op) {
+                        (Some(_), CompareOp::Matches) if value == "*" => true,
+                        (Some(property), CompareOp::Matches) => parse_list_property(property)
+                            .iter()
+                            .any(|item| item.eq_ignore_ascii_case(value)),
+                        (property, op) => compare_text(property.map(|p| p.as_str()), *op, value),
+                    }
+                }
+            };
+            if !matched {
+                return None;
+            }
+            // Filters rank on a scale comparable with BM25 scores
+            match field {
+                PlanField::Title => hit(1.0, MatchType::TitleExact),
+                PlanField::Tag => hit(0.8, MatchType::Tag),
+                _ => hit(0.5, MatchType::ContentExact),
+            }
+        }
+        PlanNode::Date { field, from, until, negate } => {
+            let at = if *field == SearchField::Created { note.created_at } else { note.modified_at };
+            let inside = from.is_none_or(|from| at >= from) && until.is_none_or(|until| at < until);
+            if inside != *negate { hit(0.5, MatchType::DateRange) } else { None }
+        }
+        PlanNode::Relation(name, target) => note.relations
+            .iter()
+            .any(|(relation, to)| relation == name && (target.is_empty() || to == target))
+            .then(|| Hit { score: 1.0, match_type: Some(MatchType::Relation), spans: Vec::new() }),
+        PlanNode::Not(inner) => match evaluate_node(inner, note, index) {
+            Some(_) => None,
+            None => Some(Hit::empty()),
+        },
+        PlanNode::And(children) => {
+            let mut total = Hit::empty();
+            let mut best = f32::MIN;
+            for child in children {
+                let child_hit = evaluate_node(child, note, index)?;
+                if child_hit.match_type.is_some() && child_hit.score > best {
+                    best = child_hit.score;
+                    total.match_type = child_hit.match_type;
+                }
+                total.score += child_hit.score;
+                total.spans.extend(child_hit.spans);
+            }
+            Some(total)
+        }
+        PlanNode::Or(children) => {
+            // The best alternative sets the score; every matching one is highlighted
+            let hits: Vec<Hit> = children.iter().filter_map(|child| evaluate_node(child, note, index)).collect();
+            let spans: Vec<MatchSpan> = hits.iter().flat_map(|hit| hit.spans.iter().copied()).collect();
+            let mut best = hits.into_iter().max_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(Ordering::Equal))?;
+            best.spans = spans;
+            Some(best)
+        }
+    }
+}
+
+/// `=`/`!=` compare whole values; `<`, `>` compare numbers or text.
+/// A missing value only satisfies `!=`.
+fn compare_text(actual: Option<&str>, op: CompareOp, value: &str) -> bool {
+    let actual = match actual {
+        Some(actual) => actual.trim().to_lowercase(),
+        None => return op == CompareOp::Ne,
+    };
+    let ordering = compare_field_values(&actual, value, SortOrder::Asc);
+    match op {
+        CompareOp::Matches => actual.contains(value),
+        CompareOp::Eq => actual == value,
+        CompareOp::Ne => actual != value,
+        CompareOp::Lt => ordering == Ordering::Less,
+        CompareOp::Le => ordering != Ordering::Greater,
+        CompareOp::Gt => ordering == Ordering::Greater,
+        CompareOp::Ge => ordering != Ordering::Less,
+    }
+}
@@ -0,0 +1,309 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::JoinHandle;
+use crate::features::Note;
+use super::tokenizer::{Tokenizer, TokenizerConfig};
+
+// How much a character trigram counts next to its whole word
+const TRIGRAM_WEIGHT: f32 = 0.3;
+
+/// Turns text into a fixed-length vector; similar texts get vectors with a
+/// high cosine similarity. Models run locally.
+pub trait EmbeddingModel: Send + Sync {
+    /// Saved vectors are only reused by the model that made them.
+    fn id(&self) -> &str;
+    fn dimensions(&self) -> usize;
+    fn embed(&self, text: &str) -> Vec<f32>;
+}
+
+/// The built-in fallback, which is lexical rather than semantic: a hashed
+/// bag of stemmed words plus their character trigrams. Notes sharing words
+/// and word forms end up close; synonyms and paraphrases don't.
+pub struct HashedEmbedding {
+    id: String,
+    dimensions: usize,
+    tokenizer: Tokenizer,
+}
+
+impl HashedEmbedding {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            id: format!("hashed-{}-v1", dimensions),
+            dimensions: dimensions.max(1),
+            tokenizer: Tokenizer::new(TokenizerConfig::default()),
+        }
+    }
+    
+    fn add_feature(&self, vector: &mut [f32], feature: &str, weight: f32) {
+        let hash = fnv1a(feature);
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[(hash % self.dimensions as u64) as usize] += sign * weight;
+    }
+}
+
+impl Default for HashedEmbedding {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl EmbeddingModel for HashedEmbedding {
+    fn id(&self) -> &str {
+        &self.id
+    }
+    
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+    
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let mut counts: HashMap<String, f32> = HashMap::new();
+        for token in self.tokenizer.tokenize(text) {
+            *counts.entry(token.term).or_default() += 1.0;
+        }
+        
+        let mut vector = vec![0.0; self.dimensions];
+        for (term, count) in counts {
+            let weight = 1.0 + count.ln();
+            self.add_feature(&mut vector, &term, weight);
+            let chars: Vec<char> = format!("<{}>", term).chars().collect();
+            for trigram in chars.windows(3) {
+                self.add_feature(&mut vector, &trigram.iter().collect::<String>(), weight * TRIGRAM_WEIGHT);
+            }
+        }
+        
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+}
+
+/// Cosine similarity; 0 for empty or mismatched vectors.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norms = a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norms > 0.0 { dot / norms } else { 0.0 }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NoteVector {
+    modified_at: DateTime<Utc>, // Version of the note the vector was made from
+    vector: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedVectors {
+    model: String,
+    vectors: HashMap<String, NoteVector>,
+}
+
+/// One vector per note, for "similar notes" and natural-language queries.
+pub(super) struct SemanticIndex {
+    model: Arc<dyn EmbeddingModel>,
+    vectors: OnceLock<HashMap<String, NoteVector>>, // Set once a background load has finished
+    pending: Mutex<Option<PendingVectors>>, // Saved vectors being read and brought up to date
+}
+
+/// A background load, with the edits made while it runs. `None` removes the
+/// note's vector.
+struct PendingVectors {
+    loading: JoinHandle<HashMap<String, NoteVector>>,
+    queued: HashMap<String, Option<NoteVector>>,
+}
+
+impl SemanticIndex {
+    pub(super) fn new(model: Box<dyn EmbeddingModel>) -> Self {
+        Self {
+            model: model.into(),
+            vectors: OnceLock::from(HashMap::new()),
+            pending: Mutex::new(None),
+        }
+    }
+    
+    /// Swaps the model; every vector has to be made again.
+    pub(super) fn set_model(&mut self, model: Box<dyn EmbeddingModel>) {
+        self.model = model.into();
+        // A load still running for the old model is left to finish unused
+        self.pending = Mutex::new(None);
+        self.vectors = OnceLock::from(HashMap::new());
+    }
+    
+    /// Whether vectors are still being read or made on a background thread.
+    /// Anything needing them until then waits for it to finish.
+    pub(super) fn is_loading(&self) -> bool {
+        self.vectors.get().is_none()
+            && self.pending.lock().is_ok_and(|pending| pending.as_ref().is_some_and(|pending| !pending.loading.is_finished()))
+    }
+    
+    /// Embeds notes changed since their vector was made and drops notes that
+    /// are gone or trashed. Returns how many notes were embedded.
+    pub(super) fn sync(&mut self, notes: &[Note]) -> usize {
+        let model = Arc::clone(&self.model);
+        embed_changed(model.as_ref(), self.vectors_mut(), notes)
+    }
+    
+    /// Uses vectors saved at `path` unless another model made them, then
+    /// embeds the notes changed since, all on a background thread.
+    pub(super) fn load(&mut self, path: PathBuf, notes: Arc<[Note]>) {
+        let current = std::mem::take(self.vectors_mut());
+        let model = Arc::clone(&self.model);
+        let loading = std::thread::spawn(move || {
+            let mut vectors = match read_vectors(&path, model.id()) {
+                Ok(Some(saved)) => saved,
+                Ok(None) => current,
+                Err(e) => {
+                    log::warn!("Re-embedding notes, can't read {}: {}", path.display(), e);
+                    current
+                }
+            };
+            let embedded = embed_changed(model.as_ref(), &mut vectors, &notes);
+            log::info!("Note vectors loaded: {} of {} embedded", embedded, vectors.len());
+            vectors
+        });
+        self.vectors = OnceLock::new();
+        self.pending = Mutex::new(Some(PendingVectors { loading, queued: HashMap::new() }));
+    }
+    
+    pub(super) fn update_note(&mut self, note: &Note) {
+        if note.is_deleted {
+            self.remove_note(&note.id);
+        } else {
+            self.embed_note(&note.id, &note.title, &note.content, note.modified_at);
+        }
+    }
+    
+    pub(super) fn embed_note(&mut self, note_id: &str, title: &str, content: &str, modified_at: DateTime<Utc>) {
+        let vector = embed(self.model.as_ref(), title, content, modified_at);
+        self.set_vector(note_id, Some(vector));
+    }
+    
+    pub(super) fn remove_note(&mut self, note_id: &str) {
+        self.set_vector(note_id, None);
+    }
+    
+    /// Changes a note's vector, or queues the change until a running load is done.
+    fn set_vector(&mut self, note_id: &str, vector: Option<NoteVector>) {
+        if self.vectors.get().is_none() {
+            if let Some(pending) = self.pending.get_mut().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                pending.queued.insert(note_id.to_string(), vector);
+                return;
+            }
+        }
+        match vector {
+            Some(vector) => self.vectors_mut().insert(note_id.to_string(), vector),
+            None => self.vectors_mut().remove(note_id),
+        };
+    }
+    
+    pub(super) fn save(&self, path: &Path) -> Result<()> {
+        let saved = SavedVectors {
+            model: self.model.id().to_string(),
+            vectors: self.vectors().clone(),
+        };
+        if let Some(dir) = path.parent() {
+            crate::utils::ensure_directory_exists(dir)?;
+        }
+        let temporary = path.with_extension("tmp");
+        crate::utils::write_string_to_file(&temporary, &serde_json::to_string(&saved)?)?;
+        std::fs::rename(&temporary, path)?;
+        Ok(())
+    }
+    
+    /// Notes most similar to `note_id`, best first, with their similarity.
+    pub(super) fn similar_to(&self, note_id: &str, min_similarity: f32, limit: usize) -> Vec<(String, f32)> {
+        match self.vectors().get(note_id) {
+            Some(saved) => self.nearest(&saved.vector, Some(note_id), min_similarity, limit),
+            None => Vec::new(),
+        }
+    }
+    
+    /// Notes closest in meaning to a natural-language query.
+    pub(super) fn search(&self, query: &str, min_similarity: f32, limit: usize) -> Vec<(String, f32)> {
+        self.nearest(&self.model.embed(query), None, min_similarity, limit)
+    }
+    
+    fn nearest(&self, vector: &[f32], exclude: Option<&str>, min_similarity: f32, limit: usize) -> Vec<(String, f32)> {
+        let mut scored: Vec<(String, f32)> = self.vectors()
+            .iter()
+            .filter(|(note_id, _)| Some(note_id.as_str()) != exclude)
+            .map(|(note_id, saved)| (note_id.clone(), cosine_similarity(vector, &saved.vector)))
+            .filter(|(_, similarity)| *similarity >= min_similarity)
+            .collect();
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.0.cmp(&b.0)));
+        scored.truncate(limit);
+        scored
+    }
+    
+    /// The vectors, waiting for a background load to finish first.
+    fn vectors(&self) -> &HashMap<String, NoteVector> {
+        self.vectors.get_or_init(|| {
+            let pending = match self.pending.lock().ok().and_then(|mut pending| pending.take()) {
+                Some(pending) => pending,
+                None => return HashMap::new(),
+            };
+            let mut vectors = pending.loading.join().unwrap_or_else(|_| {
+                log::error!("Embedding notes failed; they are embedded again as they are saved");
+                HashMap::new()
+            });
+            for (note_id, vector) in pending.queued {
+                match vector {
+                    Some(vector) => vectors.insert(note_id, vector),
+                    None => vectors.remove(&note_id),
+                };
+            }
+            vectors
+        })
+    }
+    
+    fn vectors_mut(&mut self) -> &mut HashMap<String, NoteVector> {
+        self.vectors();
+        self.vectors.get_mut().expect("note vectors are loaded")
+    }
+}
+
+fn embed(model: &dyn EmbeddingModel, title: &str, content: &str, modified_at: DateTime<Utc>) -> NoteVector {
+    NoteVector { modified_at, vector: model.embed(&format!("{}\n{}", title, content)) }
+}
+
+/// Embeds the notes whose vector is missing or older than the note and drops
+/// vectors of notes that are gone or trashed. Returns how many were embedded.
+fn embed_changed(model: &dyn EmbeddingModel, vectors: &mut HashMap<String, NoteVector>, notes: &[Note]) -> usize {
+    let live: HashSet<&str> = notes.iter().filter(|note| !note.is_deleted).map(|note| note.id.as_str()).collect();
+    vectors.retain(|note_id, _| live.contains(note_id.as_str()));
+    let mut embedded = 0;
+    for note in notes.iter().filter(|note| !note.is_deleted) {
+        if vectors.get(&note.id).map(|saved| saved.modified_at) != Some(note.modified_at) {
+            vectors.insert(note.id.clone(), embed(model, &note.title, &note.content, note.modified_at));
+            embedded += 1;
+        }
+    }
+    embedded
+}
+
+/// Vectors saved at `path`; `None` if there are none or another model made them.
+fn read_vectors(path: &Path, model: &str) -> Result<Option<HashMap<String, NoteVector>>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let saved: SavedVectors = serde_json::from_str(&crate::utils::read_file_to_string(path)?)?;
+    if saved.model != model {
+        log::info!("Re-embedding notes: {} was written by model {}", path.display(), saved.model);
+        return Ok(None);
+    }
+    Ok(Some(saved.vectors))
+}
+
+/// FNV-1a, which unlike the std hasher is stable across releases, so saved
+/// vectors stay comparable with new ones.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
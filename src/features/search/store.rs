@@ -0,0 +1,61 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::Path;
+use super::inverted::InvertedIndex;
+use super::SearchIndex;
+
+/// Bump whenever indexing or the file layout changes; files written by
+/// other versions are rebuilt.
+pub(super) const INDEX_VERSION: u32 = 4;
+const MAGIC: &str = "edison-search-index";
+
+/// What the file holds: the word postings and when each indexed note was
+/// last modified. Note text isn't stored; it comes from the notes on load.
+pub(super) struct SavedIndex {
+    pub(super) modified: HashMap<String, DateTime<Utc>>, // note id -> `modified_at` when indexed
+    pub(super) terms: InvertedIndex,
+}
+
+/// Writes a header line (`edison-search-index <version> <md5 of body>`)
+/// followed by the index as JSON. The file is written beside the target and
+/// renamed into place, so a crash never leaves half an index.
+pub(super) fn write_index(path: &Path, index: &SearchIndex) -> Result<()> {
+    let modified: HashMap<&str, DateTime<Utc>> = index.notes.values().map(|note| (note.id.as_str(), note.modified_at)).collect();
+    let body = serde_json::to_string(&(modified, &index.terms))?;
+    let checksum = format!("{:x}", md5::compute(body.as_bytes()));
+    
+    let temporary = path.with_extension("tmp");
+    crate::utils::write_string_to_file(&temporary, &format!("{} {} {}\n{}", MAGIC, INDEX_VERSION, checksum, body))?;
+    std::fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+/// Reads an index written by `write_index`. A missing file is `None`;
+/// another version or a damaged file is an error.
+pub(super) fn read_index(path: &Path) -> Result<Option<SavedIndex>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    
+    let text = crate::utils::read_file_to_string(path)?;
+    let (header, body) = text.split_once('\n').ok_or_else(|| anyhow!("missing header"))?;
+    let mut fields = header.split(' ');
+    if fields.next() != Some(MAGIC) {
+        return Err(anyhow!("not a search index"));
+    }
+    let version: u32 = fields
+        .next()
+        .and_then(|version| version.parse().ok())
+        .ok_or_else(|| anyhow!("missing version"))?;
+    if version != INDEX_VERSION {
+        return Err(anyhow!("written by index version {}, expected {}", version, INDEX_VERSION));
+    }
+    let checksum = fields.next().ok_or_else(|| anyhow!("missing checksum"))?;
+    if format!("{:x}", md5::compute(body.as_bytes())) != checksum {
+        return Err(anyhow!("checksum mismatch"));
+    }
+    
+    let (modified, terms) = serde_json::from_str(body)?;
+    Ok(Some(SavedIndex { modified, terms }))
+}
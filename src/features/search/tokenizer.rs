@@ -0,0 +1,244 @@
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+
+const ENGLISH_STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into", "is", "it",
+    "of", "on", "or", "such", "that", "the", "their", "then", "there", "these", "they", "this", "to",
+    "was", "will", "with",
+];
+
+/// Languages with a stemmer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    Arabic,
+    Danish,
+    Dutch,
+    English,
+    Finnish,
+    French,
+    German,
+    Greek,
+    Hungarian,
+    Italian,
+    Norwegian,
+    Portuguese,
+    Romanian,
+    Russian,
+    Spanish,
+    Swedish,
+    Tamil,
+    Turkish,
+}
+
+impl Language {
+    pub const ALL: [Language; 18] = [
+        Language::Arabic, Language::Danish, Language::Dutch, Language::English, Language::Finnish,
+        Language::French, Language::German, Language::Greek, Language::Hungarian, Language::Italian,
+        Language::Norwegian, Language::Portuguese, Language::Romanian, Language::Russian,
+        Language::Spanish, Language::Swedish, Language::Tamil, Language::Turkish,
+    ];
+    
+    fn algorithm(self) -> Algorithm {
+        match self {
+            Language::Arabic => Algorithm::Arabic,
+            Language::Danish => Algorithm::Danish,
+            Language::Dutch => Algorithm::Dutch,
+            Language::English => Algorithm::English,
+            Language::Finnish => Algorithm::Finnish,
+            Language::French => Algorithm::French,
+            Language::German => Algorithm::German,
+            Language::Greek => Algorithm::Greek,
+            Language::Hungarian => Algorithm::Hungarian,
+            Language::Italian => Algorithm::Italian,
+            Language::Norwegian => Algorithm::Norwegian,
+            Language::Portuguese => Algorithm::Portuguese,
+            Language::Romanian => Algorithm::Romanian,
+            Language::Russian => Algorithm::Russian,
+            Language::Spanish => Algorithm::Spanish,
+            Language::Swedish => Algorithm::Swedish,
+            Language::Tamil => Algorithm::Tamil,
+            Language::Turkish => Algorithm::Turkish,
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// How text is split into index terms. Changing it re-indexes every note.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenizerConfig {
+    pub language: Option<Language>, // Stemmer; `None` keeps words as written
+    pub stop_words: Vec<String>,    // Words left out of the index and queries
+    pub fold_diacritics: bool,      // "café" matches "cafe"
+    pub cjk_ngram: usize,           // Characters per term in Chinese, Japanese and Korean text
+}
+
+impl Default for TokenizerConfig {
+    fn default() -> Self {
+        Self {
+            language: Some(Language::English),
+            stop_words: ENGLISH_STOP_WORDS.iter().map(|word| word.to_string()).collect(),
+            fold_diacritics: true,
+            cjk_ngram: 2,
+        }
+    }
+}
+
+impl TokenizerConfig {
+    /// No stemming, stop words or folding: words are only lowercased.
+    pub fn plain() -> Self {
+        Self {
+            language: None,
+            stop_words: Vec::new(),
+            fold_diacritics: false,
+            cjk_ngram: 2,
+        }
+    }
+}
+
+/// A term and where its word is in the text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub term: String,    // Folded and stemmed, as indexed
+    pub surface: String, // Folded but not stemmed, for prefix matching
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The tokenizer pipeline: Unicode word segmentation, case and diacritic
+/// folding, stop-word removal and stemming, with runs of CJK characters
+/// indexed as overlapping n-grams instead of words.
+#[derive(Serialize, Deserialize)]
+#[serde(from = "TokenizerConfig", into = "TokenizerConfig")]
+pub struct Tokenizer {
+    config: TokenizerConfig,
+    stemmer: Option<Stemmer>,
+    stop_words: HashSet<String>,
+}
+
+impl Tokenizer {
+    pub fn new(config: TokenizerConfig) -> Self {
+        let stemmer = config.language.map(|language| Stemmer::create(language.algorithm()));
+        let mut tokenizer = Self {
+            config,
+            stemmer,
+            stop_words: HashSet::new(),
+        };
+        // Stop words are compared after folding, like the words themselves
+        tokenizer.stop_words = tokenizer.config.stop_words.iter().map(|word| tokenizer.fold(word)).collect();
+        tokenizer
+    }
+    
+    pub fn config(&self) -> &TokenizerConfig {
+        &self.config
+    }
+    
+    pub fn tokenize(&self, text: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut cjk_run: Vec<(usize, char)> = Vec::new();
+        
+        for (start, word) in text.unicode_word_indices() {
+            if word.chars().all(is_cjk) {
+                // CJK characters are mostly segmented one by one; rejoin adjacent ones
+                if cjk_run.last().is_some_and(|(index, c)| index + c.len_utf8() != start) {
+                    self.push_ngrams(&mut cjk_run, &mut tokens);
+                }
+                cjk_run.extend(word.char_indices().map(|(index, c)| (start + index, c)));
+                continue;
+            }
+            self.push_ngrams(&mut cjk_run, &mut tokens);
+            
+            let surface = self.fold(word);
+            if self.stop_words.contains(&surface) {
+                continue;
+            }
+            let term = match &self.stemmer {
+                Some(stemmer) => self.fold(&stemmer.stem(&word.to_lowercase())),
+                None => surface.clone(),
+            };
+            tokens.push(Token { term, surface, start, end: start + word.len() });
+        }
+        self.push_ngrams(&mut cjk_run, &mut tokens);
+        tokens
+    }
+    
+    /// Whether `fragment` occurs inside a stop word, which the index leaves out.
+    pub fn in_stop_word(&self, fragment: &str) -> bool {
+        self.stop_words.iter().any(|word| word.contains(fragment))
+    }
+    
+    fn fold(&self, word: &str) -> String {
+        let lower = word.to_lowercase();
+        if self.config.fold_diacritics {
+            lower.nfd().filter(|c| !is_combining_mark(*c)).collect()
+        } else {
+            lower
+        }
+    }
+    
+    /// Turns a run of CJK characters into n-grams; runs shorter than `n` are one term.
+    fn push_ngrams(&self, run: &mut Vec<(usize, char)>, tokens: &mut Vec<Token>) {
+        if run.is_empty() {
+            return;
+        }
+        let n = self.config.cjk_ngram.max(1).min(run.len());
+        for window in run.windows(n) {
+            let (start, _) = window[0];
+            let (last, c) = window[n - 1];
+            let term: String = window.iter().map(|(_, c)| *c).collect();
+            tokens.push(Token { term: term.clone(), surface: term, start, end: last + c.len_utf8() });
+        }
+        run.clear();
+    }
+}
+
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Self::new(TokenizerConfig::default())
+    }
+}
+
+impl Clone for Tokenizer {
+    fn clone(&self) -> Self {
+        Self::new(self.config.clone())
+    }
+}
+
+impl fmt::Debug for Tokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Tokenizer").field("config", &self.config).finish()
+    }
+}
+
+impl From<TokenizerConfig> for Tokenizer {
+    fn from(config: TokenizerConfig) -> Self {
+        Self::new(config)
+    }
+}
+
+impl From<Tokenizer> for TokenizerConfig {
+    fn from(tokenizer: Tokenizer) -> Self {
+        tokenizer.config
+    }
+}
+
+/// Han, kana and Hangul, which are indexed as n-grams.
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // Hiragana and Katakana
+        | 0x3400..=0x4DBF   // CJK Extension A
+        | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF   // Hangul syllables
+        | 0xF900..=0xFAFF   // CJK Compatibility Ideographs
+        | 0xFF66..=0xFF9F   // Halfwidth Katakana
+        | 0x20000..=0x2FA1F // Extensions B onwards
+    )
+}
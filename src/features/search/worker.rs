@@ -0,0 +1,235 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
+use std::thread;
+use std::time::Duration;
+use super::{Facet, FacetCounts, FacetedResults, SearchEngine, SearchResult};
+
+/// How long typing has to pause before a query runs.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Results for one submitted query, sent as they come in.
+#[derive(Debug, Clone)]
+pub struct SearchUpdate {
+    pub generation: u64, // Which `submit` the results are for
+    pub query: String,
+    pub results: Vec<SearchResult>, // The best `max_results` so far
+    pub total: usize,               // Notes matched so far
+    pub facets: FacetCounts,        // Over every match, once complete
+    pub complete: bool,             // Every note has been checked
+}
+
+struct SearchRequest {
+    generation: u64,
+    query: String,
+    selected: Vec<Facet>,
+    max_results: usize,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Runs searches on a background thread so typing stays responsive. Each
+/// submitted query cancels the one before it, and only starts once no newer
+/// query has arrived for the debounce delay.
+pub struct SearchWorker {
+    engine: Arc<RwLock<SearchEngine>>,
+    requests: Option<Sender<SearchRequest>>,
+    updates: Receiver<SearchUpdate>,
+    generation: u64,
+    cancel: Arc<AtomicBool>,   // Of the latest query
+    writers: Arc<AtomicUsize>, // Waiting for or holding the engine's write lock
+    complete: bool,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+/// The engine's write lock, from `SearchWorker::write`. Background searches
+/// stay off the engine until it is dropped.
+pub struct EngineWriteGuard<'a> {
+    engine: Option<RwLockWriteGuard<'a, SearchEngine>>, // Only taken when dropped
+    writers: &'a AtomicUsize,
+}
+
+impl Deref for EngineWriteGuard<'_> {
+    type Target = SearchEngine;
+    
+    fn deref(&self) -> &SearchEngine {
+        self.engine.as_ref().expect("write lock is held")
+    }
+}
+
+impl DerefMut for EngineWriteGuard<'_> {
+    fn deref_mut(&mut self) -> &mut SearchEngine {
+        self.engine.as_mut().expect("write lock is held")
+    }
+}
+
+impl Drop for EngineWriteGuard<'_> {
+    fn drop(&mut self) {
+        // Counted out only once the lock is released, so a search never
+        // starts while this writer still has to get in
+        self.engine = None;
+        self.writers.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl SearchWorker {
+    pub fn spawn(engine: Arc<RwLock<SearchEngine>>, debounce: Duration) -> Self {
+        let (requests, request_receiver) = mpsc::channel();
+        let (update_sender, updates) = mpsc::channel();
+        let writers = Arc::new(AtomicUsize::new(0));
+        let worker_engine = engine.clone();
+        let worker_writers = writers.clone();
+        let thread = thread::Builder::new()
+            .name("search".to_string())
+            .spawn(move || run(worker_engine, debounce, worker_writers, request_receiver, update_sender))
+            .map_err(|e| log::error!("Failed to start search worker: {}", e))
+            .ok();
+        
+        Self {
+            engine,
+            requests: Some(requests),
+            updates,
+            generation: 0,
+            cancel: Arc::new(AtomicBool::new(false)),
+            writers,
+            complete: true,
+            thread,
+        }
+    }
+    
+    /// Replaces the running query with `query` narrowed to the `selected`
+    /// facets, returning its generation.
+    pub fn submit(&mut self, query: &str, selected: &[Facet], max_results: usize) -> u64 {
+        self.cancel();
+        self.generation += 1;
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.complete = false;
+        
+        let request = SearchRequest {
+            generation: self.generation,
+            query: query.to_string(),
+            selected: selected.to_vec(),
+            max_results,
+            cancel: self.cancel.clone(),
+        };
+        if let Some(requests) = &self.requests {
+            if requests.send(request).is_err() {
+                log::error!("Search worker has stopped");
+            }
+        }
+        self.generation
+    }
+    
+    /// Stops the running query; its results are dropped.
+    pub fn cancel(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.complete = true;
+    }
+    
+    /// Takes the engine's write lock, making the running query let go of
+    /// the engine first; the query starts over once the guard is dropped.
+    pub fn write(&self) -> EngineWriteGuard<'_> {
+        self.writers.fetch_add(1, Ordering::SeqCst);
+        let engine = self.engine.write().unwrap_or_else(|e| e.into_inner());
+        EngineWriteGuard { engine: Some(engine), writers: &self.writers }
+    }
+    
+    /// The newest results for the latest query since the last call, if any.
+    /// Results for earlier queries are discarded.
+    pub fn poll(&mut self) -> Option<SearchUpdate> {
+        let mut latest = None;
+        while let Ok(update) = self.updates.try_recv() {
+            if update.generation == self.generation && !self.cancel.load(Ordering::Relaxed) {
+                latest = Some(update);
+            }
+        }
+        if latest.as_ref().is_some_and(|update| update.complete) {
+            self.complete = true;
+        }
+        latest
+    }
+    
+    /// Whether the latest query is still waiting or running.
+    pub fn is_busy(&self) -> bool {
+        !self.complete
+    }
+}
+
+impl Drop for SearchWorker {
+    fn drop(&mut self) {
+        self.cancel();
+        // Closing the channel ends the thread
+        self.requests = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run(
+    engine: Arc<RwLock<SearchEngine>>,
+    debounce: Duration,
+    writers: Arc<AtomicUsize>,
+    requests: Receiver<SearchRequest>,
+    updates: Sender<SearchUpdate>,
+) {
+    while let Ok(mut request) = requests.recv() {
+        // Keep taking newer queries until typing pauses
+        loop {
+            match requests.recv_timeout(debounce) {
+                Ok(newer) => request = newer,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+        if request.cancel.load(Ordering::Relaxed) {
+            continue;
+        }
+        
+        let send = |results: Vec<SearchResult>, total: usize, facets: FacetCounts, complete: bool| {
+            updates
+                .send(SearchUpdate {
+                    generation: request.generation,
+                    query: request.query.clone(),
+                    results,
+                    total,
+                    facets,
+                    complete,
+                })
+                .is_ok()
+        };
+        loop {
+            let outcome = {
+                let engine = match engine.read() {
+                    Ok(engine) => engine,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                // A writer is waiting, or just done and not yet counted out
+                if writers.load(Ordering::SeqCst) > 0 {
+                    drop(engine);
+                    thread::yield_now();
+                    continue;
+                }
+                engine.search_progressive(
+                    &request.query,
+                    &request.selected,
+                    request.max_results,
+                    || request.cancel.load(Ordering::Relaxed) || writers.load(Ordering::SeqCst) > 0,
+                    |partial, total| {
+                        send(partial.to_vec(), total, FacetCounts::default(), false);
+                    },
+                )
+            };
+            match outcome {
+                Some(FacetedResults { results, total, facets }) => {
+                    if !send(results, total, facets, true) {
+                        return; // Nobody is listening any more
+                    }
+                    break;
+                }
+                None if request.cancel.load(Ordering::Relaxed) => break,
+                None => continue, // Interrupted by a writer
+            }
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use chrono::{Datelike, NaiveDate};
+use regex::Regex;
+use std::collections::HashMap;
+use super::Note;
+
+/// A `- [ ]` / `- [x]` checkbox found in a note.
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub note_id: String,
+    pub note_title: String,
+    pub line: usize,          // 1-based line number in the note
+    pub raw: String,          // The full source line, used to detect stale edits
+    pub description: String,  // Text with due date and priority markers removed
+    pub done: bool,
+    pub due: Option<NaiveDate>,
+    pub priority: Option<TaskPriority>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TaskPriority {
+    High,
+    Medium,
+    Low,
+}
+
+impl TaskPriority {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskPriority::High => "High",
+            TaskPriority::Medium => "Medium",
+            TaskPriority::Low => "Low",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskStatusFilter {
+    All,
+    Open,
+    Done,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskDueFilter {
+    Any,
+    Overdue,
+    Today,
+    ThisWeek,
+    NoDate,
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskFilter {
+    pub status: TaskStatusFilter,
+    pub due: TaskDueFilter,
+    pub tag: Option<String>,
+    pub query: String,
+}
+
+impl Default for TaskFilter {
+    fn default() -> Self {
+        Self {
+            status: TaskStatusFilter::Open,
+            due: TaskDueFilter::Any,
+            tag: None,
+            query: String::new(),
+        }
+    }
+}
+
+/// Every checkbox across the vault, kept per note so a save only re-parses
+/// the note that changed.
+pub struct TaskIndex {
+    tasks: HashMap<String, Vec<Task>>,
+    task_regex: Regex,
+    due_regex: Regex,
+    priority_regex: Regex,
+    tag_regex: Regex,
+}
+
+impl TaskIndex {
+    pub fn new() -> Self {
+        Self {
+            tasks: HashMap::new(),
+            task_regex: Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s+\[([ xX])\]\s+(.*)$").unwrap(),
+            // `📅 2026-10-20`, `due:2026-10-20` or `due:: 2026-10-20`
+            due_regex: Regex::new(r"(?:📅|\bdue::?)\s*(\d{4}-\d{2}-\d{2})").unwrap(),
+            // `⏫`/`🔺` high, `🔼` medium, `🔽`/`⏬` low, or `priority:high`
+            priority_regex: Regex::new(r"(?i)(🔺|⏫|🔼|🔽|⏬|\bpriority::?\s*(high|medium|low)\b)").unwrap(),
+            tag_regex: Regex::new(r"(?:^|\s)#([\w/-]+)").unwrap(),
+        }
+    }
+    
+    pub fn rebuild<'a>(&mut self, notes: impl IntoIterator<Item = &'a Note>) {
+        self.tasks.clear();
+        for note in notes {
+            self.update_note(note);
+        }
+    }
+    
+    pub fn update_note(&mut self, note: &Note) {
+        if note.is_deleted {
+            self.tasks.remove(&note.id);
+            return;
+        }
+        
+        let tasks = self.extract_tasks(note);
+        if tasks.is_empty() {
+            self.tasks.remove(&note.id);
+        } else {
+            self.tasks.insert(note.id.clone(), tasks);
+        }
+    }
+    
+    pub fn remove_note(&mut self, note_id: &str) {
+        self.tasks.remove(note_id);
+    }
+    
+    pub fn tasks_for_note(&self, note_id: &str) -> &[Task] {
+        self.tasks.get(note_id).map(|tasks| tasks.as_slice()).unwrap_or(&[])
+    }
+    
+    /// Tasks matching the filter, soonest due first, then by priority.
+    pub fn query(&self, filter: &TaskFilter, today: NaiveDate) -> Vec<&Task> {
+        let week_end = today + chrono::Duration::days(6 - today.weekday().num_days_from_monday() as i64);
+        let query = filter.query.trim().to_lowercase();
+        
+        let mut tasks: Vec<&Task> = self.tasks
+            .values()
+            .flatten()
+            .filter(|task| match filter.status {
+                TaskStatusFilter::All => true,
+                TaskStatusFilter::Open => !task.done,
+                TaskStatusFilter::Done => task.done,
+            })
+            .filter(|task| match filter.due {
+                TaskDueFilter::Any => true,
+                TaskDueFilter::Overdue => task.due.is_some_and(|due| due < today) && !task.done,
+                TaskDueFilter::Today => task.due == Some(today),
+                TaskDueFilter::ThisWeek => task.due.is_some_and(|due| due >= today && due <= week_end),
+                TaskDueFilter::NoDate => task.due.is_none(),
+            })
+            .filter(|task| filter.tag.as_ref().is_none_or(|tag| task.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))))
+            .filter(|task| {
+                query.is_empty()
+                    || task.description.to_lowercase().contains(&query)
+                    || task.note_title.to_lowercase().contains(&query)
+            })
+            .collect();
+        
+        tasks.sort_by(|a, b| {
+            // Undated tasks sort after dated ones
+            (a.due.is_none(), a.due, a.priority.is_none(), a.priority, &a.note_title, a.line)
+                .cmp(&(b.due.is_none(), b.due, b.priority.is_none(), b.priority, &b.note_title, b.line))
+        });
+        tasks
+    }
+    
+    /// Every tag used on a task, sorted.
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self.tasks
+            .values()
+            .flatten()
+            .flat_map(|task| task.tags.iter().cloned())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+    
+    pub fn extract_tasks(&self, note: &Note) -> Vec<Task> {
+        let mut tasks = Vec::new();
+        let mut in_code_block = false;
+        
+        for (index, line) in note.content.lines().enumerate() {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+                continue;
+            }
+            if in_code_block {
+                continue;
+            }
+            
+            let cap = match self.task_regex.captures(line) {
+                Some(cap) => cap,
+                None => continue,
+            };
+            let text = &cap[2];
+            
+            let due = self.due_regex
+                .captures(text)
+                .and_then(|due| NaiveDate::parse_from_str(&due[1], "%Y-%m-%d").ok());
+            
+            let priority = self.priority_regex.captures(text).map(|cap| {
+                match cap.get(2).map(|level| level.as_str().to_lowercase()) {
+                    Some(level) if level == "high" => TaskPriority::High,
+                    Some(level) if level == "low" => TaskPriority::Low,
+                    Some(_) => TaskPriority::Medium,
+                    None => match &cap[1] {
+                        "🔺" | "⏫" => TaskPriority::High,
+                        "🔽" | "⏬" => TaskPriority::Low,
+                        _ => TaskPriority::Medium,
+                    },
+                }
+            });
+            
+            let tags = self.tag_regex
+                .captures_iter(text)
+                .map(|tag| tag[1].to_string())
+                .collect();
+            
+            let without_due = self.due_regex.replace_all(text, "");
+            let description = self.priority_regex.replace_all(&without_due, "");
+            let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+            
+            tasks.push(Task {
+                note_id: note.id.clone(),
+                note_title: note.title.clone(),
+                line: index + 1,
+                raw: line.to_string(),
+                description,
+                done: &cap[1] != " ",
+                due,
+                priority,
+                tags,
+            });
+        }
+        
+        tasks
+    }
+}
+
+impl Default for TaskIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Flips the task's checkbox in `content`. Returns `None` if the line no
+/// longer matches what was indexed.
+pub fn toggle_task_in_content(content: &str, task: &Task) -> Option<String> {
+    let mut offset = 0;
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        if index + 1 == task.line {
+            if line.trim_end_matches(['\n', '\r']) != task.raw {
+                return None;
+            }
+            
+            let bracket = line.find('[')?;
+            let mark = if task.done { " " } else { "x" };
+            
+            let mut updated = content.to_string();
+            updated.replace_range(offset + bracket + 1..offset + bracket + 2, mark);
+            return Some(updated);
+        }
+        offset += line.len();
+    }
+    None
+}
@@ -1,8 +1,8 @@
-use rusqlite::{Connection, Result, Row};
+use rusqlite::{Connection, OptionalExtension, Result, Row};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use anyhow::anyhow;
-use crate::features::{Note, NoteLink, NoteVersion, Folder};
+use crate::features::{Note, NoteLink, LinkKind, NoteVersion, Folder, Reminder, ReminderSource, Recurrence, SavedSearch};
 
 pub struct Database {
     conn: Connection,
@@ -62,6 +62,18 @@ impl Database {
             [],
         )?;
         
+        // Links to missing notes are kept as dangling edges with an empty to_note_id
+        self.ensure_column("note_links", "target_title", "TEXT NOT NULL DEFAULT ''")?;
+        
+        // Source ranges so links can be shown in context and rewritten in place
+        self.ensure_column("note_links", "start_offset", "INTEGER NOT NULL DEFAULT 0")?;
+        self.ensure_column("note_links", "end_offset", "INTEGER NOT NULL DEFAULT 0")?;
+        self.ensure_column("note_links", "line_number", "INTEGER NOT NULL DEFAULT 0")?;
+        self.ensure_column("note_links", "link_kind", "TEXT NOT NULL DEFAULT 'wiki'")?;
+        
+        // Typed links such as `parent:: [[Note]]`; empty for plain links
+        self.ensure_column("note_links", "relation", "TEXT NOT NULL DEFAULT ''")?;
+        
         // Create note_versions table
         self.conn.execute(
             "CREATE TABLE IF NOT EXISTS note_versions (
@@ -102,6 +114,33 @@ impl Database {
             [],
         )?;
         
+        // Create reminders table
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS reminders (
+                id TEXT PRIMARY KEY,
+                note_id TEXT NOT NULL,
+                source TEXT NOT NULL,
+                message TEXT NOT NULL,
+                remind_at TEXT NOT NULL,
+                recurrence TEXT NOT NULL DEFAULT '',
+                snoozed_until TEXT,
+                is_done BOOLEAN NOT NULL DEFAULT 0,
+                FOREIGN KEY(note_id) REFERENCES notes(id)
+            )",
+            [],
+        )?;
+        
+        // Create saved searches table
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS saved_searches (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                query TEXT NOT NULL, -- JSON SavedQuery
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        
         // Create indexes for better performance
         self.conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_notes_modified_at ON notes(modified_at)",
@@ -126,6 +165,25 @@ impl Database {
         Ok(())
     }
     
+    /// Adds a column to an existing table if an older database lacks it.
+    fn ensure_column(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+        
+        for existing in columns {
+            if existing? == column {
+                return Ok(());
+            }
+        }
+        
+        self.conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+        
+        Ok(())
+    }
+    
     pub fn save_note(&self, note: &Note) -> Result<()> {
         let tags_json = serde_json::to_string(&note.tags)
             .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
@@ -188,6 +246,21 @@ impl Database {
         Ok(())
     }
     
+    pub fn delete_note_file(&self, title: &str) -> Result<()> {
+        let file_name = format!("{}.md", sanitize_filename(title));
+        let file_path = self.data_dir.join("notes").join(&file_name);
+        
+        if file_path.exists() {
+            std::fs::remove_file(&file_path)
+                .map_err(|e| rusqlite::Error::SqliteFailure(
+                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_IOERR),
+                    Some(e.to_string())
+                ))?;
+        }
+        
+        Ok(())
+    }
+    
     pub fn get_note(&self, note_id: &str) -> Result<Option<Note>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, title, content, tags, created_at, modified_at, folder_id, is_favorite, is_deleted
@@ -220,6 +293,25 @@ impl Database {
         Ok(result)
     }
     
+    /// Notes in the trash, which `get_all_notes` leaves out.
+    pub fn get_deleted_notes(&self) -> Result<Vec<Note>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, title, content, tags, created_at, modified_at, folder_id, is_favorite, is_deleted
+             FROM notes WHERE is_deleted = 1 ORDER BY modified_at DESC"
+        )?;
+        
+        let notes = stmt.query_map([], |row| {
+            self.row_to_note(row)
+        })?;
+        
+        let mut result = Vec::new();
+        for note in notes {
+            result.push(note?);
+        }
+        
+        Ok(result)
+    }
+    
     // New optimized method for listing notes with minimal data
     pub fn get_notes_list(&self) -> Result<Vec<(String, String, chrono::DateTime<chrono::Utc>)>> {
         let mut stmt = self.conn.prepare_cached(
@@ -247,9 +339,12 @@ impl Database {
     
     pub fn delete_note(&self, note_id: &str) -> Result<()> {
         self.conn.execute("DELETE FROM notes WHERE id = ?1", [note_id])?;
-        self.conn.execute("DELETE FROM note_links WHERE from_note_id = ?1 OR to_note_id = ?1", [note_id])?;
+        self.conn.execute("DELETE FROM note_links WHERE from_note_id = ?1", [note_id])?;
+        // Links into the deleted note become dangling rather than disappearing
+        self.conn.execute("UPDATE note_links SET to_note_id = '' WHERE to_note_id = ?1", [note_id])?;
         self.conn.execute("DELETE FROM note_versions WHERE note_id = ?1", [note_id])?;
         self.conn.execute("DELETE FROM ai_suggestions WHERE note_id = ?1", [note_id])?;
+        self.conn.execute("DELETE FROM reminders WHERE note_id = ?1", [note_id])?;
         Ok(())
     }
     
@@ -279,12 +374,32 @@ impl Database {
     }
     
     pub fn get_note_versions(&self, note_id: &str) -> Result<Vec<NoteVersion>> {
-        let mut stmt = self.conn.prepare(
+        self.query_note_versions("WHERE note_id = ?1 ORDER BY version_number DESC", [note_id])
+    }
+    
+    /// Every saved version of every note, for searching past text.
+    pub fn get_all_note_versions(&self) -> Result<Vec<NoteVersion>> {
+        self.query_note_versions("ORDER BY note_id, version_number DESC", [])
+    }
+    
+    /// Grows with every save, so history searches can tell when to re-index.
+    pub fn count_note_versions(&self) -> Result<usize> {
+        let count: i64 = self.conn.query_row("SELECT COUNT(*) FROM note_versions", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+    
+    pub fn get_note_version(&self, version_id: &str) -> Result<Option<NoteVersion>> {
+        Ok(self.query_note_versions("WHERE id = ?1", [version_id])?.pop())
+    }
+    
+    fn query_note_versions<P: rusqlite::Params>(&self, filter: &str, params: P) -> Result<Vec<NoteVersion>> {
+        let mut stmt = self.conn.prepare(&format!(
             "SELECT id, note_id, content, timestamp, version_number
-             FROM note_versions WHERE note_id = ?1 ORDER BY version_number DESC"
-        )?;
+             FROM note_versions {}",
+            filter
+        ))?;
         
-        let versions = stmt.query_map([note_id], |row| {
+        let versions = stmt.query_map(params, |row| {
             Ok(NoteVersion {
                 id: row.get(0)?,
                 note_id: row.get(1)?,
@@ -304,7 +419,7 @@ impl Database {
         Ok(result)
     }
     
-    pub fn update_note_links(&self, note_id: &str, target_note_ids: &[String]) -> Result<()> {
+    pub fn update_note_links(&self, note_id: &str, links: &[NoteLink]) -> Result<()> {
         // Delete existing links from this note
         self.conn.execute(
             "DELETE FROM note_links WHERE from_note_id = ?1",
@@ -312,17 +427,24 @@ impl Database {
         )?;
         
         // Insert new links
-        for (position, target_id) in target_note_ids.iter().enumerate() {
+        for link in links {
             let link_id = uuid::Uuid::new_v4().to_string();
             self.conn.execute(
-                "INSERT INTO note_links (id, from_note_id, to_note_id, link_text, position)
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                "INSERT INTO note_links (id, from_note_id, to_note_id, target_title, link_text, position,
+                                         start_offset, end_offset, line_number, link_kind, relation)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
                 rusqlite::params![
                     link_id,
                     note_id,
-                    target_id,
-                    "", // We could extract the actual link text here
-                    position as i32
+                    link.to_note_id.as_deref().unwrap_or(""),
+                    link.target_title,
+                    link.link_text,
+                    link.position as i64,
+                    link.start as i64,
+                    link.end as i64,
+                    link.line as i64,
+                    link.kind.as_str(),
+                    link.relation.as_deref().unwrap_or("")
                 ],
             )?;
         }
@@ -332,7 +454,7 @@ impl Database {
     
     pub fn get_note_links(&self, note_id: &str) -> Result<Vec<String>> {
         let mut stmt = self.conn.prepare(
-            "SELECT to_note_id FROM note_links WHERE from_note_id = ?1 ORDER BY position"
+            "SELECT to_note_id FROM note_links WHERE from_note_id = ?1 AND to_note_id != '' ORDER BY position"
         )?;
         
         let links = stmt.query_map([note_id], |row| {
@@ -347,6 +469,62 @@ impl Database {
         Ok(result)
     }
     
+    /// Links whose target note doesn't exist, across the whole vault.
+    pub fn get_dangling_links(&self) -> Result<Vec<NoteLink>> {
+        self.query_note_links(
+            "WHERE to_note_id = '' ORDER BY target_title, from_note_id, position",
+            [],
+        )
+    }
+    
+    /// Every link between two existing notes, across the whole vault.
+    pub fn get_resolved_links(&self) -> Result<Vec<NoteLink>> {
+        self.query_note_links("WHERE to_note_id != '' ORDER BY from_note_id, position", [])
+    }
+    
+    /// Every stored link in the note, in document order.
+    pub fn get_outgoing_links(&self, note_id: &str) -> Result<Vec<NoteLink>> {
+        self.query_note_links("WHERE from_note_id = ?1 ORDER BY position", [note_id])
+    }
+    
+    /// Every stored link pointing at the note, grouped by source.
+    pub fn get_incoming_links(&self, note_id: &str) -> Result<Vec<NoteLink>> {
+        self.query_note_links("WHERE to_note_id = ?1 ORDER BY from_note_id, position", [note_id])
+    }
+    
+    fn query_note_links<P: rusqlite::Params>(&self, filter: &str, params: P) -> Result<Vec<NoteLink>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT from_note_id, to_note_id, target_title, link_text, position,
+                    start_offset, end_offset, line_number, link_kind, relation
+             FROM note_links {}",
+            filter
+        ))?;
+        
+        let links = stmt.query_map(params, |row| {
+            let to_note_id: String = row.get(1)?;
+            let relation: String = row.get(9)?;
+            Ok(NoteLink {
+                from_note_id: row.get(0)?,
+                to_note_id: if to_note_id.is_empty() { None } else { Some(to_note_id) },
+                target_title: row.get(2)?,
+                link_text: row.get(3)?,
+                position: row.get::<_, i64>(4)? as usize,
+                start: row.get::<_, i64>(5)? as usize,
+                end: row.get::<_, i64>(6)? as usize,
+                line: row.get::<_, i64>(7)? as usize,
+                kind: LinkKind::parse(&row.get::<_, String>(8)?),
+                relation: if relation.is_empty() { None } else { Some(relation) },
+            })
+        })?;
+        
+        let mut result = Vec::new();
+        for link in links {
+            result.push(link?);
+        }
+        
+        Ok(result)
+    }
+    
     pub fn get_backlinks(&self, note_id: &str) -> Result<Vec<String>> {
         let mut stmt = self.conn.prepare(
             "SELECT from_note_id FROM note_links WHERE to_note_id = ?1"
@@ -404,6 +582,113 @@ impl Database {
         Ok(result)
     }
     
+    pub fn save_reminder(&self, reminder: &Reminder) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO reminders (id, note_id, source, message, remind_at, recurrence, snoozed_until, is_done)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            rusqlite::params![
+                reminder.id,
+                reminder.note_id,
+                reminder.source.as_str(),
+                reminder.message,
+                reminder.remind_at.to_rfc3339(),
+                reminder.recurrence.as_str(),
+                reminder.snoozed_until.map(|time| time.to_rfc3339()),
+                reminder.is_done
+            ],
+        )?;
+        
+        Ok(())
+    }
+    
+    pub fn delete_reminder(&self, reminder_id: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM reminders WHERE id = ?1", [reminder_id])?;
+        Ok(())
+    }
+    
+    pub fn get_all_reminders(&self) -> Result<Vec<Reminder>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, note_id, source, message, remind_at, recurrence, snoozed_until, is_done FROM reminders"
+        )?;
+        
+        let parse_time = |index: usize, value: String| {
+            chrono::DateTime::parse_from_rfc3339(&value)
+                .map(|time| time.with_timezone(&chrono::Utc))
+                .map_err(|_| rusqlite::Error::InvalidColumnType(index, "timestamp".to_string(), rusqlite::types::Type::Text))
+        };
+        
+        let reminders = stmt.query_map([], |row| {
+            let snoozed_until: Option<String> = row.get(6)?;
+            Ok(Reminder {
+                id: row.get(0)?,
+                note_id: row.get(1)?,
+                source: ReminderSource::parse(&row.get::<_, String>(2)?),
+                message: row.get(3)?,
+                remind_at: parse_time(4, row.get(4)?)?,
+                // An expression the cron parser no longer accepts degrades to a one-off
+                recurrence: Recurrence::parse(&row.get::<_, String>(5)?).unwrap_or(Recurrence::Once),
+                snoozed_until: snoozed_until.map(|value| parse_time(6, value)).transpose()?,
+                is_done: row.get(7)?,
+            })
+        })?;
+        
+        let mut result = Vec::new();
+        for reminder in reminders {
+            result.push(reminder?);
+        }
+        
+        Ok(result)
+    }
+    
+    pub fn save_saved_search(&self, search: &SavedSearch) -> Result<()> {
+        let query_json = serde_json::to_string(&search.query)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        
+        self.conn.execute(
+            "INSERT OR REPLACE INTO saved_searches (id, name, query, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![
+                search.id,
+                search.name,
+                query_json,
+                search.created_at.to_rfc3339()
+            ],
+        )?;
+        
+        Ok(())
+    }
+    
+    pub fn delete_saved_search(&self, search_id: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM saved_searches WHERE id = ?1", [search_id])?;
+        Ok(())
+    }
+    
+    pub fn get_saved_searches(&self) -> Result<Vec<SavedSearch>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, query, created_at FROM saved_searches ORDER BY name"
+        )?;
+        
+        let searches = stmt.query_map([], |row| {
+            let query_json: String = row.get(2)?;
+            Ok(SavedSearch {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                query: serde_json::from_str(&query_json)
+                    .map_err(|e| rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e)))?,
+                created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+                    .map_err(|_| rusqlite::Error::InvalidColumnType(3, "created_at".to_string(), rusqlite::types::Type::Text))?
+                    .with_timezone(&chrono::Utc),
+            })
+        })?;
+        
+        let mut result = Vec::new();
+        for search in searches {
+            result.push(search?);
+        }
+        
+        Ok(result)
+    }
+    
     fn row_to_note(&self, row: &Row) -> Result<Note> {
         let tags_json: String = row.get(3)?;
         let tags: Vec<String> = serde_json::from_str(&tags_json)
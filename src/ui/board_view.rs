@@ -0,0 +1,245 @@
+use eframe::egui;
+use chrono::{DateTime, Utc};
+use crate::features::{Board, NoteManager};
+
+const COLUMN_WIDTH: f32 = 240.0;
+
+/// Kanban board for a markdown note; dragging a card rewrites the note.
+pub struct BoardView {
+    board_note_id: Option<String>,
+    board: Option<(String, DateTime<Utc>, Board)>, // Parsed board with the note id and edit time it came from
+    dragging: Option<(usize, usize)>, // Column and card index being dragged
+    new_cards: Vec<String>,           // Pending "add card" text per column
+    new_column: String,
+    open_request: Option<String>,
+    status: Option<String>,
+}
+
+impl BoardView {
+    pub fn new() -> Self {
+        Self {
+            board_note_id: None,
+            board: None,
+            dragging: None,
+            new_cards: Vec::new(),
+            new_column: String::new(),
+            open_request: None,
+            status: None,
+        }
+    }
+    
+    /// Note whose card was clicked since the last call.
+    pub fn take_open_request(&mut self) -> Option<String> {
+        self.open_request.take()
+    }
+    
+    pub fn show(&mut self, ui: &mut egui::Ui, note_manager: &mut NoteManager, current_note_id: Option<&String>) {
+        // Follow the current note when it is a board, otherwise keep the last one shown
+        let board_notes: Vec<(String, String)> = note_manager
+            .get_board_notes()
+            .into_iter()
+            .map(|note| (note.id.clone(), note.title.clone()))
+            .collect();
+        if let Some(current) = current_note_id.filter(|id| board_notes.iter().any(|(board_id, _)| board_id == *id)) {
+            self.board_note_id = Some(current.clone());
+        }
+        if self.board_note_id.as_ref().is_none_or(|id| note_manager.get_note(id).is_none()) {
+            self.board_note_id = board_notes.first().map(|(id, _)| id.clone());
+        }
+        
+        ui.horizontal(|ui| {
+            ui.heading("Board");
+            ui.separator();
+            
+            let selected_title = self.board_note_id
+                .as_ref()
+                .and_then(|id| note_manager.get_note(id))
+                .map(|note| note.title.clone())
+                .unwrap_or_else(|| "Choose a note".to_string());
+            egui::ComboBox::from_id_source("board_note")
+                .selected_text(selected_title)
+                .show_ui(ui, |ui| {
+                    for (id, title) in &board_notes {
+                        ui.selectable_value(&mut self.board_note_id, Some(id.clone()), title);
+                    }
+                    if let Some(current) = current_note_id.filter(|id| !board_notes.iter().any(|(board_id, _)| board_id == *id)) {
+                        if let Some(note) = note_manager.get_note(current) {
+                            ui.separator();
+                            ui.selectable_value(&mut self.board_note_id, Some(current.clone()), format!("Current note: {}", note.title));
+                        }
+                    }
+                });
+            
+            if let Some(status) = &self.status {
+                ui.weak(status);
+            }
+        });
+        
+        ui.separator();
+        
+        let note_id = match self.board_note_id.clone() {
+            Some(note_id) => note_id,
+            None => {
+                ui.centered_and_justified(|ui| {
+                    ui.label("Add `board: true` to a note's properties to use it as a board.");
+                });
+                return;
+            }
+        };
+        // Only re-parse the note when it has been edited since the last frame
+        let modified_at = match note_manager.get_note(&note_id) {
+            Some(note) => note.modified_at,
+            None => return,
+        };
+        let mut board = match self.board.take() {
+            Some((id, parsed_at, board)) if id == note_id && parsed_at == modified_at => board,
+            _ => match note_manager.get_board(&note_id) {
+                Some(board) => board,
+                None => return,
+            },
+        };
+        
+        self.new_cards.resize(board.columns.len(), String::new());
+        let mut changed = false;
+        let mut drop_target: Option<(usize, usize)> = None;
+        let mut add_card_to: Option<usize> = None;
+        let mut add_column = false;
+        let pointer = ui.input(|i| i.pointer.interact_pos());
+        
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            ui.horizontal_top(|ui| {
+                for (column_index, column) in board.columns.iter().enumerate() {
+                    let cards = column.cards();
+                    let column_response = egui::Frame::group(ui.style()).show(ui, |ui| {
+                        ui.set_width(COLUMN_WIDTH);
+                        ui.vertical(|ui| {
+                            ui.horizontal(|ui| {
+                                ui.strong(&column.title);
+                                ui.weak(cards.len().to_string());
+                            });
+                            ui.separator();
+                            
+                            let mut card_rects = Vec::new();
+                            for (card_index, card) in cards.iter().enumerate() {
+                                let is_dragged = self.dragging == Some((column_index, card_index));
+                                let frame = egui::Frame::none()
+                                    .fill(if is_dragged { ui.visuals().selection.bg_fill } else { ui.visuals().faint_bg_color })
+                                    .rounding(4.0)
+                                    .inner_margin(6.0);
+                                let card_response = frame.show(ui, |ui| {
+                                    ui.set_width(ui.available_width());
+                                    ui.horizontal_wrapped(|ui| {
+                                        if let Some(done) = card.done {
+                                            ui.label(if done { "☑" } else { "☐" });
+                                        }
+                                        match &card.link {
+                                            Some(target) => {
+                                                if ui.link(target).clicked() {
+                                                    self.open_request = note_manager.resolve_link(target);
+                                                }
+                                            }
+                                            None if card.done == Some(true) => {
+                                                ui.label(egui::RichText::new(&card.text).strikethrough().weak());
+                                            }
+                                            None => {
+                                                ui.label(&card.text);
+                                            }
+                                        }
+                                    });
+                                    for detail in card.details() {
+                                        ui.weak(detail);
+                                    }
+                                }).response;
+                                
+                                let drag = ui.interact(card_response.rect, ui.id().with(("board_card", column_index, card_index)), egui::Sense::drag());
+                                if drag.drag_started() {
+                                    self.dragging = Some((column_index, card_index));
+                                }
+                                if drag.hovered() {
+                                    ui.ctx().set_cursor_icon(egui::CursorIcon::Grab);
+                                }
+                                card_rects.push(card_response.rect);
+                            }
+                            
+                            ui.add_space(4.0);
+                            ui.horizontal(|ui| {
+                                let response = ui.add(
+                                    egui::TextEdit::singleline(&mut self.new_cards[column_index])
+                                        .hint_text("Add a card...")
+                                        .desired_width(COLUMN_WIDTH - 40.0),
+                                );
+                                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                                if (ui.small_button("➕").clicked() || submitted) && !self.new_cards[column_index].trim().is_empty() {
+                                    add_card_to = Some(column_index);
+                                }
+                            });
+                            
+                            card_rects
+                        }).inner
+                    });
+                    
+                    // Cards land before the first card whose middle is below the pointer
+                    if let Some(pos) = pointer.filter(|pos| column_response.response.rect.contains(*pos)) {
+                        let index = column_response.inner.iter().position(|rect| pos.y < rect.center().y).unwrap_or(column_response.inner.len());
+                        drop_target = Some((column_index, index));
+                        if self.dragging.is_some() {
+                            ui.painter().rect_stroke(column_response.response.rect, 4.0, ui.visuals().selection.stroke);
+                        }
+                    }
+                }
+                
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    ui.set_width(COLUMN_WIDTH);
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut self.new_column).hint_text("New column...").desired_width(COLUMN_WIDTH - 40.0));
+                        if ui.small_button("➕").clicked() {
+                            add_column = true;
+                        }
+                    });
+                });
+            });
+        });
+        
+        if let Some(column) = add_card_to {
+            if board.add_card(column, &self.new_cards[column]) {
+                self.new_cards[column].clear();
+                changed = true;
+            }
+        }
+        if add_column && board.add_column(&self.new_column) {
+            self.new_column.clear();
+            changed = true;
+        }
+        
+        if ui.input(|i| i.pointer.any_released()) {
+            if let (Some((from_column, card)), Some((to_column, to_index))) = (self.dragging.take(), drop_target) {
+                // Dropping below itself in the same column shifts the target up by one
+                let to_index = if from_column == to_column && to_index > card { to_index - 1 } else { to_index };
+                if (from_column, card) != (to_column, to_index) {
+                    changed |= board.move_card(from_column, card, to_column, to_index);
+                }
+            }
+        } else if self.dragging.is_some() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+        }
+        
+        if changed {
+            self.status = match note_manager.save_board(&note_id, &board) {
+                Ok(()) => None,
+                Err(e) => {
+                    log::error!("Failed to save board: {}", e);
+                    Some("Failed to save board".to_string())
+                }
+            };
+        }
+        
+        let modified_at = note_manager.get_note(&note_id).map_or(modified_at, |note| note.modified_at);
+        self.board = Some((note_id, modified_at, board));
+    }
+}
+
+impl Default for BoardView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
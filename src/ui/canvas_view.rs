@@ -0,0 +1,601 @@
+use eframe::egui;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use crate::features::{Canvas, CanvasEdge, CanvasNode, CanvasNodeKind, NoteManager, CANVAS_EXTENSION, list_canvases, note_file_path};
+
+/// How long card text has to sit still before it is written to disk.
+const TEXT_SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Infinite whiteboard of notes, text cards, images and links, saved as a
+/// JSON Canvas file in the vault.
+pub struct CanvasView {
+    canvas_files: Vec<PathBuf>,
+    canvas_path: Option<PathBuf>,
+    canvas: Canvas,
+    zoom_level: f32,
+    pan_offset: egui::Vec2,
+    selected_node: Option<String>,
+    dragging_node: Option<(String, egui::Vec2)>, // Node and pointer offset from its corner
+    connecting_from: Option<String>,             // Shift-drag from a node draws an arrow
+    textures: HashMap<String, Option<egui::TextureHandle>>,
+    new_canvas_name: String,
+    new_link: String,
+    new_image: String,
+    open_request: Option<String>,
+    status: Option<String>,
+    text_edited_at: Option<Instant>, // Card text typed since the last save
+    loaded: bool,
+}
+
+impl CanvasView {
+    pub fn new() -> Self {
+        Self {
+            canvas_files: Vec::new(),
+            canvas_path: None,
+            canvas: Canvas::new(),
+            zoom_level: 1.0,
+            pan_offset: egui::Vec2::ZERO,
+            selected_node: None,
+            dragging_node: None,
+            connecting_from: None,
+            textures: HashMap::new(),
+            new_canvas_name: String::new(),
+            new_link: String::new(),
+            new_image: String::new(),
+            open_request: None,
+            status: None,
+            text_edited_at: None,
+            loaded: false,
+        }
+    }
+    
+    /// Note whose card was clicked since the last call.
+    pub fn take_open_request(&mut self) -> Option<String> {
+        self.open_request.take()
+    }
+    
+    pub fn show(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
+        if !self.loaded {
+            self.refresh_canvas_list();
+            if let Some(path) = self.canvas_files.first().cloned() {
+                self.open_canvas(path);
+            }
+            self.loaded = true;
+        }
+        
+        ui.vertical(|ui| {
+            self.show_toolbar(ui, note_manager, current_note_id);
+            ui.separator();
+            
+            if self.canvas_path.is_none() {
+                ui.centered_and_justified(|ui| {
+                    ui.label("Create a canvas to start placing notes and cards.");
+                });
+                return;
+            }
+            
+            if self.selected_text_node().is_some() {
+                self.show_text_editor(ui);
+            }
+            match self.text_edited_at {
+                Some(at) if at.elapsed() >= TEXT_SAVE_DELAY => self.save(),
+                Some(at) => ui.ctx().request_repaint_after(TEXT_SAVE_DELAY - at.elapsed()),
+                None => {}
+            }
+            
+            let (response, painter) = ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+            let changed = self.handle_interaction(ui, &response, note_manager);
+            self.draw_canvas(ui.ctx(), &painter, response.rect, note_manager);
+            
+            if changed {
+                self.save();
+            }
+        });
+    }
+    
+    fn show_toolbar(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
+        ui.horizontal(|ui| {
+            ui.heading("Canvas");
+            ui.separator();
+            
+            let selected = self.canvas_path
+                .as_ref()
+                .and_then(|path| path.file_stem())
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "No canvas".to_string());
+            let mut open = None;
+            egui::ComboBox::from_id_source("canvas_file")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for path in &self.canvas_files {
+                        let name = path.file_stem().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+                        if ui.selectable_label(self.canvas_path.as_ref() == Some(path), name).clicked() {
+                            open = Some(path.clone());
+                        }
+                    }
+                });
+            if let Some(path) = open {
+                self.open_canvas(path);
+            }
+            
+            ui.add(egui::TextEdit::singleline(&mut self.new_canvas_name).hint_text("New canvas...").desired_width(120.0));
+            if ui.button("➕ Create").clicked() {
+                self.create_canvas();
+            }
+            
+            if let Some(status) = &self.status {
+                ui.weak(status);
+            }
+        });
+        
+        if self.canvas_path.is_none() {
+            return;
+        }
+        
+        ui.horizontal(|ui| {
+            // New cards land in the middle of the current view
+            let (x, y) = (-self.pan_offset.x as i64 - 125, -self.pan_offset.y as i64 - 30);
+            let mut changed = false;
+            
+            if ui.button("📝 Text").clicked() {
+                let id = self.canvas.add_node(CanvasNodeKind::Text { text: String::new() }, x, y);
+                self.selected_node = Some(id);
+                changed = true;
+            }
+            
+            let current_note = current_note_id.and_then(|id| note_manager.get_note(id));
+            if ui.add_enabled(current_note.is_some(), egui::Button::new("📄 Current Note")).clicked() {
+                if let Some(note) = current_note {
+                    let file = note_file_path(&note.title);
+                    self.selected_node = Some(self.canvas.add_node(CanvasNodeKind::File { file, subpath: None }, x, y));
+                    changed = true;
+                }
+            }
+            
+            if ui.button("🗂 Group").clicked() {
+                self.canvas.add_node(CanvasNodeKind::Group { label: Some("Group".to_string()) }, x, y);
+                changed = true;
+            }
+            
+            ui.separator();
+            
+            ui.add(egui::TextEdit::singleline(&mut self.new_link).hint_text("https://...").desired_width(140.0));
+            if ui.button("🔗 Link").clicked() && !self.new_link.trim().is_empty() {
+                let url = std::mem::take(&mut self.new_link).trim().to_string();
+                self.canvas.add_node(CanvasNodeKind::Link { url }, x, y);
+                changed = true;
+            }
+            
+            ui.add(egui::TextEdit::singleline(&mut self.new_image).hint_text("image.png in vault").desired_width(140.0));
+            if ui.button("🖼 Image").clicked() && !self.new_image.trim().is_empty() {
+                let file = std::mem::take(&mut self.new_image).trim().to_string();
+                self.canvas.add_node(CanvasNodeKind::File { file, subpath: None }, x, y);
+                changed = true;
+            }
+            
+            ui.separator();
+            
+            if ui.add_enabled(self.selected_node.is_some(), egui::Button::new("🗑 Delete")).clicked() {
+                if let Some(id) = self.selected_node.take() {
+                    changed |= self.canvas.remove_node(&id);
+                }
+            }
+            
+            ui.label("Zoom:");
+            ui.add(egui::Slider::new(&mut self.zoom_level, 0.1..=3.0).step_by(0.1));
+            if ui.button("🎯 Center").clicked() {
+                self.pan_offset = egui::Vec2::ZERO;
+                self.zoom_level = 1.0;
+            }
+            
+            if changed {
+                self.save();
+            }
+        });
+    }
+    
+    fn selected_text_node(&self) -> Option<&CanvasNode> {
+        self.selected_node
+            .as_ref()
+            .and_then(|id| self.canvas.node(id))
+            .filter(|node| matches!(node.kind, CanvasNodeKind::Text { .. } | CanvasNodeKind::Group { .. }))
+    }
+    
+    fn show_text_editor(&mut self, ui: &mut egui::Ui) {
+        let id = match &self.selected_node {
+            Some(id) => id.clone(),
+            None => return,
+        };
+        // Typing only marks the canvas as edited; it is written once the
+        // field loses focus or the text sits still for a moment
+        let mut changed = false;
+        let mut lost_focus = false;
+        if let Some(node) = self.canvas.node_mut(&id) {
+            match &mut node.kind {
+                CanvasNodeKind::Text { text } => {
+                    let response = ui.add(egui::TextEdit::multiline(text).hint_text("Card text (markdown)").desired_rows(3).desired_width(f32::INFINITY));
+                    changed = response.changed();
+                    lost_focus = response.lost_focus();
+                }
+                CanvasNodeKind::Group { label } => {
+                    let mut value = label.clone().unwrap_or_default();
+                    let response = ui.add(egui::TextEdit::singleline(&mut value).hint_text("Group label"));
+                    if response.changed() {
+                        *label = Some(value).filter(|value| !value.is_empty());
+                        changed = true;
+                    }
+                    lost_focus = response.lost_focus();
+                }
+                _ => {}
+            }
+        }
+        if changed {
+            self.text_edited_at = Some(Instant::now());
+        }
+        if lost_focus && self.text_edited_at.is_some() {
+            self.save();
+        }
+    }
+    
+    /// Returns whether the canvas changed and should be saved.
+    fn handle_interaction(&mut self, ui: &egui::Ui, response: &egui::Response, note_manager: &NoteManager) -> bool {
+        let center = response.rect.center();
+        let mut changed = false;
+        
+        if response.drag_started() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                let world = self.to_world(pos, center);
+                let shift_held = ui.input(|i| i.modifiers.shift);
+                if let Some(node) = self.canvas.node_at(world.x, world.y) {
+                    self.selected_node = Some(node.id.clone());
+                    if shift_held {
+                        self.connecting_from = Some(node.id.clone());
+                    } else {
+                        let offset = world - egui::vec2(node.x as f32, node.y as f32);
+                        self.dragging_node = Some((node.id.clone(), offset));
+                    }
+                }
+            }
+        }
+        
+        if response.dragged() {
+            match (&self.dragging_node, response.interact_pointer_pos()) {
+                (Some((id, offset)), Some(pos)) => {
+                    let world = self.to_world(pos, center) - *offset;
+                    if let Some(node) = self.canvas.node_mut(id) {
+                        node.x = world.x.round() as i64;
+                        node.y = world.y.round() as i64;
+                    }
+                }
+                _ if self.connecting_from.is_none() => self.pan_offset += response.drag_delta() / self.zoom_level,
+                _ => {}
+            }
+        }
+        
+        if response.drag_released() {
+            if let Some(from) = self.connecting_from.take() {
+                let target = ui.input(|i| i.pointer.interact_pos())
+                    .map(|pos| self.to_world(pos, center))
+                    .and_then(|world| self.canvas.node_at(world.x, world.y))
+                    .map(|node| node.id.clone());
+                if let Some(to) = target {
+                    changed |= self.canvas.connect(&from, &to).is_some();
+                }
+            }
+            changed |= self.dragging_node.take().is_some();
+        }
+        
+        if response.clicked() {
+            let clicked = response.interact_pointer_pos()
+                .map(|pos| self.to_world(pos, center))
+                .and_then(|world| self.canvas.node_at(world.x, world.y));
+            self.selected_node = clicked.map(|node| node.id.clone());
+            
+            match clicked.map(|node| &node.kind) {
+                Some(CanvasNodeKind::File { .. }) => {
+                    if let Some(note_id) = clicked.and_then(|node| node.resolve_note(|title| note_manager.resolve_link(title))) {
+                        self.open_request = Some(note_id);
+                    }
+                }
+                Some(CanvasNodeKind::Link { url }) => {
+                    ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+                }
+                _ => {}
+            }
+        }
+        
+        // Double-clicking empty space drops a new text card there
+        if response.double_clicked() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                let world = self.to_world(pos, center);
+                if self.canvas.node_at(world.x, world.y).is_none() {
+                    let id = self.canvas.add_node(CanvasNodeKind::Text { text: String::new() }, world.x as i64, world.y as i64);
+                    self.selected_node = Some(id);
+                    changed = true;
+                }
+            }
+        }
+        
+        if response.hovered() {
+            let scroll_delta = ui.input(|i| i.scroll_delta.y);
+            if scroll_delta != 0.0 {
+                self.zoom_level = (self.zoom_level * (1.0 + scroll_delta * 0.001)).clamp(0.1, 3.0);
+            }
+        }
+        
+        if !ui.ctx().wants_keyboard_input() && ui.input(|i| i.key_pressed(egui::Key::Delete)) {
+            if let Some(id) = self.selected_node.take() {
+                changed |= self.canvas.remove_node(&id);
+            }
+        }
+        
+        changed
+    }
+    
+    fn draw_canvas(&mut self, ctx: &egui::Context, painter: &egui::Painter, rect: egui::Rect, note_manager: &NoteManager) {
+        let center = rect.center();
+        painter.rect_filled(rect, 0.0, ctx.style().visuals.extreme_bg_color);
+        
+        let images: Vec<String> = self.canvas.nodes
+            .iter()
+            .filter(|node| node.is_image())
+            .filter_map(|node| match &node.kind {
+                CanvasNodeKind::File { file, .. } => Some(file.clone()),
+                _ => None,
+            })
+            .collect();
+        for file in images {
+            self.load_texture(ctx, &file);
+        }
+        
+        // Groups first so cards sit on top of them
+        let mut order: Vec<&CanvasNode> = self.canvas.nodes.iter().collect();
+        order.sort_by_key(|node| !matches!(node.kind, CanvasNodeKind::Group { .. }));
+        
+        for node in order {
+            let node_rect = egui::Rect::from_min_size(
+                self.to_screen(egui::vec2(node.x as f32, node.y as f32), center),
+                egui::vec2(node.width as f32, node.height as f32) * self.zoom_level,
+            );
+            if !rect.intersects(node_rect) {
+                continue;
+            }
+            self.draw_node(ctx, painter, node, node_rect, note_manager);
+        }
+        
+        for edge in &self.canvas.edges {
+            self.draw_edge(painter, edge, center);
+        }
+        
+        // Arrow being drawn from a node to the pointer
+        if let (Some(from), Some(pointer)) = (
+            self.connecting_from.as_ref().and_then(|id| self.canvas.node(id)),
+            ctx.input(|i| i.pointer.hover_pos()),
+        ) {
+            let (x, y) = from.center();
+            let start = self.to_screen(egui::vec2(x, y), center);
+            painter.line_segment([start, pointer], egui::Stroke::new(2.0, ctx.style().visuals.selection.stroke.color));
+        }
+    }
+    
+    fn draw_node(&self, ctx: &egui::Context, painter: &egui::Painter, node: &CanvasNode, rect: egui::Rect, note_manager: &NoteManager) {
+        let style = ctx.style();
+        let visuals = &style.visuals;
+        let accent = node.color.as_deref().and_then(canvas_color).unwrap_or(visuals.widgets.noninteractive.bg_stroke.color);
+        let selected = self.selected_node.as_deref() == Some(node.id.as_str());
+        let stroke = if selected {
+            egui::Stroke::new(2.5, visuals.selection.stroke.color)
+        } else {
+            egui::Stroke::new(1.5, accent)
+        };
+        let painter = painter.with_clip_rect(rect.expand(3.0).intersect(painter.clip_rect()));
+        let font_size = 14.0 * self.zoom_level;
+        let text_color = visuals.text_color();
+        let inner = rect.shrink(8.0 * self.zoom_level);
+        
+        let (title, body) = match &node.kind {
+            CanvasNodeKind::Group { label } => {
+                painter.rect(rect, 6.0, accent.linear_multiply(0.08), stroke);
+                if let Some(label) = label {
+                    painter.text(rect.left_top() - egui::vec2(0.0, 4.0), egui::Align2::LEFT_BOTTOM, label, egui::FontId::proportional(font_size), text_color);
+                }
+                return;
+            }
+            CanvasNodeKind::File { file, .. } if node.is_image() => {
+                painter.rect_stroke(rect, 4.0, stroke);
+                match self.textures.get(file).and_then(|texture| texture.as_ref()) {
+                    Some(texture) => {
+                        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                        painter.image(texture.id(), rect, uv, egui::Color32::WHITE);
+                    }
+                    None => {
+                        painter.text(rect.center(), egui::Align2::CENTER_CENTER, format!("🖼 {} not found", file), egui::FontId::proportional(font_size), text_color);
+                    }
+                }
+                return;
+            }
+            CanvasNodeKind::File { file, .. } => {
+                let note = node.resolve_note(|title| note_manager.resolve_link(title))
+                    .and_then(|id| note_manager.get_note(&id));
+                match note {
+                    Some(note) => (Some(format!("📄 {}", note.title)), note.content.clone()),
+                    None => (Some(format!("📄 {}", file)), "Missing file".to_string()),
+                }
+            }
+            CanvasNodeKind::Link { url } => (Some("🔗 Link".to_string()), url.clone()),
+            CanvasNodeKind::Text { text } => (None, text.clone()),
+        };
+        
+        painter.rect(rect, 6.0, visuals.window_fill(), stroke);
+        
+        let mut top = inner.top();
+        if let Some(title) = title {
+            let galley = painter.layout(title, egui::FontId::proportional(font_size * 1.1), text_color, inner.width());
+            top += galley.size().y + 4.0 * self.zoom_level;
+            painter.galley(inner.left_top(), galley);
+        }
+        let galley = painter.layout(body, egui::FontId::proportional(font_size), text_color, inner.width());
+        painter.galley(egui::pos2(inner.left(), top), galley);
+    }
+    
+    fn draw_edge(&self, painter: &egui::Painter, edge: &CanvasEdge, center: egui::Pos2) {
+        let (from, to) = match (self.canvas.node(&edge.from_node), self.canvas.node(&edge.to_node)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return,
+        };
+        
+        let start = self.to_screen(side_anchor(from, to), center);
+        let end = self.to_screen(side_anchor(to, from), center);
+        let color = edge.color.as_deref().and_then(canvas_color).unwrap_or(egui::Color32::GRAY);
+        let stroke = egui::Stroke::new(2.0, color);
+        painter.line_segment([start, end], stroke);
+        
+        if edge.to_arrow() {
+            draw_arrow_head(painter, start, end, stroke, self.zoom_level);
+        }
+        if edge.from_arrow() {
+            draw_arrow_head(painter, end, start, stroke, self.zoom_level);
+        }
+        if let Some(label) = &edge.label {
+            painter.text(start + (end - start) * 0.5, egui::Align2::CENTER_CENTER, label, egui::FontId::proportional(12.0), color);
+        }
+    }
+    
+    /// Loads an image from the vault once; missing files are remembered as `None`.
+    fn load_texture(&mut self, ctx: &egui::Context, file: &str) {
+        self.textures
+            .entry(file.to_string())
+            .or_insert_with(|| {
+                let path = crate::utils::get_notes_directory().ok()?.join(file);
+                let image = image::open(&path).ok()?.to_rgba8();
+                let size = [image.width() as usize, image.height() as usize];
+                let color_image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+                Some(ctx.load_texture(file, color_image, Default::default()))
+            });
+    }
+    
+    fn refresh_canvas_list(&mut self) {
+        self.canvas_files = match crate::utils::get_notes_directory() {
+            Ok(dir) => list_canvases(&dir),
+            Err(e) => {
+                log::error!("Failed to open vault directory: {}", e);
+                Vec::new()
+            }
+        };
+    }
+    
+    fn open_canvas(&mut self, path: PathBuf) {
+        if self.text_edited_at.is_some() {
+            self.save();
+        }
+        match Canvas::load(&path) {
+            Ok(canvas) => {
+                self.canvas = canvas;
+                self.canvas_path = Some(path);
+                self.selected_node = None;
+                self.pan_offset = egui::Vec2::ZERO;
+                self.status = None;
+            }
+            Err(e) => {
+                log::error!("Failed to load canvas {}: {}", path.display(), e);
+                self.status = Some("Failed to load canvas".to_string());
+            }
+        }
+    }
+    
+    fn create_canvas(&mut self) {
+        let name = crate::utils::sanitize_filename(&self.new_canvas_name);
+        if name.is_empty() {
+            return;
+        }
+        let path = match crate::utils::get_notes_directory() {
+            Ok(dir) => dir.join(format!("{}.{}", name, CANVAS_EXTENSION)),
+            Err(e) => {
+                log::error!("Failed to open vault directory: {}", e);
+                return;
+            }
+        };
+        if path.exists() {
+            self.status = Some(format!("\"{}\" already exists", name));
+            return;
+        }
+        
+        if self.text_edited_at.is_some() {
+            self.save();
+        }
+        self.canvas = Canvas::new();
+        self.canvas_path = Some(path);
+        self.selected_node = None;
+        self.save();
+        self.new_canvas_name.clear();
+        self.refresh_canvas_list();
+    }
+    
+    fn save(&mut self) {
+        self.text_edited_at = None;
+        if let Some(path) = &self.canvas_path {
+            if let Err(e) = self.canvas.save(path) {
+                log::error!("Failed to save canvas {}: {}", path.display(), e);
+                self.status = Some("Failed to save canvas".to_string());
+            }
+        }
+    }
+    
+    fn to_screen(&self, world: egui::Vec2, center: egui::Pos2) -> egui::Pos2 {
+        center + (world + self.pan_offset) * self.zoom_level
+    }
+    
+    fn to_world(&self, screen: egui::Pos2, center: egui::Pos2) -> egui::Vec2 {
+        (screen - center) / self.zoom_level - self.pan_offset
+    }
+}
+
+impl Default for CanvasView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Midpoint of the side of `node` that faces `other`.
+fn side_anchor(node: &CanvasNode, other: &CanvasNode) -> egui::Vec2 {
+    let (x, y) = node.center();
+    let (other_x, other_y) = other.center();
+    let (dx, dy) = (other_x - x, other_y - y);
+    let (half_width, half_height) = (node.width as f32 / 2.0, node.height as f32 / 2.0);
+    
+    if dx.abs() * half_height > dy.abs() * half_width {
+        egui::vec2(x + half_width * dx.signum(), y)
+    } else {
+        egui::vec2(x, y + half_height * dy.signum())
+    }
+}
+
+fn draw_arrow_head(painter: &egui::Painter, from: egui::Pos2, to: egui::Pos2, stroke: egui::Stroke, zoom: f32) {
+    let direction = (to - from).normalized();
+    let size = 10.0 * zoom.max(0.5);
+    let back = to - direction * size;
+    let normal = egui::vec2(-direction.y, direction.x) * size * 0.5;
+    painter.line_segment([to, back + normal], stroke);
+    painter.line_segment([to, back - normal], stroke);
+}
+
+/// JSON Canvas preset colors "1"-"6", or a `#rrggbb` hex color.
+fn canvas_color(color: &str) -> Option<egui::Color32> {
+    match color {
+        "1" => Some(egui::Color32::from_rgb(233, 73, 73)),
+        "2" => Some(egui::Color32::from_rgb(236, 117, 0)),
+        "3" => Some(egui::Color32::from_rgb(224, 172, 0)),
+        "4" => Some(egui::Color32::from_rgb(8, 185, 78)),
+        "5" => Some(egui::Color32::from_rgb(0, 191, 188)),
+        "6" => Some(egui::Color32::from_rgb(120, 82, 238)),
+        hex => {
+            let hex = hex.strip_prefix('#')?;
+            if hex.len() != 6 {
+                return None;
+            }
+            let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+            Some(egui::Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?))
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use eframe::egui;
-use crate::features::Note;
+use std::collections::HashSet;
+use crate::features::{Note, LinkProcessor, QueryFormat, QueryResult, FenceLine, FenceScanner};
 
 pub struct MarkdownEditor {
     content: String,
     is_preview_mode: bool,
     cursor_position: usize,
+    link_processor: LinkProcessor,
+    clicked_link: Option<String>,
+    pending_jump: Option<usize>, // Byte offset to move the cursor to on the next frame
 }
 
 impl MarkdownEditor {
@@ -13,10 +17,30 @@ impl MarkdownEditor {
             content: String::new(),
             is_preview_mode: false,
             cursor_position: 0,
+            link_processor: LinkProcessor::new(),
+            clicked_link: None,
+            pending_jump: None,
         }
     }
     
-    pub fn show(&mut self, ui: &mut egui::Ui, note: &mut Note) {
+    pub fn is_preview_mode(&self) -> bool {
+        self.is_preview_mode
+    }
+    
+    /// Target of the `[[link]]` clicked in preview since the last call.
+    pub fn take_clicked_link(&mut self) -> Option<String> {
+        self.clicked_link.take()
+    }
+    
+    /// Switches to editing and puts the cursor at `offset`, a byte offset in
+    /// the note content, scrolling it into view.
+    pub fn jump_to(&mut self, offset: usize) {
+        self.is_preview_mode = false;
+        self.pending_jump = Some(offset);
+    }
+    
+    /// `query_results` holds the result of each ```query block in the note, in order.
+    pub fn show(&mut self, ui: &mut egui::Ui, note: &mut Note, unresolved_links: &HashSet<String>, query_results: &[Result<QueryResult, String>]) {
         ui.horizontal(|ui| {
             ui.heading(&note.title);
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -40,7 +64,7 @@ impl MarkdownEditor {
             .auto_shrink([false, false])
             .show(ui, |ui| {
                 if self.is_preview_mode {
-                    self.show_preview(ui, &note.content);
+                    self.show_preview(ui, &note.content, unresolved_links, query_results);
                 } else {
                     self.show_editor(ui, &mut note.content);
                 }
@@ -84,14 +108,44 @@ impl MarkdownEditor {
             .desired_width(f32::INFINITY)
             .desired_rows(20);
             
-        ui.add(text_edit);
+        let mut output = text_edit.show(ui);
+        
+        if let Some(offset) = self.pending_jump.take() {
+            let mut offset = offset.min(content.len());
+            while !content.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            let cursor = egui::text::CCursor::new(content[..offset].chars().count());
+            output.state.set_ccursor_range(Some(egui::text::CCursorRange::one(cursor)));
+            output.state.store(ui.ctx(), output.response.id);
+            output.response.request_focus();
+            
+            let rect = output.galley.pos_from_cursor(&output.galley.from_ccursor(cursor));
+            ui.scroll_to_rect(rect.translate(output.text_draw_pos.to_vec2()), Some(egui::Align::Center));
+            self.cursor_position = offset;
+        }
     }
     
-    fn show_preview(&self, ui: &mut egui::Ui, content: &str) {
+    fn show_preview(&mut self, ui: &mut egui::Ui, content: &str, unresolved_links: &HashSet<String>, query_results: &[Result<QueryResult, String>]) {
         // Simple markdown preview - in production would use pulldown-cmark
-        let lines: Vec<&str> = content.lines().collect();
+        let mut fences = FenceScanner::new();
+        let mut query_index = 0;
         
-        for line in lines {
+        for line in content.lines() {
+            match fences.classify(line) {
+                FenceLine::Text => {}
+                FenceLine::Close("query") => {
+                    self.show_query_result(ui, query_results.get(query_index), query_index);
+                    query_index += 1;
+                    continue;
+                }
+                FenceLine::Inside(info) if info != "query" => {
+                    ui.monospace(line);
+                    continue;
+                }
+                _ => continue,
+            }
+            
             if line.starts_with("# ") {
                 ui.heading(&line[2..]);
             } else if line.starts_with("## ") {
@@ -101,13 +155,102 @@ impl MarkdownEditor {
             } else if line.starts_with("- ") || line.starts_with("* ") {
                 ui.horizontal(|ui| {
                     ui.label("•");
-                    ui.label(&line[2..]);
+                    self.show_inline(ui, &line[2..], unresolved_links);
                 });
             } else if line.trim().is_empty() {
                 ui.separator();
             } else {
-                ui.label(line);
+                self.show_inline(ui, line, unresolved_links);
+            }
+        }
+    }
+    
+    fn show_query_result(&mut self, ui: &mut egui::Ui, result: Option<&Result<QueryResult, String>>, index: usize) {
+        let result = match result {
+            Some(Ok(result)) => result,
+            Some(Err(error)) => {
+                ui.colored_label(egui::Color32::from_rgb(200, 60, 60), format!("Query error: {}", error));
+                return;
+            }
+            None => return,
+        };
+        
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            if result.rows.is_empty() {
+                ui.weak("No matching notes.");
+                return;
             }
+            
+            if result.format == QueryFormat::List {
+                for row in &result.rows {
+                    ui.horizontal(|ui| {
+                        ui.label("•");
+                        if ui.link(&row.title).clicked() {
+                            self.clicked_link = Some(row.title.clone());
+                        }
+                    });
+                }
+                return;
+            }
+            
+            egui::Grid::new(("query_result", index)).striped(true).show(ui, |ui| {
+                for column in &result.columns {
+                    ui.strong(column);
+                }
+                ui.end_row();
+                
+                for row in &result.rows {
+                    for (i, value) in row.values.iter().enumerate() {
+                        if i == 0 {
+                            if ui.link(value).clicked() {
+                                self.clicked_link = Some(row.title.clone());
+                            }
+                        } else {
+                            ui.label(value);
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.weak(format!("{} note(s)", result.rows.len()));
+        });
+    }
+    
+    /// Renders a line of text with clickable `[[links]]`; links to notes that
+    /// don't exist yet are shown in red.
+    fn show_inline(&mut self, ui: &mut egui::Ui, text: &str, unresolved_links: &HashSet<String>) {
+        let links = self.link_processor.extract_wiki_links(text);
+        if links.is_empty() {
+            ui.label(text);
+            return;
         }
+        
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 0.0;
+            let mut last_end = 0;
+            
+            for link in &links {
+                if link.start > last_end {
+                    ui.label(&text[last_end..link.start]);
+                }
+                
+                let label = link.alias().unwrap_or(link.target());
+                let response = if unresolved_links.contains(&link.target().to_lowercase()) {
+                    ui.link(egui::RichText::new(label).color(egui::Color32::from_rgb(200, 60, 60)))
+                        .on_hover_text(format!("\"{}\" doesn't exist yet. Click to create it.", link.target()))
+                } else {
+                    ui.link(label)
+                };
+                
+                if response.clicked() {
+                    self.clicked_link = Some(link.target().to_string());
+                }
+                last_end = link.end;
+            }
+            
+            if last_end < text.len() {
+                ui.label(&text[last_end..]);
+            }
+        });
     }
-}
\ No newline at end of file
+}
@@ -1,28 +1,124 @@
 use eframe::egui;
-use crate::features::NoteManager;
-use std::collections::HashMap;
+use crate::features::{LinkKind, Note, NoteManager};
+use crate::utils::{GraphExportFormat, GraphExporter, GraphSnapshot, GraphSnapshotEdge, GraphSnapshotNode};
+use crate::utils::ForceLayout;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// How long the filter box has to sit still before the graph is rebuilt.
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(300);
 
 pub struct GraphView {
     nodes: Vec<GraphNode>,
     edges: Vec<GraphEdge>,
+    layout: ForceLayout,
     selected_node: Option<String>,
     zoom_level: f32,
     pan_offset: egui::Vec2,
+    hide_orphans: bool,
+    highlighted_path: Vec<String>, // Shift-click a second node to show the path to it
+    filter: GraphFilter,
+    color_rules: Vec<ColorRule>,
+    new_rule_pattern: String,
+    new_rule_color: egui::Color32,
+    dragging_node: Option<String>,
+    built_from: Option<(u64, GraphFilter, Option<String>)>, // graph revision, filter and local centre the nodes reflect
+    applied_query: String, // The filter query as of the last pause in typing
+    query_edited_at: Option<Instant>,
+    open_request: Option<String>,
+    export_status: Option<String>,
+}
+
+/// Which notes the graph shows.
+#[derive(Debug, Clone, PartialEq)]
+struct GraphFilter {
+    tag: Option<String>,
+    folder_id: Option<String>,
+    query: String,
+    local_graph: bool, // Only notes within `local_depth` links of the current note
+    local_depth: usize,
+}
+
+impl Default for GraphFilter {
+    fn default() -> Self {
+        Self {
+            tag: None,
+            folder_id: None,
+            query: String::new(),
+            local_graph: false,
+            local_depth: 2,
+        }
+    }
+}
+
+/// Colors every node matching `pattern`; the first matching rule wins.
+#[derive(Debug, Clone)]
+struct ColorRule {
+    pattern: NodePattern,
+    color: egui::Color32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum NodePattern {
+    Tag(String),    // tag:project
+    Folder(String), // folder:Work
+    Text(String),   // anything else, matched against title and content
+}
+
+impl NodePattern {
+    fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if input.is_empty() {
+            return None;
+        }
+        
+        if let Some(tag) = input.strip_prefix("tag:") {
+            Some(NodePattern::Tag(tag.trim().trim_start_matches('#').to_string()))
+        } else if let Some(folder) = input.strip_prefix("folder:") {
+            Some(NodePattern::Folder(folder.trim().to_string()))
+        } else {
+            Some(NodePattern::Text(input.to_string()))
+        }
+    }
+    
+    fn matches(&self, note: &Note, folder_name: Option<&str>) -> bool {
+        match self {
+            NodePattern::Tag(tag) => note.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            NodePattern::Folder(folder) => folder_name.is_some_and(|name| name.eq_ignore_ascii_case(folder)),
+            NodePattern::Text(text) => {
+                let text = text.to_lowercase();
+                note.title.to_lowercase().contains(&text) || note.content.to_lowercase().contains(&text)
+            }
+        }
+    }
+    
+    fn describe(&self) -> String {
+        match self {
+            NodePattern::Tag(tag) => format!("tag:{}", tag),
+            NodePattern::Folder(folder) => format!("folder:{}", folder),
+            NodePattern::Text(text) => format!("\"{}\"", text),
+        }
+    }
 }
 
 #[derive(Clone)]
 struct GraphNode {
     id: String,
     title: String,
-    position: egui::Pos2,
+    tags: Vec<String>,
     size: f32,
     color: egui::Color32,
+    links_in: usize,
+    links_out: usize,
 }
 
 #[derive(Clone)]
 struct GraphEdge {
     from: String,
     to: String,
+    kind: LinkKind,
+    relation: Option<String>,
+    label: Option<String>, // Relation name, or alias/markdown text when it differs from the target title
 }
 
 impl GraphView {
@@ -30,13 +126,31 @@ impl GraphView {
         Self {
             nodes: Vec::new(),
             edges: Vec::new(),
+            layout: ForceLayout::new(),
             selected_node: None,
             zoom_level: 1.0,
             pan_offset: egui::Vec2::ZERO,
+            hide_orphans: false,
+            highlighted_path: Vec::new(),
+            filter: GraphFilter::default(),
+            color_rules: Vec::new(),
+            new_rule_pattern: String::new(),
+            new_rule_color: egui::Color32::from_rgb(230, 120, 30),
+            dragging_node: None,
+            built_from: None,
+            applied_query: String::new(),
+            query_edited_at: None,
+            open_request: None,
+            export_status: None,
         }
     }
     
-    pub fn show(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager) {
+    /// Note double-clicked in the graph since the last call.
+    pub fn take_open_request(&mut self) -> Option<String> {
+        self.open_request.take()
+    }
+    
+    pub fn show(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
         ui.vertical(|ui| {
             // Graph controls
             ui.horizontal(|ui| {
@@ -45,7 +159,8 @@ impl GraphView {
                 ui.separator();
                 
                 if ui.button("🔄 Refresh").clicked() {
-                    self.update_graph(note_manager);
+                    self.built_from = None;
+                    self.layout.wake();
                 }
                 
                 if ui.button("🎯 Center").clicked() {
@@ -62,8 +177,33 @@ impl GraphView {
                 if ui.button("🔍 Focus Selected").clicked() {
                     self.focus_selected_node();
                 }
+                
+                if ui.checkbox(&mut self.hide_orphans, "Hide orphans").changed() {
+                    self.built_from = None;
+                }
+                
+                if ui.button("📌 Unpin All").clicked() {
+                    self.layout.unpin_all();
+                }
+                
+                ui.separator();
+                
+                ui.menu_button("💾 Export", |ui| {
+                    for format in GraphExportFormat::all() {
+                        if ui.button(format.label()).clicked() {
+                            self.export(*format);
+                            ui.close_menu();
+                        }
+                    }
+                });
+                
+                if let Some(status) = &self.export_status {
+                    ui.weak(status);
+                }
             });
             
+            self.show_filters(ui, note_manager);
+            
             ui.separator();
             
             // Graph canvas
@@ -72,70 +212,305 @@ impl GraphView {
                 egui::Sense::click_and_drag(),
             );
             
-            self.handle_graph_interaction(&response);
+            self.handle_graph_interaction(&response, note_manager);
             self.draw_graph(&painter, response.rect);
         });
         
-        // Update graph data if needed
-        if self.nodes.is_empty() {
-            self.update_graph(note_manager);
+        // Rebuild when links, filters or the local-graph centre change
+        let local_center = if self.filter.local_graph { current_note_id.cloned() } else { None };
+        match self.query_edited_at {
+            Some(at) if at.elapsed() < FILTER_DEBOUNCE => ui.ctx().request_repaint_after(FILTER_DEBOUNCE - at.elapsed()),
+            _ => {
+                self.query_edited_at = None;
+                self.applied_query.clone_from(&self.filter.query);
+            }
         }
+        let filter = GraphFilter { query: self.applied_query.clone(), ..self.filter.clone() };
+        let wanted = (note_manager.link_graph().revision(), filter, local_center);
+        if self.built_from.as_ref() != Some(&wanted) {
+            self.update_graph(note_manager, wanted.2.as_deref());
+            self.built_from = Some(wanted);
+        }
+        
+        if self.layout.step() {
+            ui.ctx().request_repaint();
+        }
+    }
+    
+    fn show_filters(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager) {
+        ui.horizontal(|ui| {
+            ui.label("Tag:");
+            egui::ComboBox::from_id_source("graph_tag_filter")
+                .selected_text(self.filter.tag.clone().unwrap_or_else(|| "All".to_string()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.filter.tag, None, "All");
+                    for tag in note_manager.get_all_tags() {
+                        ui.selectable_value(&mut self.filter.tag, Some(tag.clone()), format!("#{}", tag));
+                    }
+                });
+            
+            ui.label("Folder:");
+            let mut folders = note_manager.get_folders();
+            folders.sort_by(|a, b| a.name.cmp(&b.name));
+            let folder_name = self.filter.folder_id.as_ref()
+                .and_then(|id| folders.iter().find(|f| &f.id == id))
+                .map(|f| f.name.clone())
+                .unwrap_or_else(|| "All".to_string());
+            egui::ComboBox::from_id_source("graph_folder_filter")
+                .selected_text(folder_name)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.filter.folder_id, None, "All");
+                    for folder in &folders {
+                        ui.selectable_value(&mut self.filter.folder_id, Some(folder.id.clone()), &folder.name);
+                    }
+                });
+            
+            let query = ui.add(egui::TextEdit::singleline(&mut self.filter.query)
+                .hint_text("Filter notes...")
+                .desired_width(140.0));
+            if query.changed() {
+                self.query_edited_at = Some(Instant::now());
+            }
+            
+            ui.separator();
+            
+            ui.checkbox(&mut self.filter.local_graph, "Local graph");
+            if self.filter.local_graph {
+                ui.add(egui::Slider::new(&mut self.filter.local_depth, 1..=5).text("hops"));
+            }
+            
+            ui.separator();
+            
+            ui.menu_button("🎨 Groups", |ui| self.show_color_rules(ui));
+        });
     }
     
-    fn update_graph(&mut self, note_manager: &NoteManager) {
+    fn show_color_rules(&mut self, ui: &mut egui::Ui) {
+        let mut remove = None;
+        let mut changed = false;
+        
+        for (i, rule) in self.color_rules.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                changed |= ui.color_edit_button_srgba(&mut rule.color).changed();
+                ui.label(rule.pattern.describe());
+                if ui.small_button("×").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        
+        if let Some(i) = remove {
+            self.color_rules.remove(i);
+            changed = true;
+        }
+        
+        ui.separator();
+        
+        ui.horizontal(|ui| {
+            ui.color_edit_button_srgba(&mut self.new_rule_color);
+            ui.add(egui::TextEdit::singleline(&mut self.new_rule_pattern)
+                .hint_text("tag:x, folder:y or text")
+                .desired_width(140.0));
+            if ui.button("Add").clicked() {
+                if let Some(pattern) = NodePattern::parse(&self.new_rule_pattern) {
+                    self.color_rules.push(ColorRule { pattern, color: self.new_rule_color });
+                    self.new_rule_pattern.clear();
+                    changed = true;
+                }
+            }
+        });
+        
+        if changed {
+            self.built_from = None;
+        }
+    }
+    
+    fn update_graph(&mut self, note_manager: &NoteManager, local_center: Option<&str>) {
         self.nodes.clear();
         self.edges.clear();
         
-        let notes = note_manager.get_all_notes();
-        let mut node_positions = HashMap::new();
+        let graph = note_manager.link_graph();
+        let folder_names: std::collections::HashMap<&str, &str> = note_manager.get_folders()
+            .into_iter()
+            .map(|folder| (folder.id.as_str(), folder.name.as_str()))
+            .collect();
+        let folder_of = |note: &Note| note.folder_id.as_deref().and_then(|id| folder_names.get(id).copied());
+        
+        let orphans: HashSet<&str> = if self.hide_orphans {
+            graph.orphans().into_iter().collect()
+        } else {
+            HashSet::new()
+        };
+        let local: Option<HashSet<String>> = local_center.map(|center| {
+            graph.neighborhood(center, self.filter.local_depth)
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect()
+        });
+        let query = NodePattern::parse(&self.applied_query);
         
-        // Create nodes
-        for (i, note) in notes.iter().enumerate() {
-            let angle = (i as f32) * 2.0 * std::f32::consts::PI / (notes.len() as f32);
-            let radius = 200.0;
-            let x = radius * angle.cos();
-            let y = radius * angle.sin();
+        let mut notes: Vec<&Note> = note_manager.get_all_notes()
+            .into_iter()
+            .filter(|note| !orphans.contains(note.id.as_str()))
+            .filter(|note| local.as_ref().is_none_or(|ids| ids.contains(&note.id)))
+            .filter(|note| self.filter.tag.as_ref().is_none_or(|tag| note.tags.contains(tag)))
+            .filter(|note| self.filter.folder_id.is_none() || note.folder_id == self.filter.folder_id)
+            .filter(|note| query.as_ref().is_none_or(|pattern| pattern.matches(note, folder_of(note))))
+            .collect();
+        notes.sort_by(|a, b| a.title.cmp(&b.title));
+        
+        // Size nodes by how central they are in the link graph
+        let scores = graph.pagerank();
+        let max_score = scores.values().cloned().fold(0.0, f64::max).max(f64::EPSILON);
+        
+        for note in &notes {
+            let default_color = if note.tags.is_empty() {
+                egui::Color32::from_rgb(0, 120, 212) // Open Blue
+            } else {
+                egui::Color32::from_rgb(0, 204, 106) // Community Green
+            };
+            let color = self.color_rules.iter()
+                .find(|rule| rule.pattern.matches(note, folder_of(note)))
+                .map_or(default_color, |rule| rule.color);
             
-            let node = GraphNode {
+            self.nodes.push(GraphNode {
                 id: note.id.clone(),
                 title: note.title.clone(),
-                position: egui::pos2(x, y),
-                size: 20.0 + (note.content.len() as f32).sqrt() * 0.1,
-                color: if note.tags.is_empty() {
-                    egui::Color32::from_rgb(0, 120, 212) // Open Blue
-                } else {
-                    egui::Color32::from_rgb(0, 204, 106) // Community Green
-                },
-            };
-            
-            node_positions.insert(note.id.clone(), node.position);
-            self.nodes.push(node);
+                tags: note.tags.clone(),
+                size: 12.0 + 24.0 * (scores.get(&note.id).copied().unwrap_or(0.0) / max_score) as f32,
+                color,
+                links_in: graph.incoming(&note.id).len(),
+                links_out: graph.outgoing(&note.id).len(),
+            });
         }
         
-        // Create edges based on links
+        // Create edges based on links; the link graph already keeps one edge per pair
+        let visible: HashSet<&str> = notes.iter().map(|note| note.id.as_str()).collect();
         for note in &notes {
-            let links = note_manager.get_note_links(&note.id);
-            for link in links {
-                if node_positions.contains_key(&link) {
-                    self.edges.push(GraphEdge {
-                        from: note.id.clone(),
-                        to: link,
-                    });
+            for (to, edge) in graph.outgoing_edges(&note.id) {
+                if !visible.contains(to) {
+                    continue;
                 }
+                self.edges.push(GraphEdge {
+                    from: note.id.clone(),
+                    to: to.to_string(),
+                    kind: edge.kind,
+                    relation: edge.relation.clone(),
+                    label: edge.label.clone(),
+                });
             }
         }
+        
+        let node_ids: Vec<String> = self.nodes.iter().map(|node| node.id.clone()).collect();
+        let edge_pairs: Vec<(String, String)> = self.edges.iter().map(|edge| (edge.from.clone(), edge.to.clone())).collect();
+        self.layout.sync(&node_ids, &edge_pairs);
+    }
+    
+    /// The graph exactly as shown: visible nodes at their laid-out positions.
+    pub fn snapshot(&self) -> GraphSnapshot {
+        let nodes = self.nodes.iter()
+            .filter_map(|node| {
+                let position = self.layout.position(&node.id)?;
+                Some(GraphSnapshotNode {
+                    id: node.id.clone(),
+                    title: node.title.clone(),
+                    tags: node.tags.clone(),
+                    x: position.x,
+                    y: position.y,
+                    radius: node.size,
+                    color: [node.color.r(), node.color.g(), node.color.b()],
+                })
+            })
+            .collect();
+        
+        let edges = self.edges.iter()
+            .map(|edge| {
+                let color = edge_color(edge);
+                GraphSnapshotEdge {
+                    from: edge.from.clone(),
+                    to: edge.to.clone(),
+                    kind: edge.kind,
+                    relation: edge.relation.clone(),
+                    label: edge.label.clone(),
+                    color: [color.r(), color.g(), color.b()],
+                }
+            })
+            .collect();
+        
+        GraphSnapshot { nodes, edges }
+    }
+    
+    fn export(&mut self, format: GraphExportFormat) {
+        let result = crate::utils::get_exports_directory().and_then(|dir| {
+            let path = dir.join(format!(
+                "graph-{}.{}",
+                chrono::Local::now().format("%Y%m%d-%H%M%S"),
+                format.extension(),
+            ));
+            GraphExporter::new().export(&self.snapshot(), format, &path)?;
+            Ok(path)
+        });
+        
+        self.export_status = Some(match result {
+            Ok(path) => format!("Saved {}", path.display()),
+            Err(e) => {
+                log::error!("Failed to export graph: {}", e);
+                format!("Export failed: {}", e)
+            }
+        });
     }
     
-    fn handle_graph_interaction(&mut self, response: &egui::Response) {
-        // Handle panning
+    fn handle_graph_interaction(&mut self, response: &egui::Response, note_manager: &NoteManager) {
+        let center = response.rect.center();
+        
+        // Dragging a node moves and pins it; dragging empty space pans
+        if response.drag_started() {
+            self.dragging_node = response.interact_pointer_pos()
+                .and_then(|pos| self.find_node_at_position(pos, center));
+        }
         if response.dragged() {
-            self.pan_offset += response.drag_delta();
+            match (&self.dragging_node, response.interact_pointer_pos()) {
+                (Some(node_id), Some(pos)) => {
+                    let world = self.to_world(pos, center);
+                    self.layout.set_position(node_id, world);
+                }
+                _ => self.pan_offset += response.drag_delta() / self.zoom_level,
+            }
+        }
+        if response.drag_released() {
+            self.dragging_node = None;
+        }
+        
+        if response.double_clicked() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                self.open_request = self.find_node_at_position(pos, center);
+            }
+        }
+        
+        // Right-click toggles whether a node is pinned
+        if response.secondary_clicked() {
+            if let Some(node_id) = response.interact_pointer_pos().and_then(|pos| self.find_node_at_position(pos, center)) {
+                self.layout.toggle_pin(&node_id);
+            }
         }
         
         // Handle node selection
         if response.clicked() {
             if let Some(pointer_pos) = response.interact_pointer_pos() {
-                self.selected_node = self.find_node_at_position(pointer_pos);
+                let clicked = self.find_node_at_position(pointer_pos, center);
+                let shift_held = response.ctx.input(|i| i.modifiers.shift);
+                
+                match (&self.selected_node, &clicked) {
+                    (Some(from), Some(to)) if shift_held => {
+                        self.highlighted_path = note_manager.link_graph()
+                            .shortest_path(from, to)
+                            .unwrap_or_default();
+                    }
+                    _ => {
+                        self.selected_node = clicked;
+                        self.highlighted_path.clear();
+                    }
+                }
             }
         }
         
@@ -154,23 +529,46 @@ impl GraphView {
         
         // Draw edges first (so they appear behind nodes)
         for edge in &self.edges {
-            if let (Some(from_node), Some(to_node)) = (
-                self.nodes.iter().find(|n| n.id == edge.from),
-                self.nodes.iter().find(|n| n.id == edge.to),
+            if let (Some(from_world), Some(to_world)) = (
+                self.layout.position(&edge.from),
+                self.layout.position(&edge.to),
             ) {
-                let from_pos = center + (from_node.position.to_vec2() + self.pan_offset) * self.zoom_level;
-                let to_pos = center + (to_node.position.to_vec2() + self.pan_offset) * self.zoom_level;
+                let from_pos = self.to_screen(from_world, center);
+                let to_pos = self.to_screen(to_world, center);
                 
-                painter.line_segment(
-                    [from_pos, to_pos],
-                    egui::Stroke::new(1.0, egui::Color32::GRAY),
-                );
+                let on_path = self.highlighted_path.windows(2).any(|pair| {
+                    (pair[0] == edge.from && pair[1] == edge.to) || (pair[0] == edge.to && pair[1] == edge.from)
+                });
+                let stroke = if on_path {
+                    egui::Stroke::new(3.0, egui::Color32::from_rgb(255, 170, 0))
+                } else if edge.relation.is_some() {
+                    egui::Stroke::new(2.0, edge_color(edge))
+                } else {
+                    egui::Stroke::new(1.0, edge_color(edge))
+                };
+                painter.line_segment([from_pos, to_pos], stroke);
+                
+                // Label edges with their link text once zoomed in
+                // Relations are always labeled; other link text only once zoomed in
+                if let Some(label) = edge.label.as_ref().filter(|_| edge.relation.is_some() || self.zoom_level >= 1.0) {
+                    let label_color = if edge.relation.is_some() { edge_color(edge) } else { egui::Color32::DARK_GRAY };
+                    painter.text(
+                        from_pos + (to_pos - from_pos) * 0.5,
+                        egui::Align2::CENTER_CENTER,
+                        label,
+                        egui::FontId::proportional(11.0),
+                        label_color,
+                    );
+                }
             }
         }
         
         // Draw nodes
         for node in &self.nodes {
-            let pos = center + (node.position.to_vec2() + self.pan_offset) * self.zoom_level;
+            let pos = match self.layout.position(&node.id) {
+                Some(world) => self.to_screen(world, center),
+                None => continue,
+            };
             let radius = node.size * self.zoom_level;
             
             let color = if Some(&node.id) == self.selected_node.as_ref() {
@@ -181,7 +579,12 @@ impl GraphView {
             
             // Draw node circle
             painter.circle_filled(pos, radius, color);
-            painter.circle_stroke(pos, radius, egui::Stroke::new(2.0, egui::Color32::WHITE));
+            let outline = if self.layout.is_pinned(&node.id) {
+                egui::Color32::from_rgb(230, 120, 30)
+            } else {
+                egui::Color32::WHITE
+            };
+            painter.circle_stroke(pos, radius, egui::Stroke::new(2.0, outline));
             
             // Draw node label if zoomed in enough
             if self.zoom_level > 0.5 {
@@ -201,7 +604,7 @@ impl GraphView {
             if let Some(node) = self.nodes.iter().find(|n| n.id == *selected_id) {
                 let info_rect = egui::Rect::from_min_size(
                     rect.min + egui::vec2(10.0, 10.0),
-                    egui::vec2(200.0, 60.0),
+                    egui::vec2(220.0, 80.0),
                 );
                 
                 painter.rect_filled(info_rect, 5.0, egui::Color32::from_black_alpha(200));
@@ -218,7 +621,20 @@ impl GraphView {
                 painter.text(
                     info_rect.min + egui::vec2(10.0, 30.0),
                     egui::Align2::LEFT_TOP,
-                    "Click to open note",
+                    format!("{} in · {} out", node.links_in, node.links_out),
+                    egui::FontId::default(),
+                    egui::Color32::LIGHT_GRAY,
+                );
+                
+                let hint = if self.highlighted_path.len() > 1 {
+                    format!("Path: {} hops", self.highlighted_path.len() - 1)
+                } else {
+                    "Shift-click: path · Double-click: open".to_string()
+                };
+                painter.text(
+                    info_rect.min + egui::vec2(10.0, 50.0),
+                    egui::Align2::LEFT_TOP,
+                    hint,
                     egui::FontId::default(),
                     egui::Color32::LIGHT_GRAY,
                 );
@@ -226,15 +642,22 @@ impl GraphView {
         }
     }
     
-    fn find_node_at_position(&self, pos: egui::Pos2) -> Option<String> {
-        // This is simplified - in practice you'd need to transform coordinates properly
-        for node in &self.nodes {
-            let distance = (node.position - pos).length();
-            if distance < node.size {
-                return Some(node.id.clone());
-            }
-        }
-        None
+    fn find_node_at_position(&self, pos: egui::Pos2, center: egui::Pos2) -> Option<String> {
+        // Topmost (last drawn) node wins
+        self.nodes.iter().rev()
+            .find(|node| {
+                self.layout.position(&node.id)
+                    .is_some_and(|world| (self.to_screen(world, center) - pos).length() < node.size * self.zoom_level)
+            })
+            .map(|node| node.id.clone())
+    }
+    
+    fn to_screen(&self, world: egui::Vec2, center: egui::Pos2) -> egui::Pos2 {
+        center + (world + self.pan_offset) * self.zoom_level
+    }
+    
+    fn to_world(&self, screen: egui::Pos2, center: egui::Pos2) -> egui::Vec2 {
+        (screen - center) / self.zoom_level - self.pan_offset
     }
     
     fn center_graph(&mut self) {
@@ -244,10 +667,32 @@ impl GraphView {
     
     fn focus_selected_node(&mut self) {
         if let Some(selected_id) = &self.selected_node {
-            if let Some(node) = self.nodes.iter().find(|n| n.id == *selected_id) {
-                self.pan_offset = -node.position.to_vec2();
+            if let Some(position) = self.layout.position(selected_id) {
+                self.pan_offset = -position;
                 self.zoom_level = 1.5;
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Plain links are grey and embeds purple; each relation name gets a stable
+/// color of its own.
+fn edge_color(edge: &GraphEdge) -> egui::Color32 {
+    const RELATION_COLORS: [egui::Color32; 6] = [
+        egui::Color32::from_rgb(220, 80, 60),
+        egui::Color32::from_rgb(0, 150, 136),
+        egui::Color32::from_rgb(230, 140, 0),
+        egui::Color32::from_rgb(63, 81, 181),
+        egui::Color32::from_rgb(156, 39, 176),
+        egui::Color32::from_rgb(96, 125, 139),
+    ];
+    
+    match (&edge.relation, edge.kind) {
+        (Some(relation), _) => {
+            let hash = relation.bytes().fold(0usize, |hash, b| hash.wrapping_mul(31).wrapping_add(b as usize));
+            RELATION_COLORS[hash % RELATION_COLORS.len()]
+        }
+        (None, LinkKind::Embed) => egui::Color32::from_rgb(150, 110, 200),
+        (None, LinkKind::Wiki | LinkKind::Markdown) => egui::Color32::GRAY,
+    }
+}
@@ -1,26 +1,35 @@
 use eframe::egui;
 use std::sync::{Arc, Mutex};
 use crate::storage::Database;
-use crate::features::{Note, NoteManager, CollaborationManager, CollaborativeEdit, User};
+use crate::features::{Note, NoteManager, CollaborationManager, CollaborativeEdit, User, RenamePreview, RenameUndo, Recurrence, ReminderNotification, parse_reminder_time, SavedQuery, DiffLine, QueryResult};
 
 mod editor;
 mod sidebar;
 mod toolbar;
 mod settings;
 mod graph_view;
+mod tasks_view;
+mod board_view;
+mod canvas_view;
 mod accessibility;
 
 pub use editor::MarkdownEditor;
-pub use sidebar::{LeftSidebar, RightSidebar};
+pub use sidebar::{LeftSidebar, RightSidebar, SidebarAction};
 pub use toolbar::Toolbar;
 pub use settings::SettingsPanel;
 pub use graph_view::GraphView;
+pub use tasks_view::TasksView;
+pub use board_view::BoardView;
+pub use canvas_view::CanvasView;
 pub use accessibility::{AccessibilitySupport, FocusManager};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum AppView {
     Editor,
     GraphView,
+    Tasks,
+    Board,
+    Canvas,
     Settings,
 }
 
@@ -59,6 +68,67 @@ impl Default for EnhanceModalState {
     }
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct RenameModalState {
+    pub is_open: bool,
+    pub new_title: String,
+    pub preview: Option<RenamePreview>,
+    pub undo: Option<RenameUndo>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BrokenLinksModalState {
+    pub is_open: bool,
+}
+
+/// An earlier version of a note compared line by line with the current one.
+#[derive(Debug, Clone, Default)]
+pub struct VersionDiffModalState {
+    pub is_open: bool,
+    pub version_id: String,
+    pub heading: String,
+    pub diff: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CreateFromLinkState {
+    pub is_open: bool,
+    pub title: String,
+    pub template_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReminderModalState {
+    pub is_open: bool,
+    pub when: String,
+    pub repeat: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CommandPaletteState {
+    pub is_open: bool,
+    pub query: String,
+    pub selected: usize,
+    pub saved_search_id: Option<String>, // Set while listing a saved search's notes
+}
+
+#[derive(Debug, Clone)]
+enum PaletteCommand {
+    NewNote,
+    SwitchView(AppView),
+    RenameNote,
+    BrokenLinks,
+    AddReminder,
+    ToggleLeftSidebar,
+    ToggleRightSidebar,
+    OpenSettings,
+    SaveSearch(String),
+    ShowSavedSearch(String),
+    OpenNote(String),
+}
+
 pub struct EdisonNoteApp {
     db: Arc<Mutex<Database>>,
     note_manager: NoteManager,
@@ -77,16 +147,38 @@ pub struct EdisonNoteApp {
     toolbar: Toolbar,
     settings_panel: SettingsPanel,
     graph_view: GraphView,
+    tasks_view: TasksView,
+    board_view: BoardView,
+    canvas_view: CanvasView,
     
     // UI State
     search_query: String,
     show_enhance_modal: bool,
     word_count: usize,
     sync_status: String,
+    query_results: Vec<Result<QueryResult, String>>, // ```query block results for the previewed note
+    query_results_key: Option<(String, u64, chrono::DateTime<chrono::Utc>)>, // note id, search revision and edit time they were run for
     
     // Enhanced AI Modal State
     enhance_modal_state: EnhanceModalState,
     
+    // Rename Modal State
+    rename_modal_state: RenameModalState,
+    
+    // Broken Link State
+    broken_links_modal_state: BrokenLinksModalState,
+    create_from_link_state: CreateFromLinkState,
+    
+    // Version History State
+    version_diff_modal_state: VersionDiffModalState,
+    
+    // Reminder State
+    reminder_modal_state: ReminderModalState,
+    reminder_notifications: Vec<ReminderNotification>,
+    
+    // Command Palette State
+    command_palette_state: CommandPaletteState,
+    
     // Collaboration State
     collaboration_manager: Option<CollaborationManager>,
     collaborative_users: Vec<User>,
@@ -114,13 +206,25 @@ impl EdisonNoteApp {
             toolbar: Toolbar::new(),
             settings_panel: SettingsPanel::new(),
             graph_view: GraphView::new(),
+            tasks_view: TasksView::new(),
+            board_view: BoardView::new(),
+            canvas_view: CanvasView::new(),
             
             search_query: String::new(),
             show_enhance_modal: false,
             word_count: 0,
             sync_status: "Ready".to_string(),
+            query_results: Vec::new(),
+            query_results_key: None,
             
             enhance_modal_state: EnhanceModalState::default(),
+            rename_modal_state: RenameModalState::default(),
+            broken_links_modal_state: BrokenLinksModalState::default(),
+            create_from_link_state: CreateFromLinkState::default(),
+            version_diff_modal_state: VersionDiffModalState::default(),
+            reminder_modal_state: ReminderModalState::default(),
+            reminder_notifications: Vec::new(),
+            command_palette_state: CommandPaletteState::default(),
             
             collaboration_manager: None,
             collaborative_users: Vec::new(),
@@ -130,6 +234,12 @@ impl EdisonNoteApp {
 }
 
 impl eframe::App for EdisonNoteApp {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Err(e) = self.note_manager.save_search_index() {
+            log::error!("Failed to save search index: {}", e);
+        }
+    }
+    
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Handle keyboard shortcuts
         self.handle_shortcuts(ctx);
@@ -157,6 +267,15 @@ impl eframe::App for EdisonNoteApp {
                 .show(ctx, |ui| {
                     self.left_sidebar.show(ui, &mut self.note_manager, &mut self.current_note_id);
                 });
+            
+            if let Some((note_id, offset)) = self.left_sidebar.take_jump_request() {
+                self.current_note_id = Some(note_id);
+                self.current_view = AppView::Editor;
+                self.editor.jump_to(offset);
+            }
+            if let Some(action) = self.left_sidebar.take_action() {
+                self.handle_sidebar_action(action);
+            }
         }
         
         // Right sidebar
@@ -173,17 +292,37 @@ impl eframe::App for EdisonNoteApp {
                         self.right_sidebar.show(ui, &self.note_manager, self.current_note_id.as_ref());
                     }
                 });
+            
+            if let Some(action) = self.right_sidebar.take_action() {
+                self.handle_sidebar_action(action);
+            }
         }
         
         // Main content area
         egui::CentralPanel::default().show(ctx, |ui| {
             match self.current_view {
                 AppView::Editor => {
-                    if let Some(note_id) = &self.current_note_id {
-                        if let Some(note) = self.note_manager.get_note(note_id) {
-                            self.editor.show(ui, note);
+                    if let Some(note_id) = self.current_note_id.clone() {
+                        let unresolved_links = self.note_manager.unresolved_targets(&note_id);
+                        // Query blocks only re-run when the note or the search index changes
+                        let preview = self.editor.is_preview_mode();
+                        if preview {
+                            let key = self.note_manager.get_note(&note_id)
+                                .map(|note| (note_id.clone(), self.note_manager.search_revision(), note.modified_at));
+                            if key != self.query_results_key {
+                                self.query_results = self.note_manager.get_query_results(&note_id);
+                                self.query_results_key = key;
+                            }
+                        }
+                        let query_results: &[Result<QueryResult, String>] = if preview { &self.query_results } else { &[] };
+                        if let Some(note) = self.note_manager.get_note_mut(&note_id) {
+                            self.editor.show(ui, note, &unresolved_links, query_results);
                             self.word_count = note.content.split_whitespace().count();
                         }
+                        
+                        if let Some(target) = self.editor.take_clicked_link() {
+                            self.open_link(&target);
+                        }
                     } else {
                         ui.centered_and_justified(|ui| {
                             ui.heading("Welcome to Edison Note");
@@ -192,7 +331,36 @@ impl eframe::App for EdisonNoteApp {
                     }
                 }
                 AppView::GraphView => {
-                    self.graph_view.show(ui, &self.note_manager);
+                    self.graph_view.show(ui, &self.note_manager, self.current_note_id.as_ref());
+                    
+                    if let Some(note_id) = self.graph_view.take_open_request() {
+                        self.current_note_id = Some(note_id);
+                        self.current_view = AppView::Editor;
+                    }
+                }
+                AppView::Tasks => {
+                    self.tasks_view.show(ui, &mut self.note_manager);
+                    
+                    if let Some(note_id) = self.tasks_view.take_open_request() {
+                        self.current_note_id = Some(note_id);
+                        self.current_view = AppView::Editor;
+                    }
+                }
+                AppView::Board => {
+                    self.board_view.show(ui, &mut self.note_manager, self.current_note_id.as_ref());
+                    
+                    if let Some(note_id) = self.board_view.take_open_request() {
+                        self.current_note_id = Some(note_id);
+                        self.current_view = AppView::Editor;
+                    }
+                }
+                AppView::Canvas => {
+                    self.canvas_view.show(ui, &self.note_manager, self.current_note_id.as_ref());
+                    
+                    if let Some(note_id) = self.canvas_view.take_open_request() {
+                        self.current_note_id = Some(note_id);
+                        self.current_view = AppView::Editor;
+                    }
                 }
                 AppView::Settings => {
                     self.settings_panel.show(ui);
@@ -205,6 +373,43 @@ impl eframe::App for EdisonNoteApp {
             self.show_enhanced_ai_modal(ctx);
         }
         
+        // Show rename modal if open
+        if self.rename_modal_state.is_open {
+            self.show_rename_modal(ctx);
+        }
+        
+        // Show broken links report if open
+        if self.broken_links_modal_state.is_open {
+            self.show_broken_links_modal(ctx);
+        }
+        
+        // Show version diff if open
+        if self.version_diff_modal_state.is_open {
+            self.show_version_diff_modal(ctx);
+        }
+        
+        // Show create-note-from-link prompt if open
+        if self.create_from_link_state.is_open {
+            self.show_create_from_link_modal(ctx);
+        }
+        
+        // Show command palette if open
+        if self.command_palette_state.is_open {
+            self.show_command_palette(ctx);
+        }
+        
+        // Show reminder dialog if open
+        if self.reminder_modal_state.is_open {
+            self.show_reminder_modal(ctx);
+        }
+        
+        // Reminders that are due, including ones missed while the app was closed
+        let due = self.note_manager.poll_reminders(chrono::Utc::now());
+        self.reminder_notifications.extend(due);
+        if !self.reminder_notifications.is_empty() {
+            self.show_reminder_notifications(ctx);
+        }
+        
         // Request repaint for animations
         ctx.request_repaint();
     }
@@ -226,9 +431,17 @@ impl EdisonNoteApp {
             }
         }
         
-        // Ctrl+Shift+P / Cmd+Shift+P - Command palette (TODO: implement)
+        // Ctrl+Shift+P / Cmd+Shift+P - Command palette
         if input.modifiers.command && input.modifiers.shift && input.key_pressed(egui::Key::P) {
-            // TODO: Show command palette
+            self.command_palette_state = CommandPaletteState {
+                is_open: !self.command_palette_state.is_open,
+                ..CommandPaletteState::default()
+            };
+        }
+        
+        // F2 - Rename current note
+        if input.key_pressed(egui::Key::F2) {
+            self.open_rename_modal();
         }
         
         // Ctrl+E / Cmd+E - Toggle enhance modal
@@ -257,11 +470,21 @@ impl EdisonNoteApp {
                     }
                     ui.close_menu();
                 }
+                if ui.button("Rename Note").clicked() {
+                    self.open_rename_modal();
+                    ui.close_menu();
+                }
                 ui.separator();
                 if ui.button("Import").clicked() {
                     ui.close_menu();
                 }
-                if ui.button("Export").clicked() {
+                if ui.add_enabled(self.current_note_id.is_some(), egui::Button::new("Export")).clicked() {
+                    if let Some(note_id) = &self.current_note_id {
+                        match self.note_manager.export_note_html(note_id) {
+                            Ok(path) => log::info!("Exported note to {}", path.display()),
+                            Err(e) => log::error!("Failed to export note: {}", e),
+                        }
+                    }
                     ui.close_menu();
                 }
             });
@@ -296,6 +519,18 @@ impl EdisonNoteApp {
                     self.current_view = AppView::GraphView;
                     ui.close_menu();
                 }
+                if ui.button("Tasks View").clicked() {
+                    self.current_view = AppView::Tasks;
+                    ui.close_menu();
+                }
+                if ui.button("Board View").clicked() {
+                    self.current_view = AppView::Board;
+                    ui.close_menu();
+                }
+                if ui.button("Canvas View").clicked() {
+                    self.current_view = AppView::Canvas;
+                    ui.close_menu();
+                }
                 if ui.button("Editor View").clicked() {
                     self.current_view = AppView::Editor;
                     ui.close_menu();
@@ -312,6 +547,18 @@ impl EdisonNoteApp {
                     }
                     ui.close_menu();
                 }
+                if ui.button("Broken Links").clicked() {
+                    self.broken_links_modal_state.is_open = true;
+                    ui.close_menu();
+                }
+                if ui.add_enabled(self.current_note_id.is_some(), egui::Button::new("Add Reminder...")).clicked() {
+                    self.open_reminder_modal();
+                    ui.close_menu();
+                }
+                if ui.button("Command Palette").clicked() {
+                    self.command_palette_state = CommandPaletteState { is_open: true, ..CommandPaletteState::default() };
+                    ui.close_menu();
+                }
                 ui.separator();
                 if ui.button("Settings").clicked() {
                     self.settings_open = true;
@@ -476,6 +723,655 @@ impl EdisonNoteApp {
             });
     }
     
+    fn handle_sidebar_action(&mut self, action: SidebarAction) {
+        match action {
+            SidebarAction::OpenNote(note_id) => {
+                self.current_note_id = Some(note_id);
+                self.current_view = AppView::Editor;
+            }
+            SidebarAction::LinkMention { target_note_id, mention } => {
+                match self.note_manager.link_mention(&target_note_id, &mention) {
+                    Ok(true) => {}
+                    Ok(false) => log::warn!("Mention in note {} changed before it could be linked", mention.source_note_id),
+                    Err(e) => log::error!("Failed to link mention: {}", e),
+                }
+            }
+            SidebarAction::DiffVersion(version_id) => {
+                let version = self.note_manager.get_note_version(&version_id);
+                match (version, self.note_manager.diff_version(&version_id)) {
+                    (Some(version), Some(diff)) => {
+                        let title = self.note_manager.get_note(&version.note_id).map(|note| note.title.clone()).unwrap_or_default();
+                        self.version_diff_modal_state = VersionDiffModalState {
+                            is_open: true,
+                            version_id,
+                            heading: format!("{} · v{} ({}) → current", title, version.version_number, version.timestamp.format("%Y-%m-%d %H:%M")),
+                            diff,
+                        };
+                    }
+                    _ => log::warn!("Version {} is no longer available", version_id),
+                }
+            }
+            SidebarAction::RestoreVersion(version_id) => self.restore_version(&version_id),
+        }
+    }
+    
+    fn restore_version(&mut self, version_id: &str) {
+        let note_id = self.note_manager.get_note_version(version_id).map(|version| version.note_id);
+        match self.note_manager.restore_version(version_id) {
+            Ok(()) => {
+                self.current_note_id = note_id;
+                self.current_view = AppView::Editor;
+            }
+            Err(e) => log::error!("Failed to restore version: {}", e),
+        }
+    }
+    
+    fn show_version_diff_modal(&mut self, ctx: &egui::Context) {
+        let mut is_open = self.version_diff_modal_state.is_open;
+        let mut restore = false;
+        
+        egui::Window::new("📜 Compare Version")
+            .open(&mut is_open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(600.0)
+            .default_height(450.0)
+            .show(ctx, |ui| {
+                let state = &self.version_diff_modal_state;
+                ui.label(&state.heading);
+                ui.horizontal(|ui| {
+                    let removed = state.diff.iter().filter(|line| matches!(line, DiffLine::Removed(_))).count();
+                    let added = state.diff.iter().filter(|line| matches!(line, DiffLine::Added(_))).count();
+                    ui.colored_label(egui::Color32::from_rgb(200, 60, 60), format!("−{} line(s) since", removed));
+                    ui.colored_label(egui::Color32::from_rgb(60, 160, 60), format!("+{} line(s) since", added));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        restore = ui.button("🔄 Restore this version").clicked();
+                    });
+                });
+                ui.separator();
+                
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for line in &state.diff {
+                        let (prefix, text, color) = match line {
+                            DiffLine::Same(text) => (" ", text, ui.visuals().weak_text_color()),
+                            DiffLine::Removed(text) => ("−", text, egui::Color32::from_rgb(200, 60, 60)),
+                            DiffLine::Added(text) => ("+", text, egui::Color32::from_rgb(60, 160, 60)),
+                        };
+                        ui.label(egui::RichText::new(format!("{} {}", prefix, text)).monospace().color(color));
+                    }
+                });
+            });
+        
+        self.version_diff_modal_state.is_open = is_open && !restore;
+        if restore {
+            let version_id = self.version_diff_modal_state.version_id.clone();
+            self.restore_version(&version_id);
+        }
+    }
+    
+    /// Opens the note a `[[link]]` points at, or offers to create it.
+    fn open_link(&mut self, target: &str) {
+        match self.note_manager.resolve_link(target) {
+            Some(note_id) => {
+                self.current_note_id = Some(note_id);
+                self.current_view = AppView::Editor;
+            }
+            None => {
+                self.create_from_link_state = CreateFromLinkState {
+                    is_open: true,
+                    title: target.to_string(),
+                    template_id: None,
+                };
+            }
+        }
+    }
+    
+    fn show_broken_links_modal(&mut self, ctx: &egui::Context) {
+        let mut is_open = self.broken_links_modal_state.is_open;
+        let mut open_note = None;
+        let mut create_title = None;
+        
+        egui::Window::new("⛓ Broken Links")
+            .open(&mut is_open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(450.0)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                let broken_links = self.note_manager.get_broken_links();
+                
+                if broken_links.is_empty() {
+                    ui.weak("Every link points at an existing note.");
+                    return;
+                }
+                
+                ui.label(format!("{} missing note(s) referenced across the vault", broken_links.len()));
+                ui.separator();
+                
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for broken in broken_links {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(egui::Color32::from_rgb(200, 60, 60), format!("[[{}]]", broken.target_title));
+                            ui.weak(format!("{} reference(s)", broken.reference_count));
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.small_button("➕ Create").clicked() {
+                                    create_title = Some(broken.target_title.clone());
+                                }
+                            });
+                        });
+                        
+                        for (source_id, source_title) in &broken.sources {
+                            ui.horizontal(|ui| {
+                                ui.add_space(16.0);
+                                if ui.link(source_title).clicked() {
+                                    open_note = Some(source_id.clone());
+                                }
+                            });
+                        }
+                        
+                        ui.separator();
+                    }
+                });
+            });
+        
+        self.broken_links_modal_state.is_open = is_open;
+        
+        if let Some(note_id) = open_note {
+            self.current_note_id = Some(note_id);
+            self.current_view = AppView::Editor;
+        }
+        if let Some(title) = create_title {
+            self.open_link(&title);
+        }
+    }
+    
+    fn show_create_from_link_modal(&mut self, ctx: &egui::Context) {
+        egui::Window::new("📝 Create Note")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(format!("\"{}\" doesn't exist yet.", self.create_from_link_state.title));
+                
+                ui.horizontal(|ui| {
+                    ui.label("Template:");
+                    
+                    let templates = self.note_manager.get_templates();
+                    let selected_text = self.create_from_link_state.template_id.as_ref()
+                        .and_then(|id| templates.iter().find(|t| &t.id == id))
+                        .map(|t| t.title.clone())
+                        .unwrap_or_else(|| "Blank note".to_string());
+                    
+                    egui::ComboBox::from_id_source("create_from_link_template")
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.create_from_link_state.template_id, None, "Blank note");
+                            for template in &templates {
+                                ui.selectable_value(
+                                    &mut self.create_from_link_state.template_id,
+                                    Some(template.id.clone()),
+                                    &template.title,
+                                );
+                            }
+                        });
+                });
+                
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    if ui.button("Create").clicked() {
+                        let title = self.create_from_link_state.title.clone();
+                        let template_id = self.create_from_link_state.template_id.clone();
+                        
+                        match self.note_manager.create_note_from_link(&title, template_id.as_deref()) {
+                            Ok(note_id) => {
+                                self.current_note_id = Some(note_id);
+                                self.current_view = AppView::Editor;
+                            }
+                            Err(e) => log::error!("Failed to create note from link: {}", e),
+                        }
+                        self.create_from_link_state = CreateFromLinkState::default();
+                    }
+                    
+                    if ui.button("Cancel").clicked() {
+                        self.create_from_link_state = CreateFromLinkState::default();
+                    }
+                });
+            });
+    }
+    
+    fn open_reminder_modal(&mut self) {
+        let tomorrow = chrono::Local::now().date_naive() + chrono::Duration::days(1);
+        self.reminder_modal_state = ReminderModalState {
+            is_open: true,
+            when: format!("{} 09:00", tomorrow.format("%Y-%m-%d")),
+            ..ReminderModalState::default()
+        };
+    }
+    
+    fn palette_entries(&self) -> Vec<(String, PaletteCommand)> {
+        // Inside a saved search, the palette lists its notes
+        if let Some(search_id) = &self.command_palette_state.saved_search_id {
+            return self.note_manager
+                .run_saved_search(search_id, 100)
+                .into_iter()
+                .map(|result| (format!("📝 {}", result.title), PaletteCommand::OpenNote(result.note_id)))
+                .collect();
+        }
+        
+        let mut entries = vec![
+            ("📝 New Note".to_string(), PaletteCommand::NewNote),
+            ("📝 Editor View".to_string(), PaletteCommand::SwitchView(AppView::Editor)),
+            ("🕸️ Graph View".to_string(), PaletteCommand::SwitchView(AppView::GraphView)),
+            ("☑ Tasks View".to_string(), PaletteCommand::SwitchView(AppView::Tasks)),
+            ("▦ Board View".to_string(), PaletteCommand::SwitchView(AppView::Board)),
+            ("🎨 Canvas View".to_string(), PaletteCommand::SwitchView(AppView::Canvas)),
+            ("✏ Rename Note".to_string(), PaletteCommand::RenameNote),
+            ("⛓ Broken Links".to_string(), PaletteCommand::BrokenLinks),
+            ("⏰ Add Reminder".to_string(), PaletteCommand::AddReminder),
+            ("◀ Toggle Left Sidebar".to_string(), PaletteCommand::ToggleLeftSidebar),
+            ("▶ Toggle Right Sidebar".to_string(), PaletteCommand::ToggleRightSidebar),
+            ("⚙ Settings".to_string(), PaletteCommand::OpenSettings),
+        ];
+        for search in self.note_manager.get_saved_searches() {
+            entries.push((format!("🔎 {}", search.name), PaletteCommand::ShowSavedSearch(search.id.clone())));
+        }
+        
+        let query = self.command_palette_state.query.trim();
+        if !query.is_empty() {
+            entries.push((format!("💾 Save \"{}\" as smart folder", query), PaletteCommand::SaveSearch(query.to_string())));
+        }
+        entries
+    }
+    
+    fn show_command_palette(&mut self, ctx: &egui::Context) {
+        let query = self.command_palette_state.query.to_lowercase();
+        let entries: Vec<(String, PaletteCommand)> = self.palette_entries()
+            .into_iter()
+            .filter(|(label, command)| {
+                matches!(command, PaletteCommand::SaveSearch(_)) || label.to_lowercase().contains(&query)
+            })
+            .collect();
+        
+        let (up, down, enter, escape) = ctx.input(|i| (
+            i.key_pressed(egui::Key::ArrowUp),
+            i.key_pressed(egui::Key::ArrowDown),
+            i.key_pressed(egui::Key::Enter),
+            i.key_pressed(egui::Key::Escape),
+        ));
+        
+        let state = &mut self.command_palette_state;
+        if down && state.selected + 1 < entries.len() {
+            state.selected += 1;
+        }
+        if up && state.selected > 0 {
+            state.selected -= 1;
+        }
+        state.selected = state.selected.min(entries.len().saturating_sub(1));
+        
+        let mut chosen = if enter { entries.get(state.selected).map(|(_, command)| command.clone()) } else { None };
+        
+        let title = match &state.saved_search_id {
+            Some(search_id) => self.note_manager
+                .get_saved_searches()
+                .iter()
+                .find(|search| &search.id == search_id)
+                .map(|search| format!("🔎 {}", search.name))
+                .unwrap_or_else(|| "🔎 Smart Folder".to_string()),
+            None => "⌘ Command Palette".to_string(),
+        };
+        
+        egui::Window::new(title)
+            .id(egui::Id::new("command_palette"))
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 80.0))
+            .fixed_size(egui::vec2(420.0, 320.0))
+            .show(ctx, |ui| {
+                let state = &mut self.command_palette_state;
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut state.query)
+                        .hint_text("Type a command or smart folder...")
+                        .desired_width(f32::INFINITY),
+                );
+                if response.changed() {
+                    state.selected = 0;
+                }
+                response.request_focus();
+                
+                ui.separator();
+                
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (index, (label, command)) in entries.iter().enumerate() {
+                        let selected = index == state.selected;
+                        let item = ui.selectable_label(selected, label);
+                        if selected && (up || down) {
+                            item.scroll_to_me(None);
+                        }
+                        if item.clicked() {
+                            chosen = Some(command.clone());
+                        }
+                    }
+                    if entries.is_empty() {
+                        ui.weak("No matches.");
+                    }
+                });
+            });
+        
+        if escape {
+            self.command_palette_state = CommandPaletteState::default();
+        } else if let Some(command) = chosen {
+            self.run_palette_command(command);
+        }
+    }
+    
+    fn run_palette_command(&mut self, command: PaletteCommand) {
+        // Every command closes the palette except stepping into a saved search
+        self.command_palette_state = CommandPaletteState::default();
+        
+        match command {
+            PaletteCommand::NewNote => {
+                let note_id = self.note_manager.create_new_note();
+                self.current_note_id = Some(note_id);
+                self.current_view = AppView::Editor;
+            }
+            PaletteCommand::SwitchView(view) => self.current_view = view,
+            PaletteCommand::RenameNote => self.open_rename_modal(),
+            PaletteCommand::BrokenLinks => self.broken_links_modal_state.is_open = true,
+            PaletteCommand::AddReminder => {
+                if self.current_note_id.is_some() {
+                    self.open_reminder_modal();
+                }
+            }
+            PaletteCommand::ToggleLeftSidebar => self.left_sidebar_open = !self.left_sidebar_open,
+            PaletteCommand::ToggleRightSidebar => self.right_sidebar_open = !self.right_sidebar_open,
+            PaletteCommand::OpenSettings => {
+                self.settings_open = true;
+                self.right_sidebar_open = true;
+            }
+            PaletteCommand::SaveSearch(query) => {
+                if let Err(e) = self.note_manager.save_search(query.clone(), SavedQuery::Text(query)) {
+                    log::error!("Failed to save search: {}", e);
+                }
+                self.left_sidebar_open = true;
+            }
+            PaletteCommand::ShowSavedSearch(search_id) => {
+                self.command_palette_state = CommandPaletteState {
+                    is_open: true,
+                    saved_search_id: Some(search_id),
+                    ..CommandPaletteState::default()
+                };
+            }
+            PaletteCommand::OpenNote(note_id) => {
+                self.current_note_id = Some(note_id);
+                self.current_view = AppView::Editor;
+            }
+        }
+    }
+    
+    fn show_reminder_modal(&mut self, ctx: &egui::Context) {
+        let note_id = match self.current_note_id.clone() {
+            Some(note_id) => note_id,
+            None => {
+                self.reminder_modal_state = ReminderModalState::default();
+                return;
+            }
+        };
+        let mut is_open = self.reminder_modal_state.is_open;
+        let mut delete_id = None;
+        
+        egui::Window::new("⏰ Reminders")
+            .open(&mut is_open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                egui::Grid::new("reminder_form").num_columns(2).show(ui, |ui| {
+                    ui.label("When:");
+                    ui.text_edit_singleline(&mut self.reminder_modal_state.when);
+                    ui.end_row();
+                    
+                    ui.label("Repeat:");
+                    ui.add(egui::TextEdit::singleline(&mut self.reminder_modal_state.repeat).hint_text("daily, weekly or cron"));
+                    ui.end_row();
+                });
+                
+                if let Some(error) = &self.reminder_modal_state.error {
+                    ui.colored_label(egui::Color32::from_rgb(200, 60, 60), error);
+                }
+                
+                if ui.button("Add Reminder").clicked() {
+                    let state = &self.reminder_modal_state;
+                    let result = match (parse_reminder_time(&state.when), Recurrence::parse(&state.repeat)) {
+                        (None, _) => Err("Use a time like 2026-10-20 09:00".to_string()),
+                        (_, None) => Err("Repeat must be daily, weekly or a cron expression".to_string()),
+                        (Some(remind_at), Some(recurrence)) => self.note_manager
+                            .add_reminder(&note_id, remind_at, recurrence)
+                            .map_err(|e| e.to_string()),
+                    };
+                    
+                    self.reminder_modal_state.error = result.err();
+                    if self.reminder_modal_state.error.is_none() {
+                        self.reminder_modal_state.repeat.clear();
+                    }
+                }
+                
+                let reminders = self.note_manager.get_note_reminders(&note_id);
+                if !reminders.is_empty() {
+                    ui.separator();
+                    for reminder in reminders {
+                        ui.horizontal(|ui| {
+                            let when = reminder.fire_at().with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+                            if reminder.is_done {
+                                ui.weak(format!("✔ {}", when));
+                            } else {
+                                ui.label(when.to_string());
+                            }
+                            if reminder.recurrence.is_recurring() {
+                                ui.weak(format!("🔁 {}", reminder.recurrence.as_str()));
+                            }
+                            ui.weak(&reminder.message);
+                            
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.small_button("🗑").on_hover_text("Delete reminder").clicked() {
+                                    delete_id = Some(reminder.id.clone());
+                                }
+                            });
+                        });
+                    }
+                }
+            });
+        
+        self.reminder_modal_state.is_open = is_open;
+        
+        if let Some(reminder_id) = delete_id {
+            if let Err(e) = self.note_manager.delete_reminder(&reminder_id) {
+                log::error!("Failed to delete reminder: {}", e);
+            }
+        }
+    }
+    
+    fn show_reminder_notifications(&mut self, ctx: &egui::Context) {
+        let mut handled = Vec::new();
+        let mut open_note = None;
+        
+        egui::Window::new("🔔 Reminders")
+            .id(egui::Id::new("reminder_notifications"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-16.0, 48.0))
+            .show(ctx, |ui| {
+                for (index, notification) in self.reminder_notifications.iter().enumerate() {
+                    let reminder = &notification.reminder;
+                    
+                    ui.horizontal(|ui| {
+                        ui.strong(&reminder.message);
+                        if notification.missed {
+                            ui.colored_label(egui::Color32::from_rgb(220, 140, 60), "missed");
+                        }
+                    });
+                    ui.weak(reminder.fire_at().with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string());
+                    
+                    ui.horizontal(|ui| {
+                        if ui.small_button("Open").clicked() {
+                            open_note = Some(reminder.note_id.clone());
+                        }
+                        
+                        let now = chrono::Utc::now();
+                        let snooze = [
+                            ("10 min", chrono::Duration::minutes(10)),
+                            ("1 hour", chrono::Duration::hours(1)),
+                            ("Tomorrow", chrono::Duration::days(1)),
+                        ];
+                        for (label, delay) in snooze {
+                            if ui.small_button(label).on_hover_text("Snooze").clicked() {
+                                if let Err(e) = self.note_manager.snooze_reminder(&reminder.id, now + delay) {
+                                    log::error!("Failed to snooze reminder: {}", e);
+                                }
+                                handled.push(index);
+                            }
+                        }
+                        
+                        if ui.small_button("✔ Done").clicked() {
+                            if let Err(e) = self.note_manager.complete_reminder(&reminder.id) {
+                                log::error!("Failed to complete reminder: {}", e);
+                            }
+                            handled.push(index);
+                        }
+                    });
+                    
+                    ui.separator();
+                }
+            });
+        
+        for index in handled.into_iter().rev() {
+            self.reminder_notifications.remove(index);
+        }
+        if let Some(note_id) = open_note {
+            self.current_note_id = Some(note_id);
+            self.current_view = AppView::Editor;
+        }
+    }
+    
+    fn open_rename_modal(&mut self) {
+        if let Some(note_id) = &self.current_note_id {
+            if let Some(note) = self.note_manager.get_note(note_id) {
+                self.rename_modal_state = RenameModalState {
+                    is_open: true,
+                    new_title: note.title.clone(),
+                    ..Default::default()
+                };
+            }
+        }
+    }
+    
+    fn show_rename_modal(&mut self, ctx: &egui::Context) {
+        let note_id = match &self.current_note_id {
+            Some(id) => id.clone(),
+            None => {
+                self.rename_modal_state.is_open = false;
+                return;
+            }
+        };
+        
+        egui::Window::new("✏️ Rename Note")
+            .collapsible(false)
+            .resizable(true)
+            .default_width(500.0)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("New title:");
+                    let response = ui.text_edit_singleline(&mut self.rename_modal_state.new_title);
+                    if response.changed() {
+                        // Any earlier preview no longer matches the typed title
+                        self.rename_modal_state.preview = None;
+                    }
+                });
+                
+                if let Some(error) = &self.rename_modal_state.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                
+                ui.separator();
+                
+                // Preview of incoming links that will be rewritten
+                if let Some(preview) = &self.rename_modal_state.preview {
+                    if preview.rewrites.is_empty() {
+                        ui.weak("No other notes link to this note.");
+                    } else {
+                        ui.label(format!(
+                            "{} link(s) in {} note(s) will be updated:",
+                            preview.rewrites.iter().map(|r| r.link_count).sum::<usize>(),
+                            preview.rewrites.len()
+                        ));
+                        
+                        egui::ScrollArea::vertical()
+                            .max_height(250.0)
+                            .show(ui, |ui| {
+                                for rewrite in &preview.rewrites {
+                                    egui::CollapsingHeader::new(format!("{} ({})", rewrite.note_title, rewrite.link_count))
+                                        .id_source(&rewrite.note_id)
+                                        .show(ui, |ui| {
+                                            let changed_lines = rewrite.original_content.lines()
+                                                .zip(rewrite.updated_content.lines())
+                                                .filter(|(before, after)| before != after);
+                                            
+                                            for (before, after) in changed_lines {
+                                                ui.colored_label(egui::Color32::from_rgb(200, 60, 60), format!("- {}", before));
+                                                ui.colored_label(egui::Color32::from_rgb(0, 204, 106), format!("+ {}", after));
+                                            }
+                                        });
+                                }
+                            });
+                    }
+                    ui.separator();
+                }
+                
+                // Action buttons
+                ui.horizontal(|ui| {
+                    if ui.button("👁 Preview").clicked() {
+                        self.rename_modal_state.preview = self.note_manager
+                            .preview_rename(&note_id, &self.rename_modal_state.new_title);
+                        self.rename_modal_state.error = None;
+                    }
+                    
+                    let can_apply = self.rename_modal_state.preview.is_some();
+                    if ui.add_enabled(can_apply, egui::Button::new("Rename")).clicked() {
+                        if let Some(preview) = self.rename_modal_state.preview.take() {
+                            match self.note_manager.apply_rename(&preview) {
+                                Ok(undo) => {
+                                    self.rename_modal_state.undo = Some(undo);
+                                    self.rename_modal_state.error = None;
+                                }
+                                Err(e) => {
+                                    log::error!("Failed to rename note: {}", e);
+                                    self.rename_modal_state.error = Some(e.to_string());
+                                }
+                            }
+                        }
+                    }
+                    
+                    if let Some(undo) = self.rename_modal_state.undo.clone() {
+                        if ui.button("🔄 Undo").clicked() {
+                            if let Err(e) = self.note_manager.undo_rename(&undo) {
+                                log::error!("Failed to undo rename: {}", e);
+                                self.rename_modal_state.error = Some(e.to_string());
+                            } else {
+                                self.rename_modal_state.new_title = undo.old_title.clone();
+                                self.rename_modal_state.undo = None;
+                            }
+                        }
+                    }
+                    
+                    if ui.button("Close").clicked() {
+                        self.rename_modal_state = RenameModalState::default();
+                    }
+                });
+            });
+    }
+    
     // Collaboration methods
     fn start_collaboration(&mut self, note_id: &str) {
         let user_id = format!("user_{}", uuid::Uuid::new_v4().to_string()[..8]);
@@ -1,10 +1,30 @@
 use eframe::egui;
-use crate::features::{Note, NoteManager};
+use crate::features::{Facet, FacetCounts, HistoryResult, HistoryScope, HistorySource, Note, NoteManager, SavedQuery, SearchQuery, SearchResult, UnlinkedMention};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+// History search indexes every version, so it waits for typing to pause
+const HISTORY_DEBOUNCE: Duration = Duration::from_millis(400);
 
 pub struct LeftSidebar {
     search_query: String,
     selected_folder: Option<String>,
     selected_tag: Option<String>,
+    smart_folder_results: HashMap<String, (u64, Vec<(String, String)>)>, // search id -> (search revision, [(note id, title)])
+    renaming_search: Option<(String, String)>,
+    jump_request: Option<(String, usize)>, // (note id, byte offset) of a clicked search match
+    semantic_search: bool,                 // Search by meaning instead of the query language
+    semantic_results: Option<(String, u64, Vec<SearchResult>)>, // (query, search revision, results)
+    selected_facets: Vec<Facet>,
+    submitted_search: Option<(String, Vec<Facet>, u64)>, // Query, selected facets and search revision last sent to the search worker
+    search_results: Vec<SearchResult>,                   // Latest from the search worker, best first
+    search_total: usize,                                 // Notes matched, beyond the results shown
+    search_facets: FacetCounts,                          // Over every match of the last completed search
+    search_history: bool,                                  // Also search earlier versions and the trash
+    history_results: Option<(String, Vec<HistoryResult>)>,      // (query, results)
+    query_edited_at: Option<Instant>,
+    pending_action: Option<SidebarAction>,
 }
 
 impl LeftSidebar {
@@ -13,20 +33,59 @@ impl LeftSidebar {
             search_query: String::new(),
             selected_folder: None,
             selected_tag: None,
+            smart_folder_results: HashMap::new(),
+            renaming_search: None,
+            jump_request: None,
+            semantic_search: false,
+            semantic_results: None,
+            selected_facets: Vec::new(),
+            submitted_search: None,
+            search_results: Vec::new(),
+            search_total: 0,
+            search_facets: FacetCounts::default(),
+            search_history: false,
+            history_results: None,
+            query_edited_at: None,
+            pending_action: None,
         }
     }
     
+    /// Note and content offset of the search match clicked since the last call.
+    pub fn take_jump_request(&mut self) -> Option<(String, usize)> {
+        self.jump_request.take()
+    }
+    
+    /// Version diffs and restores asked for from history search results.
+    pub fn take_action(&mut self) -> Option<SidebarAction> {
+        self.pending_action.take()
+    }
+    
     pub fn show(&mut self, ui: &mut egui::Ui, note_manager: &mut NoteManager, current_note_id: &mut Option<String>) {
         ui.vertical(|ui| {
             // Search bar
             ui.horizontal(|ui| {
-                ui.add(egui::TextEdit::singleline(&mut self.search_query)
-                    .hint_text("🔍 Search notes..."));
+                let hint = if self.semantic_search { "🧠 Describe what you're looking for..." } else { "🔍 Search notes..." };
+                if ui.add(egui::TextEdit::singleline(&mut self.search_query).hint_text(hint)).changed() {
+                    self.query_edited_at = Some(Instant::now());
+                }
+                ui.toggle_value(&mut self.semantic_search, "🧠").on_hover_text("Search by meaning");
+                ui.toggle_value(&mut self.search_history, "🕘").on_hover_text("Also search version history and trash");
                 if ui.button("⚙").clicked() {
                     // Search settings
                 }
             });
             
+            if !self.search_query.trim().is_empty() {
+                self.show_search_results(ui, note_manager, current_note_id);
+                if self.search_history && !self.semantic_search {
+                    self.show_history_results(ui, note_manager);
+                }
+            } else if self.submitted_search.take().is_some() {
+                note_manager.cancel_search();
+                self.search_results.clear();
+                self.search_total = 0;
+            }
+            
             ui.separator();
             
             // Quick actions
@@ -49,6 +108,12 @@ impl LeftSidebar {
                     self.show_folders(ui, note_manager);
                 });
             
+            egui::CollapsingHeader::new("🔎 Smart Folders")
+                .default_open(true)
+                .show(ui, |ui| {
+                    self.show_smart_folders(ui, note_manager, current_note_id);
+                });
+            
             egui::CollapsingHeader::new("📝 Recent Notes")
                 .default_open(true)
                 .show(ui, |ui| {
@@ -81,6 +146,285 @@ impl LeftSidebar {
         ui.selectable_label(self.selected_folder.as_deref() == Some("Projects"), "📁 Projects");
     }
     
+    fn show_search_results(&mut self, ui: &mut egui::Ui, note_manager: &mut NoteManager, current_note_id: &mut Option<String>) {
+        let mut searching = false;
+        let (results, total, facets) = if self.semantic_search {
+            if note_manager.is_embedding() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.weak("Embedding notes…");
+                });
+                ui.ctx().request_repaint_after(std::time::Duration::from_millis(200));
+                return;
+            }
+            let revision = note_manager.search_revision();
+            let stale = self.semantic_results.as_ref().is_none_or(|(query, built, _)| *query != self.search_query || *built != revision);
+            if stale {
+                let results = note_manager.semantic_search(&self.search_query, 20);
+                self.semantic_results = Some((self.search_query.clone(), revision, results));
+            }
+            let results = self.semantic_results.as_ref().expect("semantic results were just computed").2.clone();
+            let total = results.len();
+            (results, total, None)
+        } else {
+            if let Err(e) = SearchQuery::parse(&self.search_query) {
+                ui.colored_label(egui::Color32::from_rgb(220, 80, 80), e.to_string());
+                return;
+            }
+            self.update_search(note_manager);
+            
+            // Show what has been found so far; facets wait for the whole set,
+            // but those of the same query stay up while its facets change
+            searching = note_manager.is_searching();
+            if searching {
+                ui.ctx().request_repaint_after(std::time::Duration::from_millis(50));
+            }
+            let facets = (!searching || self.search_facets != FacetCounts::default()).then(|| self.search_facets.clone());
+            (self.search_results.clone(), self.search_total, facets)
+        };
+        
+        ui.horizontal(|ui| {
+            if searching {
+                ui.spinner();
+                ui.weak(format!("Searching… {} so far", total));
+            } else {
+                ui.weak(format!("{} result(s)", total));
+            }
+            if self.semantic_search {
+                return; // Smart folders hold query language searches
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("💾 Save").on_hover_text("Save as a smart folder").clicked() {
+                    let query = self.search_query.trim().to_string();
+                    if let Err(e) = note_manager.save_search(query.clone(), SavedQuery::Text(query)) {
+                        log::error!("Failed to save search: {}", e);
+                    }
+                }
+            });
+        });
+        
+        for result in &results {
+            let is_selected = current_note_id.as_ref() == Some(&result.note_id);
+            let title = highlighted(ui, &result.title, &result.title_highlights, egui::TextStyle::Body);
+            let mut clicked = ui.selectable_label(is_selected, title).clicked();
+            if !result.snippet.text.is_empty() {
+                let snippet = highlighted(ui, &result.snippet.text, &result.snippet.highlights, egui::TextStyle::Small);
+                clicked |= ui.add(egui::Label::new(snippet).sense(egui::Sense::click())).clicked();
+            }
+            
+            if clicked {
+                *current_note_id = Some(result.note_id.clone());
+                if let Some(span) = result.first_content_match() {
+                    self.jump_request = Some((result.note_id.clone(), span.start));
+                }
+            }
+        }
+        
+        if let Some(facets) = facets {
+            self.show_facets(ui, &facets);
+        }
+    }
+    
+    /// Matches in trashed notes and in text since removed from notes. Only
+    /// searched again once the query changes and typing pauses, or after a
+    /// restore from the results.
+    fn show_history_results(&mut self, ui: &mut egui::Ui, note_manager: &mut NoteManager) {
+        match self.query_edited_at {
+            Some(at) if at.elapsed() < HISTORY_DEBOUNCE => ui.ctx().request_repaint_after(HISTORY_DEBOUNCE - at.elapsed()),
+            _ => {
+                self.query_edited_at = None;
+                if self.history_results.as_ref().map(|(query, _)| query) != Some(&self.search_query) {
+                    let scope = HistoryScope { versions: true, trash: true };
+                    let results = note_manager.search_history(&self.search_query, scope, 20);
+                    self.history_results = Some((self.search_query.clone(), results));
+                }
+            }
+        }
+        let results = match &self.history_results {
+            Some((query, results)) if *query == self.search_query && !results.is_empty() => results.clone(),
+            _ => return,
+        };
+        
+        ui.separator();
+        ui.weak(format!("🕘 {} in history", results.len()));
+        for found in &results {
+            let title = highlighted(ui, &found.result.title, &found.result.title_highlights, egui::TextStyle::Body);
+            ui.label(title);
+            if !found.result.snippet.text.is_empty() {
+                ui.label(highlighted(ui, &found.result.snippet.text, &found.result.snippet.highlights, egui::TextStyle::Small));
+            }
+            
+            ui.horizontal(|ui| {
+                match &found.source {
+                    HistorySource::Trash => {
+                        ui.weak("🗑 In trash");
+                        if ui.small_button("↩ Restore").clicked() {
+                            if let Err(e) = note_manager.restore_note(&found.result.note_id) {
+                                log::error!("Failed to restore note: {}", e);
+                            }
+                            self.history_results = None;
+                        }
+                    }
+                    HistorySource::Version { version_id, version_number, timestamp } => {
+                        ui.weak(format!("v{} · {}", version_number, timestamp.format("%Y-%m-%d %H:%M")));
+                        if ui.small_button("📖").on_hover_text("Compare with current").clicked() {
+                            self.pending_action = Some(SidebarAction::DiffVersion(version_id.clone()));
+                        }
+                        if ui.small_button("🔄").on_hover_text("Restore this version").clicked() {
+                            self.pending_action = Some(SidebarAction::RestoreVersion(version_id.clone()));
+                            self.history_results = None;
+                        }
+                    }
+                }
+            });
+        }
+    }
+    
+    /// Sends the query to the search worker when it, the selected facets or
+    /// the indexed notes change, and takes whatever results have come back
+    /// since last frame.
+    fn update_search(&mut self, note_manager: &mut NoteManager) {
+        let revision = note_manager.search_revision();
+        if self.submitted_search.as_ref().map(|(query, _, _)| query) != Some(&self.search_query) {
+            self.selected_facets.clear();
+            self.search_facets = FacetCounts::default();
+        }
+        let search = (self.search_query.clone(), self.selected_facets.clone(), revision);
+        if self.submitted_search.as_ref() != Some(&search) {
+            // Facets are counted over every match by the worker; only the top results come back
+            note_manager.submit_search(&self.search_query, &self.selected_facets, 20);
+            self.submitted_search = Some(search);
+        }
+        
+        if let Some(update) = note_manager.poll_search() {
+            self.search_results = update.results;
+            self.search_total = update.total;
+            if update.complete {
+                self.search_facets = update.facets;
+            }
+        }
+    }
+    
+    /// Facet values with their counts; clicking one narrows the results to
+    /// it, clicking it again widens them.
+    fn show_facets(&mut self, ui: &mut egui::Ui, facets: &FacetCounts) {
+        egui::CollapsingHeader::new("🔎 Refine")
+            .default_open(true)
+            .show(ui, |ui| {
+                if !self.selected_facets.is_empty() && ui.small_button("Clear filters").clicked() {
+                    self.selected_facets.clear();
+                }
+                
+                let groups = [
+                    ("Tags", &facets.tags),
+                    ("Folders", &facets.folders),
+                    ("Created", &facets.months),
+                    ("Properties", &facets.properties),
+                    ("Matched in", &facets.match_types),
+                ];
+                for (name, counts) in groups {
+                    if counts.is_empty() {
+                        continue;
+                    }
+                    ui.label(egui::RichText::new(name).small().strong());
+                    for count in counts.iter().take(8) {
+                        let selected = self.selected_facets.contains(&count.facet);
+                        if ui.selectable_label(selected, format!("{} ({})", count.label, count.count)).clicked() {
+                            if selected {
+                                self.selected_facets.retain(|facet| *facet != count.facet);
+                            } else {
+                                self.selected_facets.push(count.facet.clone());
+                            }
+                        }
+                    }
+                }
+            });
+    }
+    
+    fn show_smart_folders(&mut self, ui: &mut egui::Ui, note_manager: &mut NoteManager, current_note_id: &mut Option<String>) {
+        let revision = note_manager.search_revision();
+        let searches: Vec<(String, String)> = note_manager
+            .get_saved_searches()
+            .iter()
+            .map(|search| (search.id.clone(), search.name.clone()))
+            .collect();
+        
+        if searches.is_empty() {
+            ui.weak("Save a search to keep it here.");
+            return;
+        }
+        
+        self.smart_folder_results.retain(|id, _| searches.iter().any(|(search_id, _)| search_id == id));
+        let mut delete_id = None;
+        let mut rename = None;
+        
+        for (search_id, name) in &searches {
+            if let Some((renaming_id, new_name)) = &mut self.renaming_search {
+                if renaming_id == search_id {
+                    let response = ui.text_edit_singleline(new_name);
+                    if response.lost_focus() {
+                        if ui.input(|i| i.key_pressed(egui::Key::Enter)) && !new_name.trim().is_empty() {
+                            rename = Some((search_id.clone(), new_name.trim().to_string()));
+                        }
+                        self.renaming_search = None;
+                    } else {
+                        response.request_focus();
+                    }
+                    continue;
+                }
+            }
+            
+            // Results are recomputed only when the index changes
+            let stale = self.smart_folder_results.get(search_id).is_none_or(|(built, _)| *built != revision);
+            if stale {
+                let notes = note_manager
+                    .run_saved_search(search_id, 100)
+                    .into_iter()
+                    .map(|result| (result.note_id, result.title))
+                    .collect();
+                self.smart_folder_results.insert(search_id.clone(), (revision, notes));
+            }
+            let notes = &self.smart_folder_results[search_id].1;
+            
+            let header = egui::CollapsingHeader::new(format!("🔎 {} ({})", name, notes.len()))
+                .id_source(("smart_folder", search_id))
+                .default_open(false)
+                .show(ui, |ui| {
+                    for (note_id, title) in notes {
+                        let is_selected = current_note_id.as_ref() == Some(note_id);
+                        if ui.selectable_label(is_selected, title).clicked() {
+                            *current_note_id = Some(note_id.clone());
+                        }
+                    }
+                    if notes.is_empty() {
+                        ui.weak("No matching notes.");
+                    }
+                });
+            
+            header.header_response.context_menu(|ui| {
+                if ui.button("Rename").clicked() {
+                    self.renaming_search = Some((search_id.clone(), name.clone()));
+                    ui.close_menu();
+                }
+                if ui.button("Delete").clicked() {
+                    delete_id = Some(search_id.clone());
+                    ui.close_menu();
+                }
+            });
+        }
+        
+        if let Some((search_id, name)) = rename {
+            if let Err(e) = note_manager.rename_saved_search(&search_id, name) {
+                log::error!("Failed to rename saved search: {}", e);
+            }
+        }
+        if let Some(search_id) = delete_id {
+            if let Err(e) = note_manager.delete_saved_search(&search_id) {
+                log::error!("Failed to delete saved search: {}", e);
+            }
+        }
+    }
+    
     fn show_notes_list(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: &mut Option<String>) {
         let notes = note_manager.get_recent_notes(10);
         
@@ -149,33 +493,93 @@ impl LeftSidebar {
     }
 }
 
+/// `text` with its byte `ranges` drawn on the selection colour.
+fn highlighted(ui: &egui::Ui, text: &str, ranges: &[Range<usize>], style: egui::TextStyle) -> egui::text::LayoutJob {
+    let font_id = style.resolve(ui.style());
+    let plain = egui::TextFormat::simple(font_id, ui.visuals().text_color());
+    let mark = egui::TextFormat {
+        background: ui.visuals().selection.bg_fill,
+        ..plain.clone()
+    };
+    
+    let mut job = egui::text::LayoutJob::default();
+    let mut position = 0;
+    for range in ranges {
+        if range.start < position || range.end > text.len() || !text.is_char_boundary(range.start) || !text.is_char_boundary(range.end) {
+            continue;
+        }
+        job.append(&text[position..range.start], 0.0, plain.clone());
+        job.append(&text[range.clone()], 0.0, mark.clone());
+        position = range.end;
+    }
+    job.append(&text[position..], 0.0, plain);
+    job
+}
+
 pub struct RightSidebar {
     active_tab: RightSidebarTab,
+    pending_action: Option<SidebarAction>,
+    unlinked_mentions: Option<(String, Vec<UnlinkedMention>)>, // cached per note id
+    graph_metrics: Option<GraphMetrics>,
+    related_notes: Option<(String, u64, Vec<(String, String, f32)>)>, // (note id, search revision, [(id, title, similarity)])
+}
+
+/// Vault-wide graph scores, recomputed only when the link graph changes.
+struct GraphMetrics {
+    revision: u64,
+    ranks: Vec<String>,                       // note ids by descending PageRank
+    component_sizes: HashMap<String, usize>,  // note id -> size of its cluster
+}
+
+/// Requests from the sidebar that need mutable access to app state.
+#[derive(Debug, Clone)]
+pub enum SidebarAction {
+    OpenNote(String),
+    LinkMention { target_note_id: String, mention: UnlinkedMention },
+    DiffVersion(String),    // Version id to compare with the current note
+    RestoreVersion(String), // Version id to bring back
 }
 
 #[derive(Debug, Clone, PartialEq)]
 enum RightSidebarTab {
     Metadata,
     Backlinks,
+    UnlinkedMentions,
     Outline,
     History,
+    Related,
 }
 
 impl RightSidebar {
     pub fn new() -> Self {
         Self {
             active_tab: RightSidebarTab::Metadata,
+            pending_action: None,
+            unlinked_mentions: None,
+            graph_metrics: None,
+            related_notes: None,
         }
     }
     
+    pub fn take_action(&mut self) -> Option<SidebarAction> {
+        let action = self.pending_action.take();
+        if let Some(SidebarAction::LinkMention { .. }) = action {
+            // Offsets of the remaining mentions shift once one is linked
+            self.unlinked_mentions = None;
+        }
+        action
+    }
+    
     pub fn show(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
         ui.vertical(|ui| {
             // Tab bar
             ui.horizontal(|ui| {
                 ui.selectable_value(&mut self.active_tab, RightSidebarTab::Metadata, "📊");
                 ui.selectable_value(&mut self.active_tab, RightSidebarTab::Backlinks, "🔗");
+                ui.selectable_value(&mut self.active_tab, RightSidebarTab::UnlinkedMentions, "💬");
                 ui.selectable_value(&mut self.active_tab, RightSidebarTab::Outline, "📋");
                 ui.selectable_value(&mut self.active_tab, RightSidebarTab::History, "📜");
+                ui.selectable_value(&mut self.active_tab, RightSidebarTab::Related, "🧠");
             });
             
             ui.separator();
@@ -184,13 +588,15 @@ impl RightSidebar {
             match self.active_tab {
                 RightSidebarTab::Metadata => self.show_metadata(ui, note_manager, current_note_id),
                 RightSidebarTab::Backlinks => self.show_backlinks(ui, note_manager, current_note_id),
+                RightSidebarTab::UnlinkedMentions => self.show_unlinked_mentions(ui, note_manager, current_note_id),
                 RightSidebarTab::Outline => self.show_outline(ui, note_manager, current_note_id),
                 RightSidebarTab::History => self.show_history(ui, note_manager, current_note_id),
+                RightSidebarTab::Related => self.show_related(ui, note_manager, current_note_id),
             }
         });
     }
     
-    fn show_metadata(&self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
+    fn show_metadata(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
         if let Some(note_id) = current_note_id {
             if let Some(note) = note_manager.get_note(note_id) {
                 ui.heading("Metadata");
@@ -235,33 +641,222 @@ impl RightSidebar {
                         // Add tag
                     }
                 });
+                
+                ui.separator();
+                self.show_graph_metrics(ui, note_manager, note_id);
             }
         } else {
             ui.weak("No note selected");
         }
     }
     
-    fn show_backlinks(&self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
-        ui.heading("Backlinks");
-        ui.separator();
+    fn show_graph_metrics(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager, note_id: &str) {
+        let graph = note_manager.link_graph();
         
-        if let Some(note_id) = current_note_id {
-            let backlinks = note_manager.get_backlinks(note_id);
+        if self.graph_metrics.as_ref().map(|m| m.revision) != Some(graph.revision()) {
+            let scores = graph.pagerank();
+            let mut ranks: Vec<String> = scores.keys().cloned().collect();
+            ranks.sort_by(|a, b| scores[b].partial_cmp(&scores[a]).unwrap_or(std::cmp::Ordering::Equal));
             
-            for backlink in backlinks {
-                if ui.link(&backlink.title).clicked() {
-                    // Navigate to backlinked note
+            let mut component_sizes = HashMap::new();
+            for component in graph.components() {
+                for id in &component {
+                    component_sizes.insert(id.clone(), component.len());
                 }
             }
             
+            self.graph_metrics = Some(GraphMetrics {
+                revision: graph.revision(),
+                ranks,
+                component_sizes,
+            });
+        }
+        
+        let metrics = match &self.graph_metrics {
+            Some(metrics) => metrics,
+            None => return,
+        };
+        
+        ui.label("Graph:");
+        
+        ui.horizontal(|ui| {
+            ui.label("Links:");
+            ui.label(format!("{} out, {} in", graph.outgoing(note_id).len(), graph.incoming(note_id).len()));
+        });
+        
+        if let Some(rank) = metrics.ranks.iter().position(|id| id == note_id) {
+            ui.horizontal(|ui| {
+                ui.label("Centrality:");
+                ui.label(format!("#{} of {}", rank + 1, metrics.ranks.len()));
+            });
+        }
+        
+        match metrics.component_sizes.get(note_id) {
+            Some(1) | None => {
+                ui.colored_label(egui::Color32::from_rgb(200, 140, 0), "Orphan: no links in or out");
+            }
+            Some(size) => {
+                ui.horizontal(|ui| {
+                    ui.label("Cluster:");
+                    ui.label(format!("{} notes", size));
+                });
+            }
+        }
+        
+        let nearby: Vec<(String, usize)> = graph.neighborhood(note_id, 2)
+            .into_iter()
+            .filter(|(_, distance)| *distance > 0)
+            .collect();
+        
+        if !nearby.is_empty() {
+            ui.label("Nearby:");
+            for (id, distance) in nearby.iter().take(10) {
+                if let Some(note) = note_manager.get_note(id) {
+                    ui.horizontal(|ui| {
+                        ui.weak(if *distance == 1 { "1 hop" } else { "2 hops" });
+                        if ui.link(&note.title).clicked() {
+                            self.pending_action = Some(SidebarAction::OpenNote(id.clone()));
+                        }
+                    });
+                }
+            }
+        }
+    }
+    
+    fn show_backlinks(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
+        ui.heading("Backlinks");
+        ui.separator();
+        
+        if let Some(note_id) = current_note_id {
+            let backlinks = note_manager.get_backlink_mentions(note_id);
+            
             if backlinks.is_empty() {
                 ui.weak("No backlinks found");
             }
+            
+            for (source, mentions) in backlinks {
+                if ui.link(format!("{} ({})", source.title, mentions.len())).clicked() {
+                    self.pending_action = Some(SidebarAction::OpenNote(source.id.clone()));
+                }
+                
+                for mention in mentions {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.add_space(16.0);
+                        ui.weak(&mention.context);
+                    });
+                }
+                
+                ui.add_space(4.0);
+            }
         } else {
             ui.weak("No note selected");
         }
     }
     
+    fn show_related(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
+        ui.heading("Related Notes");
+        ui.separator();
+        
+        let note_id = match current_note_id {
+            Some(note_id) => note_id,
+            None => {
+                ui.weak("No note selected");
+                return;
+            }
+        };
+        
+        if note_manager.is_embedding() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.weak("Embedding notes…");
+            });
+            ui.ctx().request_repaint_after(std::time::Duration::from_millis(200));
+            return;
+        }
+        
+        // Similarities are recomputed only when the note or the index changes
+        let revision = note_manager.search_revision();
+        let stale = self.related_notes.as_ref().is_none_or(|(id, built, _)| id != note_id || *built != revision);
+        if stale {
+            let related = note_manager
+                .get_related_notes(note_id, 15)
+                .into_iter()
+                .map(|result| (result.note_id, result.title, result.score))
+                .collect();
+            self.related_notes = Some((note_id.clone(), revision, related));
+        }
+        
+        let related = &self.related_notes.as_ref().expect("related notes were just computed").2;
+        if related.is_empty() {
+            ui.weak("No similar notes found");
+        }
+        for (related_id, title, similarity) in related {
+            ui.horizontal(|ui| {
+                if ui.link(title).clicked() {
+                    self.pending_action = Some(SidebarAction::OpenNote(related_id.clone()));
+                }
+                ui.weak(format!("{:.0}%", similarity * 100.0));
+            });
+        }
+    }
+    
+    fn show_unlinked_mentions(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
+        ui.horizontal(|ui| {
+            ui.heading("Unlinked Mentions");
+            if ui.small_button("🔄").clicked() {
+                self.unlinked_mentions = None;
+            }
+        });
+        ui.separator();
+        
+        let note_id = match current_note_id {
+            Some(note_id) => note_id,
+            None => {
+                ui.weak("No note selected");
+                return;
+            }
+        };
+        
+        // Scanning the vault is too costly to repeat every frame
+        let is_stale = self.unlinked_mentions.as_ref().is_none_or(|(id, _)| id != note_id);
+        if is_stale {
+            self.unlinked_mentions = Some((note_id.clone(), note_manager.get_unlinked_mentions(note_id)));
+        }
+        
+        let mentions = match &self.unlinked_mentions {
+            Some((_, mentions)) => mentions,
+            None => return,
+        };
+        
+        if mentions.is_empty() {
+            ui.weak("No unlinked mentions found");
+        }
+        
+        let mut last_source: Option<&str> = None;
+        for mention in mentions {
+            if last_source != Some(mention.source_note_id.as_str()) {
+                if let Some(source) = note_manager.get_note(&mention.source_note_id) {
+                    ui.add_space(4.0);
+                    if ui.link(&source.title).clicked() {
+                        self.pending_action = Some(SidebarAction::OpenNote(source.id.clone()));
+                    }
+                }
+                last_source = Some(&mention.source_note_id);
+            }
+            
+            ui.horizontal_wrapped(|ui| {
+                ui.add_space(16.0);
+                ui.weak(&mention.context);
+                if ui.small_button("🔗 Link").clicked() {
+                    self.pending_action = Some(SidebarAction::LinkMention {
+                        target_note_id: note_id.clone(),
+                        mention: mention.clone(),
+                    });
+                }
+            });
+        }
+    }
+    
     fn show_outline(&self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
         ui.heading("Outline");
         ui.separator();
@@ -290,21 +885,22 @@ impl RightSidebar {
         }
     }
     
-    fn show_history(&self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
+    fn show_history(&mut self, ui: &mut egui::Ui, note_manager: &NoteManager, current_note_id: Option<&String>) {
         ui.heading("Version History");
         ui.separator();
         
         if let Some(note_id) = current_note_id {
             let versions = note_manager.get_note_versions(note_id);
             
-            for version in versions {
+            for version in &versions {
                 ui.horizontal(|ui| {
+                    ui.label(format!("v{}", version.version_number));
                     ui.label(version.timestamp.format("%m/%d %H:%M").to_string());
-                    if ui.small_button("📖").clicked() {
-                        // View this version
+                    if ui.small_button("📖").on_hover_text("Compare with current").clicked() {
+                        self.pending_action = Some(SidebarAction::DiffVersion(version.id.clone()));
                     }
-                    if ui.small_button("🔄").clicked() {
-                        // Restore this version
+                    if ui.small_button("🔄").on_hover_text("Restore this version").clicked() {
+                        self.pending_action = Some(SidebarAction::RestoreVersion(version.id.clone()));
                     }
                 });
             }
@@ -0,0 +1,147 @@
+use eframe::egui;
+use crate::features::{NoteManager, Task, TaskDueFilter, TaskFilter, TaskPriority, TaskStatusFilter};
+
+/// Every `- [ ]` checkbox in the vault, filterable by status, due date and tag.
+pub struct TasksView {
+    filter: TaskFilter,
+    open_request: Option<String>,
+    status: Option<String>,
+}
+
+impl TasksView {
+    pub fn new() -> Self {
+        Self {
+            filter: TaskFilter::default(),
+            open_request: None,
+            status: None,
+        }
+    }
+    
+    /// Note whose task was clicked since the last call.
+    pub fn take_open_request(&mut self) -> Option<String> {
+        self.open_request.take()
+    }
+    
+    pub fn show(&mut self, ui: &mut egui::Ui, note_manager: &mut NoteManager) {
+        ui.horizontal(|ui| {
+            ui.heading("Tasks");
+            ui.separator();
+            
+            ui.selectable_value(&mut self.filter.status, TaskStatusFilter::Open, "Open");
+            ui.selectable_value(&mut self.filter.status, TaskStatusFilter::Done, "Done");
+            ui.selectable_value(&mut self.filter.status, TaskStatusFilter::All, "All");
+            
+            ui.separator();
+            
+            egui::ComboBox::from_id_source("task_due_filter")
+                .selected_text(due_label(self.filter.due))
+                .show_ui(ui, |ui| {
+                    for due in [TaskDueFilter::Any, TaskDueFilter::Overdue, TaskDueFilter::Today, TaskDueFilter::ThisWeek, TaskDueFilter::NoDate] {
+                        ui.selectable_value(&mut self.filter.due, due, due_label(due));
+                    }
+                });
+            
+            let tags = note_manager.task_index().tags();
+            egui::ComboBox::from_id_source("task_tag_filter")
+                .selected_text(self.filter.tag.as_deref().map(|tag| format!("#{}", tag)).unwrap_or_else(|| "Any tag".to_string()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.filter.tag, None, "Any tag");
+                    for tag in tags {
+                        let label = format!("#{}", tag);
+                        ui.selectable_value(&mut self.filter.tag, Some(tag), label);
+                    }
+                });
+            
+            ui.add(egui::TextEdit::singleline(&mut self.filter.query).hint_text("Filter tasks...").desired_width(160.0));
+            
+            if let Some(status) = &self.status {
+                ui.weak(status);
+            }
+        });
+        
+        ui.separator();
+        
+        let today = chrono::Local::now().date_naive();
+        let mut toggled: Option<Task> = None;
+        
+        let tasks = note_manager.get_tasks(&self.filter);
+        if tasks.is_empty() {
+            ui.centered_and_justified(|ui| {
+                ui.label("No tasks match the current filters.");
+            });
+            return;
+        }
+        
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for task in &tasks {
+                ui.horizontal(|ui| {
+                    let mut done = task.done;
+                    if ui.checkbox(&mut done, "").changed() {
+                        toggled = Some((*task).clone());
+                    }
+                    
+                    if let Some(priority) = task.priority {
+                        ui.colored_label(priority_color(priority), priority.label());
+                    }
+                    
+                    if task.done {
+                        ui.label(egui::RichText::new(&task.description).strikethrough().weak());
+                    } else {
+                        ui.label(&task.description);
+                    }
+                    
+                    if let Some(due) = task.due {
+                        let text = format!("📅 {}", due.format("%Y-%m-%d"));
+                        if due < today && !task.done {
+                            ui.colored_label(egui::Color32::from_rgb(220, 80, 80), text);
+                        } else {
+                            ui.weak(text);
+                        }
+                    }
+                    
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let source = format!("{}:{}", task.note_title, task.line);
+                        if ui.link(source).clicked() {
+                            self.open_request = Some(task.note_id.clone());
+                        }
+                    });
+                });
+            }
+        });
+        
+        if let Some(task) = toggled {
+            self.status = match note_manager.toggle_task(&task) {
+                Ok(true) => None,
+                Ok(false) => Some(format!("\"{}\" changed since it was indexed", task.note_title)),
+                Err(e) => {
+                    log::error!("Failed to toggle task: {}", e);
+                    Some("Failed to update task".to_string())
+                }
+            };
+        }
+    }
+}
+
+impl Default for TasksView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn due_label(due: TaskDueFilter) -> &'static str {
+    match due {
+        TaskDueFilter::Any => "Any date",
+        TaskDueFilter::Overdue => "Overdue",
+        TaskDueFilter::Today => "Today",
+        TaskDueFilter::ThisWeek => "This week",
+        TaskDueFilter::NoDate => "No date",
+    }
+}
+
+fn priority_color(priority: TaskPriority) -> egui::Color32 {
+    match priority {
+        TaskPriority::High => egui::Color32::from_rgb(220, 80, 80),
+        TaskPriority::Medium => egui::Color32::from_rgb(230, 170, 60),
+        TaskPriority::Low => egui::Color32::from_rgb(100, 150, 220),
+    }
+}
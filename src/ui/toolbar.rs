@@ -30,6 +30,9 @@ impl Toolbar {
             // View toggles
             ui.selectable_value(current_view, AppView::Editor, "📝 Editor");
             ui.selectable_value(current_view, AppView::GraphView, "🕸️ Graph");
+            ui.selectable_value(current_view, AppView::Tasks, "☑ Tasks");
+            ui.selectable_value(current_view, AppView::Board, "▦ Board");
+            ui.selectable_value(current_view, AppView::Canvas, "🎨 Canvas");
             
             ui.separator();
             
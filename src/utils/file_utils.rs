@@ -26,6 +26,13 @@ pub fn get_notes_directory() -> Result<PathBuf> {
     Ok(notes_dir)
 }
 
+pub fn get_exports_directory() -> Result<PathBuf> {
+    let app_dir = get_app_data_directory()?;
+    let exports_dir = app_dir.join("exports");
+    ensure_directory_exists(&exports_dir)?;
+    Ok(exports_dir)
+}
+
 pub fn sanitize_filename(filename: &str) -> String {
     filename
         .chars()
@@ -0,0 +1,426 @@
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use anyhow::{anyhow, Result};
+use crate::features::LinkKind;
+use image::{Rgba, RgbaImage};
+use serde::Serialize;
+use std::path::Path;
+
+const PADDING: f32 = 40.0;
+const LABEL_SIZE: f32 = 14.0;
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const LABEL_COLOR: [u8; 3] = [30, 30, 30];
+
+/// The graph as currently shown: the visible (filtered) notes with their
+/// laid-out positions and colors, and the links between them.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GraphSnapshot {
+    pub nodes: Vec<GraphSnapshotNode>,
+    pub edges: Vec<GraphSnapshotEdge>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphSnapshotNode {
+    pub id: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub color: [u8; 3],
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphSnapshotEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: LinkKind,
+    pub relation: Option<String>,
+    pub label: Option<String>,
+    pub color: [u8; 3],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphExportFormat {
+    Dot,
+    GraphMl,
+    Json,
+    Svg,
+    Png,
+}
+
+impl GraphExportFormat {
+    pub fn all() -> &'static [GraphExportFormat] {
+        &[
+            GraphExportFormat::Dot,
+            GraphExportFormat::GraphMl,
+            GraphExportFormat::Json,
+            GraphExportFormat::Svg,
+            GraphExportFormat::Png,
+        ]
+    }
+    
+    pub fn extension(&self) -> &'static str {
+        match self {
+            GraphExportFormat::Dot => "dot",
+            GraphExportFormat::GraphMl => "graphml",
+            GraphExportFormat::Json => "json",
+            GraphExportFormat::Svg => "svg",
+            GraphExportFormat::Png => "png",
+        }
+    }
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            GraphExportFormat::Dot => "Graphviz (.dot)",
+            GraphExportFormat::GraphMl => "GraphML (.graphml)",
+            GraphExportFormat::Json => "JSON (.json)",
+            GraphExportFormat::Svg => "SVG image (.svg)",
+            GraphExportFormat::Png => "PNG image (.png)",
+        }
+    }
+}
+
+pub struct GraphExporter {
+    font_data: Vec<u8>,
+}
+
+impl GraphExporter {
+    pub fn new() -> Self {
+        // Reuse egui's bundled UI font so images match what is on screen
+        let fonts = egui::FontDefinitions::default();
+        let font_data = fonts.families
+            .get(&egui::FontFamily::Proportional)
+            .and_then(|family| family.first())
+            .and_then(|name| fonts.font_data.get(name))
+            .map(|data| data.font.to_vec())
+            .unwrap_or_default();
+        
+        Self { font_data }
+    }
+    
+    pub fn export(&self, graph: &GraphSnapshot, format: GraphExportFormat, path: &Path) -> Result<()> {
+        match format {
+            GraphExportFormat::Dot => std::fs::write(path, self.to_dot(graph))?,
+            GraphExportFormat::GraphMl => std::fs::write(path, self.to_graphml(graph))?,
+            GraphExportFormat::Json => std::fs::write(path, self.to_json(graph)?)?,
+            GraphExportFormat::Svg => std::fs::write(path, self.to_svg(graph))?,
+            GraphExportFormat::Png => std::fs::write(path, self.to_png(graph, 1.0)?)?,
+        }
+        Ok(())
+    }
+    
+    pub fn to_dot(&self, graph: &GraphSnapshot) -> String {
+        let mut dot = String::from("digraph notes {\n");
+        dot.push_str("    node [shape=ellipse, style=filled, fontname=\"Helvetica\"];\n");
+        
+        for node in &graph.nodes {
+            dot.push_str(&format!(
+                "    \"{}\" [label=\"{}\", fillcolor=\"{}\", pos=\"{:.1},{:.1}\"];\n",
+                escape_dot(&node.id),
+                escape_dot(&node.title),
+                hex_color(node.color),
+                node.x,
+                -node.y, // Graphviz's y axis points up
+            ));
+        }
+        
+        for edge in &graph.edges {
+            let mut attributes = Vec::new();
+            if let Some(label) = &edge.label {
+                attributes.push(format!("label=\"{}\"", escape_dot(label)));
+            }
+            if edge.kind == LinkKind::Embed {
+                attributes.push("style=dashed".to_string());
+            }
+            if edge.relation.is_some() {
+                attributes.push(format!("color=\"{}\", penwidth=2", hex_color(edge.color)));
+            }
+            
+            let attributes = if attributes.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attributes.join(", "))
+            };
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\"{};\n",
+                escape_dot(&edge.from),
+                escape_dot(&edge.to),
+                attributes,
+            ));
+        }
+        
+        dot.push_str("}\n");
+        dot
+    }
+    
+    pub fn to_graphml(&self, graph: &GraphSnapshot) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        xml.push_str("  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n");
+        xml.push_str("  <key id=\"tags\" for=\"node\" attr.name=\"tags\" attr.type=\"string\"/>\n");
+        xml.push_str("  <key id=\"color\" for=\"node\" attr.name=\"color\" attr.type=\"string\"/>\n");
+        xml.push_str("  <key id=\"x\" for=\"node\" attr.name=\"x\" attr.type=\"float\"/>\n");
+        xml.push_str("  <key id=\"y\" for=\"node\" attr.name=\"y\" attr.type=\"float\"/>\n");
+        xml.push_str("  <key id=\"kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n");
+        xml.push_str("  <key id=\"relation\" for=\"edge\" attr.name=\"relation\" attr.type=\"string\"/>\n");
+        xml.push_str("  <key id=\"label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n");
+        xml.push_str("  <graph id=\"notes\" edgedefault=\"directed\">\n");
+        
+        for node in &graph.nodes {
+            xml.push_str(&format!("    <node id=\"{}\">\n", escape_xml(&node.id)));
+            xml.push_str(&format!("      <data key=\"title\">{}</data>\n", escape_xml(&node.title)));
+            xml.push_str(&format!("      <data key=\"tags\">{}</data>\n", escape_xml(&node.tags.join(","))));
+            xml.push_str(&format!("      <data key=\"color\">{}</data>\n", hex_color(node.color)));
+            xml.push_str(&format!("      <data key=\"x\">{:.1}</data>\n", node.x));
+            xml.push_str(&format!("      <data key=\"y\">{:.1}</data>\n", node.y));
+            xml.push_str("    </node>\n");
+        }
+        
+        for (i, edge) in graph.edges.iter().enumerate() {
+            xml.push_str(&format!(
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">\n",
+                i,
+                escape_xml(&edge.from),
+                escape_xml(&edge.to),
+            ));
+            xml.push_str(&format!("      <data key=\"kind\">{}</data>\n", edge.kind.as_str()));
+            if let Some(relation) = &edge.relation {
+                xml.push_str(&format!("      <data key=\"relation\">{}</data>\n", escape_xml(relation)));
+            }
+            if let Some(label) = &edge.label {
+                xml.push_str(&format!("      <data key=\"label\">{}</data>\n", escape_xml(label)));
+            }
+            xml.push_str("    </edge>\n");
+        }
+        
+        xml.push_str("  </graph>\n");
+        xml.push_str("</graphml>\n");
+        xml
+    }
+    
+    pub fn to_json(&self, graph: &GraphSnapshot) -> Result<String> {
+        Ok(serde_json::to_string_pretty(graph)?)
+    }
+    
+    pub fn to_svg(&self, graph: &GraphSnapshot) -> String {
+        let (min_x, min_y, width, height) = bounds(graph);
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" viewBox=\"{:.1} {:.1} {:.1} {:.1}\">\n",
+            width, height, min_x, min_y, width, height,
+        );
+        svg.push_str(&format!(
+            "  <rect x=\"{:.1}\" y=\"{:.1}\" width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+            min_x, min_y, hex_color(BACKGROUND),
+        ));
+        
+        for edge in &graph.edges {
+            let (from, to) = match (find_node(graph, &edge.from), find_node(graph, &edge.to)) {
+                (Some(from), Some(to)) => (from, to),
+                _ => continue,
+            };
+            
+            let dash = if edge.kind == LinkKind::Embed { " stroke-dasharray=\"4 3\"" } else { "" };
+            let width = if edge.relation.is_some() { 2 } else { 1 };
+            svg.push_str(&format!(
+                "  <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"{}\"{}/>\n",
+                from.x, from.y, to.x, to.y, hex_color(edge.color), width, dash,
+            ));
+            
+            if let Some(label) = &edge.label {
+                svg.push_str(&format!(
+                    "  <text x=\"{:.1}\" y=\"{:.1}\" font-family=\"sans-serif\" font-size=\"11\" text-anchor=\"middle\" fill=\"#555555\">{}</text>\n",
+                    (from.x + to.x) / 2.0, (from.y + to.y) / 2.0, escape_xml(label),
+                ));
+            }
+        }
+        
+        for node in &graph.nodes {
+            svg.push_str(&format!(
+                "  <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\" stroke=\"#ffffff\" stroke-width=\"2\"/>\n",
+                node.x, node.y, node.radius, hex_color(node.color),
+            ));
+            svg.push_str(&format!(
+                "  <text x=\"{:.1}\" y=\"{:.1}\" font-family=\"sans-serif\" font-size=\"{}\" text-anchor=\"middle\" fill=\"{}\">{}</text>\n",
+                node.x, node.y + node.radius + LABEL_SIZE, LABEL_SIZE, hex_color(LABEL_COLOR), escape_xml(&node.title),
+            ));
+        }
+        
+        svg.push_str("</svg>\n");
+        svg
+    }
+    
+    /// Rasterizes the layout to PNG bytes. `scale` multiplies the on-screen size.
+    pub fn to_png(&self, graph: &GraphSnapshot, scale: f32) -> Result<Vec<u8>> {
+        let font = FontRef::try_from_slice(&self.font_data)
+            .map_err(|_| anyhow!("No font available to render graph labels"))?;
+        
+        let (min_x, min_y, width, height) = bounds(graph);
+        let to_pixel = |x: f32, y: f32| ((x - min_x) * scale, (y - min_y) * scale);
+        
+        let mut canvas = RgbaImage::from_pixel(
+            (width * scale).ceil().max(1.0) as u32,
+            (height * scale).ceil().max(1.0) as u32,
+            rgba(BACKGROUND, 255),
+        );
+        
+        for edge in &graph.edges {
+            if let (Some(from), Some(to)) = (find_node(graph, &edge.from), find_node(graph, &edge.to)) {
+                let width = if edge.relation.is_some() { 2.0 } else { 1.0 };
+                draw_line(&mut canvas, to_pixel(from.x, from.y), to_pixel(to.x, to.y), edge.color, width * scale.max(1.0));
+            }
+        }
+        
+        for node in &graph.nodes {
+            let (x, y) = to_pixel(node.x, node.y);
+            let radius = node.radius * scale;
+            fill_circle(&mut canvas, (x, y), radius + 2.0 * scale, [255, 255, 255]);
+            fill_circle(&mut canvas, (x, y), radius, node.color);
+            draw_text_centered(&mut canvas, &font, &node.title, (x, y + radius + 4.0 * scale), LABEL_SIZE * scale, LABEL_COLOR);
+        }
+        
+        let mut bytes = Vec::new();
+        image::DynamicImage::ImageRgba8(canvas)
+            .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageOutputFormat::Png)?;
+        Ok(bytes)
+    }
+}
+
+impl Default for GraphExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn find_node<'a>(graph: &'a GraphSnapshot, id: &str) -> Option<&'a GraphSnapshotNode> {
+    graph.nodes.iter().find(|node| node.id == id)
+}
+
+/// Top-left corner and size of the area covering every node and its label.
+fn bounds(graph: &GraphSnapshot) -> (f32, f32, f32, f32) {
+    if graph.nodes.is_empty() {
+        return (0.0, 0.0, PADDING * 2.0, PADDING * 2.0);
+    }
+    
+    let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+    let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+    for node in &graph.nodes {
+        // Leave room for the title under the node
+        let half_label = node.title.chars().count() as f32 * LABEL_SIZE * 0.3;
+        min_x = min_x.min(node.x - node.radius.max(half_label));
+        max_x = max_x.max(node.x + node.radius.max(half_label));
+        min_y = min_y.min(node.y - node.radius);
+        max_y = max_y.max(node.y + node.radius + LABEL_SIZE * 1.5);
+    }
+    
+    (
+        min_x - PADDING,
+        min_y - PADDING,
+        max_x - min_x + PADDING * 2.0,
+        max_y - min_y + PADDING * 2.0,
+    )
+}
+
+fn hex_color(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+fn rgba(color: [u8; 3], alpha: u8) -> Rgba<u8> {
+    Rgba([color[0], color[1], color[2], alpha])
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Alpha-blends `color` over the pixel at (x, y), ignoring points off the canvas.
+fn blend(canvas: &mut RgbaImage, x: i64, y: i64, color: [u8; 3], coverage: f32) {
+    if x < 0 || y < 0 || x >= canvas.width() as i64 || y >= canvas.height() as i64 {
+        return;
+    }
+    
+    let coverage = coverage.clamp(0.0, 1.0);
+    let pixel = canvas.get_pixel_mut(x as u32, y as u32);
+    for channel in 0..3 {
+        let under = pixel[channel] as f32;
+        pixel[channel] = (under + (color[channel] as f32 - under) * coverage).round() as u8;
+    }
+}
+
+fn draw_line(canvas: &mut RgbaImage, from: (f32, f32), to: (f32, f32), color: [u8; 3], width: f32) {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as usize;
+    let half_width = width / 2.0;
+    
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        let (x, y) = (from.0 + dx * t, from.1 + dy * t);
+        let reach = half_width.ceil() as i64;
+        for ox in -reach..=reach {
+            for oy in -reach..=reach {
+                let (px, py) = (x.round() as i64 + ox, y.round() as i64 + oy);
+                let distance = ((px as f32 - x).powi(2) + (py as f32 - y).powi(2)).sqrt();
+                let coverage = (half_width + 0.5 - distance).clamp(0.0, 1.0);
+                if coverage > 0.0 {
+                    blend(canvas, px, py, color, coverage);
+                }
+            }
+        }
+    }
+}
+
+fn fill_circle(canvas: &mut RgbaImage, center: (f32, f32), radius: f32, color: [u8; 3]) {
+    let reach = radius.ceil() as i64 + 1;
+    let (cx, cy) = (center.0.round() as i64, center.1.round() as i64);
+    
+    for x in (cx - reach)..=(cx + reach) {
+        for y in (cy - reach)..=(cy + reach) {
+            let distance = ((x as f32 - center.0).powi(2) + (y as f32 - center.1).powi(2)).sqrt();
+            let coverage = (radius + 0.5 - distance).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                blend(canvas, x, y, color, coverage);
+            }
+        }
+    }
+}
+
+/// Draws `text` horizontally centred on `top.0`, hanging below `top.1`.
+fn draw_text_centered(canvas: &mut RgbaImage, font: &FontRef, text: &str, top: (f32, f32), size: f32, color: [u8; 3]) {
+    let scaled = font.as_scaled(PxScale::from(size));
+    
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+        glyphs.push(id.with_scale_and_position(PxScale::from(size), ab_glyph::point(caret, scaled.ascent())));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    
+    let origin = (top.0 - caret / 2.0, top.1);
+    for glyph in glyphs {
+        if let Some(outlined) = font.outline_glyph(glyph) {
+            let glyph_bounds = outlined.px_bounds();
+            outlined.draw(|x, y, coverage| {
+                blend(
+                    canvas,
+                    (origin.0 + glyph_bounds.min.x + x as f32) as i64,
+                    (origin.1 + glyph_bounds.min.y + y as f32) as i64,
+                    color,
+                    coverage,
+                );
+            });
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use eframe::egui::{vec2, Vec2};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+const REPULSION: f32 = 6000.0;
+const SPRING_LENGTH: f32 = 90.0;
+const SPRING_STRENGTH: f32 = 0.02;
+const GRAVITY: f32 = 0.01;
+const DAMPING: f32 = 0.85;
+const MAX_SPEED: f32 = 40.0;
+const SETTLE_ENERGY: f32 = 0.05;
+
+// Above this many nodes repulsion is approximated with a Barnes–Hut quadtree
+const BARNES_HUT_THRESHOLD: usize = 150;
+const BARNES_HUT_THETA: f32 = 0.8;
+const QUADTREE_MAX_DEPTH: usize = 24;
+
+/// Incremental force-directed layout. Positions survive re-syncs, so adding
+/// or removing a note only nudges its neighbours instead of reshuffling the
+/// whole graph. Pinned nodes stay where the user dropped them.
+pub struct ForceLayout {
+    node_ids: Vec<String>,
+    positions: HashMap<String, Vec2>,
+    velocities: HashMap<String, Vec2>,
+    edges: Vec<(String, String)>,
+    pinned: HashSet<String>,
+    energy: f32,
+}
+
+impl ForceLayout {
+    pub fn new() -> Self {
+        Self {
+            node_ids: Vec::new(),
+            positions: HashMap::new(),
+            velocities: HashMap::new(),
+            edges: Vec::new(),
+            pinned: HashSet::new(),
+            energy: f32::MAX,
+        }
+    }
+    
+    /// Updates the set of nodes and edges, keeping the positions of nodes
+    /// that were already laid out. New nodes start next to their neighbours.
+    pub fn sync(&mut self, node_ids: &[String], edges: &[(String, String)]) {
+        let keep: HashSet<&String> = node_ids.iter().collect();
+        self.positions.retain(|id, _| keep.contains(id));
+        self.velocities.retain(|id, _| keep.contains(id));
+        self.pinned.retain(|id| keep.contains(id));
+        
+        self.edges = edges
+            .iter()
+            .filter(|(from, to)| keep.contains(from) && keep.contains(to) && from != to)
+            .cloned()
+            .collect();
+        self.node_ids = node_ids.to_vec();
+        
+        for (i, id) in node_ids.iter().enumerate() {
+            if self.positions.contains_key(id) {
+                continue;
+            }
+            
+            let placed_neighbors: Vec<Vec2> = self.edges
+                .iter()
+                .filter_map(|(from, to)| {
+                    if from == id { Some(to) } else if to == id { Some(from) } else { None }
+                })
+                .filter_map(|other| self.positions.get(other).copied())
+                .collect();
+            
+            let position = if placed_neighbors.is_empty() {
+                // Golden-angle spiral keeps unconnected newcomers spread out
+                let angle = i as f32 * 2.399_963;
+                let radius = 30.0 * (i as f32 + 1.0).sqrt();
+                vec2(radius * angle.cos(), radius * angle.sin())
+            } else {
+                let centroid = placed_neighbors.iter().fold(Vec2::ZERO, |sum, p| sum + *p) / placed_neighbors.len() as f32;
+                centroid + jitter(id) * 20.0
+            };
+            
+            self.positions.insert(id.clone(), position);
+            self.velocities.insert(id.clone(), Vec2::ZERO);
+        }
+        
+        self.wake();
+    }
+    
+    /// Advances the simulation one tick. Returns `false` once it has settled.
+    pub fn step(&mut self) -> bool {
+        if self.is_settled() {
+            return false;
+        }
+        
+        let index: HashMap<&String, usize> = self.node_ids.iter().enumerate().map(|(i, id)| (id, i)).collect();
+        let points: Vec<Vec2> = self.node_ids.iter().map(|id| self.positions[id]).collect();
+        let mut forces = vec![Vec2::ZERO; points.len()];
+        
+        // Repulsion between every pair of nodes
+        if points.len() > BARNES_HUT_THRESHOLD {
+            let tree = QuadTree::build(&points);
+            for (i, point) in points.iter().enumerate() {
+                forces[i] += tree.repulsion(i, *point);
+            }
+        } else {
+            for i in 0..points.len() {
+                for j in (i + 1)..points.len() {
+                    let force = repulsion(points[i], points[j], 1.0);
+                    forces[i] += force;
+                    forces[j] -= force;
+                }
+            }
+        }
+        
+        // Springs along links
+        for (from, to) in &self.edges {
+            let (a, b) = (index[from], index[to]);
+            let delta = points[b] - points[a];
+            let distance = delta.length().max(1.0);
+            let force = delta / distance * (distance - SPRING_LENGTH) * SPRING_STRENGTH;
+            forces[a] += force;
+            forces[b] -= force;
+        }
+        
+        // Gentle pull towards the origin so disconnected clusters don't drift off
+        let mut energy = 0.0;
+        for (i, id) in self.node_ids.iter().enumerate() {
+            if self.pinned.contains(id) {
+                continue;
+            }
+            
+            let force = forces[i] - points[i] * GRAVITY;
+            let velocity = self.velocities.entry(id.clone()).or_insert(Vec2::ZERO);
+            *velocity = (*velocity + force) * DAMPING;
+            if velocity.length() > MAX_SPEED {
+                *velocity = *velocity / velocity.length() * MAX_SPEED;
+            }
+            
+            energy += velocity.length_sq();
+            if let Some(position) = self.positions.get_mut(id) {
+                *position += *velocity;
+            }
+        }
+        
+        self.energy = if self.node_ids.is_empty() { 0.0 } else { energy / self.node_ids.len() as f32 };
+        !self.is_settled()
+    }
+    
+    pub fn is_settled(&self) -> bool {
+        self.energy < SETTLE_ENERGY
+    }
+    
+    /// Restarts the simulation, e.g. after nodes were moved by hand.
+    pub fn wake(&mut self) {
+        self.energy = f32::MAX;
+    }
+    
+    pub fn position(&self, node_id: &str) -> Option<Vec2> {
+        self.positions.get(node_id).copied()
+    }
+    
+    /// Moves a node by hand. Moved nodes are pinned in place.
+    pub fn set_position(&mut self, node_id: &str, position: Vec2) {
+        if let Some(current) = self.positions.get_mut(node_id) {
+            *current = position;
+            self.pinned.insert(node_id.to_string());
+            self.velocities.insert(node_id.to_string(), Vec2::ZERO);
+            self.wake();
+        }
+    }
+    
+    pub fn is_pinned(&self, node_id: &str) -> bool {
+        self.pinned.contains(node_id)
+    }
+    
+    pub fn toggle_pin(&mut self, node_id: &str) {
+        if !self.pinned.remove(node_id) {
+            self.pinned.insert(node_id.to_string());
+        }
+        self.wake();
+    }
+    
+    pub fn unpin_all(&mut self) {
+        self.pinned.clear();
+        self.wake();
+    }
+}
+
+impl Default for ForceLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Force pushing `a` away from `b`, scaled by the mass at `b`.
+fn repulsion(a: Vec2, b: Vec2, mass: f32) -> Vec2 {
+    let mut delta = a - b;
+    if delta.length_sq() < 0.01 {
+        delta = vec2(0.1, 0.0);
+    }
+    let distance_sq = delta.length_sq().max(1.0);
+    delta / distance_sq.sqrt() * (REPULSION * mass / distance_sq)
+}
+
+/// Stable small offset per node so stacked nodes separate deterministically.
+fn jitter(id: &str) -> Vec2 {
+    let mut hasher = DefaultHasher::new();
+    id.hash(&mut hasher);
+    let angle = (hasher.finish() % 6283) as f32 / 1000.0;
+    vec2(angle.cos(), angle.sin())
+}
+
+/// Quadtree over node positions for Barnes–Hut repulsion: distant groups of
+/// nodes are treated as a single mass at their centre.
+struct QuadTree {
+    cells: Vec<QuadCell>,
+}
+
+struct QuadCell {
+    center: Vec2,
+    half_size: f32,
+    mass: f32,
+    mass_center: Vec2,
+    body: Option<usize>,
+    children: Option<[usize; 4]>,
+}
+
+impl QuadTree {
+    fn build(points: &[Vec2]) -> Self {
+        let (mut min, mut max) = (points[0], points[0]);
+        for point in points {
+            min = min.min(*point);
+            max = max.max(*point);
+        }
+        let half_size = ((max - min).max_elem() / 2.0).max(1.0) + 1.0;
+        
+        let mut tree = Self {
+            cells: vec![QuadCell::new((min + max) / 2.0, half_size)],
+        };
+        for (i, point) in points.iter().enumerate() {
+            tree.insert(0, i, *point, points, 0);
+        }
+        tree
+    }
+    
+    fn insert(&mut self, cell: usize, body: usize, point: Vec2, points: &[Vec2], depth: usize) {
+        let total = self.cells[cell].mass + 1.0;
+        self.cells[cell].mass_center = (self.cells[cell].mass_center * self.cells[cell].mass + point) / total;
+        self.cells[cell].mass = total;
+        
+        if let Some(children) = self.cells[cell].children {
+            let child = children[self.cells[cell].quadrant(point)];
+            self.insert(child, body, point, points, depth + 1);
+            return;
+        }
+        
+        let existing = match self.cells[cell].body {
+            None if total == 1.0 => {
+                self.cells[cell].body = Some(body);
+                return;
+            }
+            existing => existing,
+        };
+        
+        // Coincident points would split forever; lump them together instead
+        if depth >= QUADTREE_MAX_DEPTH {
+            return;
+        }
+        
+        self.subdivide(cell);
+        self.cells[cell].body = None;
+        
+        let children = self.cells[cell].children.unwrap_or_default();
+        if let Some(existing) = existing {
+            let child = children[self.cells[cell].quadrant(points[existing])];
+            self.insert(child, existing, points[existing], points, depth + 1);
+        }
+        let child = children[self.cells[cell].quadrant(point)];
+        self.insert(child, body, point, points, depth + 1);
+    }
+    
+    fn subdivide(&mut self, cell: usize) {
+        let center = self.cells[cell].center;
+        let quarter = self.cells[cell].half_size / 2.0;
+        let mut children = [0; 4];
+        
+        for (quadrant, child) in children.iter_mut().enumerate() {
+            let offset = vec2(
+                if quadrant & 1 == 0 { -quarter } else { quarter },
+                if quadrant & 2 == 0 { -quarter } else { quarter },
+            );
+            *child = self.cells.len();
+            self.cells.push(QuadCell::new(center + offset, quarter));
+        }
+        
+        self.cells[cell].children = Some(children);
+    }
+    
+    fn repulsion(&self, body: usize, point: Vec2) -> Vec2 {
+        let mut force = Vec2::ZERO;
+        let mut stack = vec![0];
+        
+        while let Some(cell) = stack.pop() {
+            let cell = &self.cells[cell];
+            if cell.mass == 0.0 || cell.body == Some(body) {
+                continue;
+            }
+            
+            let distance = (cell.mass_center - point).length().max(1.0);
+            match cell.children {
+                Some(children) if cell.half_size * 2.0 / distance >= BARNES_HUT_THETA => {
+                    stack.extend_from_slice(&children);
+                }
+                _ => force += repulsion(point, cell.mass_center, cell.mass),
+            }
+        }
+        
+        force
+    }
+}
+
+impl QuadCell {
+    fn new(center: Vec2, half_size: f32) -> Self {
+        Self {
+            center,
+            half_size,
+            mass: 0.0,
+            mass_center: Vec2::ZERO,
+            body: None,
+            children: None,
+        }
+    }
+    
+    fn quadrant(&self, point: Vec2) -> usize {
+        let mut quadrant = 0;
+        if point.x >= self.center.x {
+            quadrant |= 1;
+        }
+        if point.y >= self.center.y {
+            quadrant |= 2;
+        }
+        quadrant
+    }
+}
@@ -1,7 +1,11 @@
 pub mod file_utils;
 pub mod markdown;
 pub mod export;
+pub mod graph_export;
+pub mod graph_layout;
 
 pub use file_utils::*;
 pub use markdown::*;
-pub use export::*;
\ No newline at end of file
+pub use export::*;
+pub use graph_export::*;
+pub use graph_layout::*;
\ No newline at end of file
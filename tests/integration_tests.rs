@@ -1,10 +1,11 @@
 use edison_note::{
-    features::{Note, NoteManager, SearchEngine, CollaborationManager},
+    features::{Note, NoteLink, LinkKind, NoteManager, SearchEngine, CollaborationManager, LinkProcessor, BacklinkIndex, LinkGraph, TaskIndex, TaskFilter, TaskStatusFilter, TaskDueFilter, TaskPriority, toggle_task_in_content, Reminder, ReminderScheduler, Recurrence, derive_reminders, parse_reminder_time, SearchFilter, SavedQuery, SavedSearch, QueryBlock, QueryFormat, SortOrder, expand_query_blocks, find_query_blocks, FenceLine, FenceScanner, Board, Canvas, CanvasNodeKind, CanvasEdgeEnd, CanvasSide, note_file_path, Folder, SearchQuery, QueryPlan, SearchField, CompareOp, FieldValue, MatchField, MatchType, IndexLoadReport, Snippet, Tokenizer, TokenizerConfig, Language, EmbeddingModel, HashedEmbedding, cosine_similarity, Facet, FacetCount, SearchWorker, NoteVersion, HistoryScope, HistorySource, DiffLine, diff_lines},
     ai::{EnhancementRequest, EnhancementType, WritingStyle, Sensitivity},
     storage::Database,
+    utils::ForceLayout,
 };
-use chrono::Utc;
-use std::sync::{Arc, Mutex};
+use chrono::{Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::sync::{Arc, Mutex, RwLock};
 
 #[tokio::test]
 async fn test_note_creation_and_management() {
@@ -109,10 +110,12 @@ fn test_search_engine() {
     
     // Test advanced search patterns
     let regex_results = search_engine.search("/[Mm]eeting/", 10);
-    // Would work with proper regex implementation
+    assert_eq!(regex_results.len(), 1);
+    assert_eq!(regex_results[0].title, "Meeting Notes");
     
     let tag_combo_results = search_engine.search("tag:project AND tag:meeting", 10);
-    // Would work with proper tag combination implementation
+    assert_eq!(tag_combo_results.len(), 1);
+    assert_eq!(tag_combo_results[0].title, "Meeting Notes");
     
     println!("✅ Search engine tests passed");
 }
@@ -182,6 +185,1668 @@ fn test_note_linking_patterns() {
     println!("✅ Note linking pattern tests passed");
 }
 
+#[test]
+fn test_rename_link_rewriting() {
+    let processor = LinkProcessor::new();
+    let content = "See [[Old Title]], [[old title#Goals]] and [[Old Title#Goals|the goals]]. Not [[Other]].";
+    
+    let (updated, count) = processor.rewrite_wiki_links(content, "Old Title", "New Title");
+    
+    assert_eq!(count, 3);
+    assert_eq!(
+        updated,
+        "See [[New Title]], [[New Title#Goals]] and [[New Title#Goals|the goals]]. Not [[Other]]."
+    );
+    
+    // Alias and heading parts are parsed separately from the target
+    let links = processor.extract_wiki_links("[[Project X#Status|status]]");
+    assert_eq!(links[0].target(), "Project X");
+    assert_eq!(links[0].heading(), Some("Status"));
+    assert_eq!(links[0].alias(), Some("status"));
+    
+    println!("✅ Rename link rewriting tests passed");
+}
+
+#[test]
+fn test_backlink_index() {
+    let mut target = Note::new("Project X".to_string());
+    target.update_content("---\naliases: [PX, \"The Project\"]\n---\n\nProject overview".to_string());
+    
+    let mut by_title = Note::new("Standup".to_string());
+    by_title.update_content("Discussed [[project x#Risks]] today.".to_string());
+    
+    let mut by_alias = Note::new("Roadmap".to_string());
+    by_alias.update_content("Next up: [[PX|the big one]]".to_string());
+    
+    let mut index = BacklinkIndex::new();
+    index.rebuild([&target, &by_title, &by_alias]);
+    
+    let mut sources = index.backlink_sources(&target.id);
+    sources.sort();
+    let mut expected = vec![by_title.id.clone(), by_alias.id.clone()];
+    expected.sort();
+    assert_eq!(sources, expected);
+    assert!(index.backlinks(&target.id)[0].context.contains("[["));
+    
+    // Saving a note only re-indexes that note
+    by_alias.update_content("No links any more".to_string());
+    index.update_note(&by_alias);
+    assert_eq!(index.backlink_sources(&target.id), vec![by_title.id.clone()]);
+    
+    // A second note with the same title takes over when the first goes away
+    let mut duplicate = Note::new("Project X".to_string());
+    duplicate.update_content("Copy".to_string());
+    index.update_note(&duplicate);
+    index.update_note(&target);
+    assert_eq!(index.resolve("project x"), Some(target.id.as_str()));
+    let mut renamed = target.clone();
+    renamed.title = "Project Y".to_string();
+    index.update_note(&renamed);
+    assert_eq!(index.resolve("project x"), Some(duplicate.id.as_str()));
+    assert_eq!(index.resolve("px"), Some(target.id.as_str()));
+    index.remove_note(&duplicate.id);
+    assert_eq!(index.resolve("project x"), None);
+    
+    // The map from LinkProcessor agrees with the index
+    let map = LinkProcessor::new().create_backlinks_map(&[target.clone(), by_title.clone()]);
+    assert_eq!(map.get(&target.id), Some(&vec![by_title.id.clone()]));
+    assert!(!map.contains_key(&by_title.id));
+    
+    println!("✅ Backlink index tests passed");
+}
+
+#[test]
+fn test_unlinked_mentions() {
+    let processor = LinkProcessor::new();
+    
+    let mut target = Note::new("Project X".to_string());
+    target.update_content("---\naliases: [PX]\n---\n".to_string());
+    
+    let mut source = Note::new("Weekly Review".to_string());
+    source.update_content("project x slipped. [[Project X]] is linked. PX again, but not PXL.".to_string());
+    
+    let mentions = processor.find_unlinked_mentions(&target, &[&target, &source]);
+    let matched: Vec<&str> = mentions.iter().map(|m| m.matched_text.as_str()).collect();
+    assert_eq!(matched, vec!["project x", "PX"]);
+    
+    // Linking keeps the original wording as an alias
+    let linked = processor.link_mention(&source.content, &mentions[0], &target.title).unwrap();
+    assert!(linked.starts_with("[[Project X|project x]] slipped."));
+    
+    // Stale mentions are refused rather than corrupting the note
+    assert!(processor.link_mention("changed", &mentions[1], &target.title).is_none());
+    
+    let suggestions = processor.suggest_links("Notes about project x planning", &["Project X".to_string()]);
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].confidence, 1.0);
+    
+    println!("✅ Unlinked mention tests passed");
+}
+
+#[test]
+fn test_broken_link_detection() {
+    let processor = LinkProcessor::new();
+    
+    let existing = Note::new("Reading List".to_string());
+    let mut source = Note::new("Inbox".to_string());
+    source.update_content("See [[reading list]] and [[Someday Ideas|ideas]].".to_string());
+    
+    let mut index = BacklinkIndex::new();
+    index.rebuild([&existing, &source]);
+    
+    // Each extracted link is recorded even when its target doesn't exist yet
+    let mut links: Vec<NoteLink> = processor.extract_note_links(&source.id, &source.content);
+    for link in &mut links {
+        link.to_note_id = index.resolve(&link.target_title).map(str::to_string);
+    }
+    
+    assert_eq!(links.len(), 2);
+    assert!(links[0].is_resolved());
+    assert_eq!(links[0].to_note_id.as_deref(), Some(existing.id.as_str()));
+    assert!(!links[1].is_resolved());
+    assert_eq!(links[1].target_title, "Someday Ideas");
+    
+    println!("✅ Broken link detection tests passed");
+}
+
+#[test]
+fn test_link_positions() {
+    let processor = LinkProcessor::new();
+    let content = "Intro [[Project X|the project]]\n![[Project X]] and [plan](notes/Project%20X.md#plan)\n[site](https://example.com)";
+    
+    let links = processor.extract_note_links("source", content);
+    assert_eq!(links.len(), 3);
+    
+    assert_eq!(links[0].kind, LinkKind::Wiki);
+    assert_eq!(links[0].line, 1);
+    assert_eq!(links[0].link_text, "[[Project X|the project]]");
+    assert_eq!(&content[links[0].start..links[0].end], links[0].link_text);
+    assert_eq!(links[0].display_text(), "the project");
+    
+    assert_eq!(links[1].kind, LinkKind::Embed);
+    assert_eq!(links[1].line, 2);
+    assert_eq!(links[1].link_text, "![[Project X]]");
+    
+    assert_eq!(links[2].kind, LinkKind::Markdown);
+    assert_eq!(links[2].target_title, "Project X");
+    assert_eq!(links[2].display_text(), "plan");
+    
+    // Stored ranges rewrite every kind of link in place
+    let (renamed, count) = processor.rewrite_link_ranges(content, &links, "project x", "Project Y").unwrap();
+    assert_eq!(count, 3);
+    assert!(renamed.starts_with("Intro [[Project Y|the project]]\n![[Project Y]] and [plan](notes/Project%20Y.md#plan)"));
+    
+    // Stale ranges are rejected so the caller can fall back to re-parsing
+    assert!(processor.rewrite_link_ranges("edited", &links, "Project X", "Project Y").is_none());
+    
+    println!("✅ Link position tests passed");
+}
+
+#[test]
+fn test_link_graph_analytics() {
+    let link = |from: &str, to: &str| NoteLink {
+        from_note_id: from.to_string(),
+        to_note_id: Some(to.to_string()),
+        target_title: to.to_string(),
+        link_text: format!("[[{}]]", to),
+        position: 0,
+        start: 0,
+        end: 0,
+        line: 1,
+        kind: LinkKind::Wiki,
+        relation: None,
+    };
+    
+    let mut graph = LinkGraph::new();
+    graph.rebuild(
+        ["a", "b", "c", "d", "e"],
+        &[link("a", "b"), link("a", "b"), link("b", "c"), link("d", "c"), link("e", "e")],
+    );
+    
+    // Duplicate links collapse into one edge
+    assert_eq!(graph.edge_count(), 4);
+    let mut incoming = graph.incoming("c");
+    incoming.sort();
+    assert_eq!(incoming, vec!["b", "d"]);
+    
+    // A note that only links to itself is still an orphan
+    assert_eq!(graph.orphans(), vec!["e"]);
+    
+    let ranks = graph.pagerank();
+    assert!((ranks.values().sum::<f64>() - 1.0).abs() < 1e-6);
+    assert!(ranks["c"] > ranks["a"]);
+    
+    let components = graph.components();
+    assert_eq!(components[0], vec!["a", "b", "c", "d"]);
+    assert_eq!(components[1], vec!["e"]);
+    
+    // Paths and neighborhoods follow links in either direction
+    assert_eq!(graph.shortest_path("a", "d").unwrap(), vec!["a", "b", "c", "d"]);
+    assert!(graph.shortest_path("a", "e").is_none());
+    let nearby: Vec<String> = graph.neighborhood("c", 1).into_iter().map(|(id, _)| id).collect();
+    assert_eq!(nearby, vec!["c", "b", "d"]);
+    
+    // Incremental updates replace a note's outgoing edges
+    let revision = graph.revision();
+    graph.set_links("d", &[]);
+    assert!(graph.revision() > revision);
+    assert_eq!(graph.orphans(), vec!["d", "e"]);
+    
+    // Re-saving the same links leaves the revision alone
+    let revision = graph.revision();
+    graph.set_links("b", &[link("b", "c")]);
+    assert_eq!(graph.revision(), revision);
+    
+    // A typed relation wins over a plain link between the same notes
+    let parent = NoteLink { relation: Some("parent".to_string()), ..link("b", "c") };
+    graph.set_links("b", &[link("b", "c"), parent]);
+    assert!(graph.revision() > revision);
+    let edges = graph.outgoing_edges("b");
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[0].0, "c");
+    assert_eq!(edges[0].1.relation.as_deref(), Some("parent"));
+    assert_eq!(edges[0].1.label.as_deref(), Some("parent"));
+    
+    println!("✅ Link graph analytics tests passed");
+}
+
+#[test]
+fn test_graph_export() {
+    use edison_note::utils::{GraphExporter, GraphSnapshot, GraphSnapshotEdge, GraphSnapshotNode};
+    
+    let node = |id: &str, title: &str, x: f32| GraphSnapshotNode {
+        id: id.to_string(),
+        title: title.to_string(),
+        tags: vec!["research".to_string()],
+        x,
+        y: 0.0,
+        radius: 12.0,
+        color: [0, 120, 212],
+    };
+    let graph = GraphSnapshot {
+        nodes: vec![node("a", "Ideas & \"Drafts\"", -50.0), node("b", "Outline", 50.0)],
+        edges: vec![GraphSnapshotEdge {
+            from: "a".to_string(),
+            to: "b".to_string(),
+            kind: LinkKind::Embed,
+            relation: None,
+            label: Some("see <this>".to_string()),
+            color: [150, 110, 200],
+        }],
+    };
+    let exporter = GraphExporter::new();
+    
+    let dot = exporter.to_dot(&graph);
+    assert!(dot.starts_with("digraph notes {"));
+    assert!(dot.contains("label=\"Ideas & \\\"Drafts\\\"\""));
+    assert!(dot.contains("\"a\" -> \"b\" [label=\"see <this>\", style=dashed];"));
+    
+    let graphml = exporter.to_graphml(&graph);
+    assert!(graphml.contains("<data key=\"title\">Ideas &amp; &quot;Drafts&quot;</data>"));
+    assert!(graphml.contains("<edge id=\"e0\" source=\"a\" target=\"b\">"));
+    
+    let json = exporter.to_json(&graph).unwrap();
+    assert!(json.contains("\"kind\": \"Embed\""));
+    
+    let svg = exporter.to_svg(&graph);
+    assert!(svg.contains("<circle") && svg.contains("fill=\"#0078d4\""));
+    assert!(svg.contains("see &lt;this&gt;"));
+    
+    let png = exporter.to_png(&graph, 1.0).unwrap();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    
+    println!("✅ Graph export tests passed");
+}
+
+#[test]
+fn test_relation_links() {
+    let processor = LinkProcessor::new();
+    let content = "parent:: [[Project X]]\n- Blocked-By:: [[Design Review]], [[Budget|budget sign-off]]\nSee [[Notes]] and key:: value";
+    
+    let links = processor.extract_note_links("task", content);
+    let relations: Vec<(Option<&str>, &str)> = links.iter()
+        .map(|link| (link.relation.as_deref(), link.target_title.as_str()))
+        .collect();
+    assert_eq!(relations, vec![
+        (Some("parent"), "Project X"),
+        (Some("blocked-by"), "Design Review"),
+        (Some("blocked-by"), "Budget"),
+        (None, "Notes"),
+    ]);
+    
+    let mut task = Note::new("Launch".to_string());
+    task.update_content(content.to_string());
+    let mut other = Note::new("Hiring".to_string());
+    other.update_content("blocked-by:: [[Budget]]".to_string());
+    let unrelated = Note::new("Budget".to_string());
+    
+    let mut search_engine = SearchEngine::new();
+    search_engine.update_index(&[task.clone(), other.clone(), unrelated]);
+    
+    let blocked: Vec<String> = search_engine.search("blocked-by::[[budget]]", 10)
+        .into_iter()
+        .map(|result| result.title)
+        .collect();
+    assert_eq!(blocked, vec!["Hiring", "Launch"]);
+    
+    let children = search_engine.search("parent:: \"Project X\"", 10);
+    assert_eq!(children.len(), 1);
+    assert_eq!(children[0].note_id, task.id);
+    
+    // A bare relation lists every note using it
+    assert_eq!(search_engine.search("blocked-by::", 10).len(), 2);
+    
+    println!("✅ Relation link tests passed");
+}
+
+#[test]
+fn test_task_index() {
+    let mut note = Note::new("Sprint".to_string());
+    note.update_content("# Sprint\n- [ ] Ship release 📅 2026-10-20 ⏫ #work\n- [x] Write changelog due:2026-10-15\n  * [ ] Review PR priority:low #work/review\n```\n- [ ] not a task\n```\n1. [ ] Book venue".to_string());
+    let mut trashed = Note::new("Old".to_string());
+    trashed.update_content("- [ ] Forgotten".to_string());
+    trashed.move_to_trash();
+    
+    let mut index = TaskIndex::new();
+    index.rebuild([&note, &trashed]);
+    
+    let tasks = index.tasks_for_note(&note.id);
+    assert_eq!(tasks.len(), 4);
+    assert!(index.tasks_for_note(&trashed.id).is_empty());
+    
+    assert_eq!(tasks[0].line, 2);
+    assert_eq!(tasks[0].description, "Ship release #work");
+    assert_eq!(tasks[0].due, NaiveDate::from_ymd_opt(2026, 10, 20));
+    assert_eq!(tasks[0].priority, Some(TaskPriority::High));
+    assert_eq!(tasks[0].tags, vec!["work"]);
+    
+    assert!(tasks[1].done);
+    assert_eq!(tasks[1].due, NaiveDate::from_ymd_opt(2026, 10, 15));
+    assert_eq!(tasks[2].priority, Some(TaskPriority::Low));
+    assert_eq!(tasks[2].tags, vec!["work/review"]);
+    assert_eq!(tasks[3].line, 8);
+    
+    // Filters
+    let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+    let open = index.query(&TaskFilter::default(), today);
+    assert_eq!(open.len(), 3);
+    assert_eq!(open[0].description, "Ship release #work"); // dated tasks first
+    
+    let overdue = TaskFilter { status: TaskStatusFilter::All, due: TaskDueFilter::Overdue, ..TaskFilter::default() };
+    assert!(index.query(&overdue, today).is_empty()); // the overdue task is already done
+    
+    let this_week = TaskFilter { due: TaskDueFilter::ThisWeek, ..TaskFilter::default() };
+    assert_eq!(index.query(&this_week, NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()).len(), 1);
+    
+    let tagged = TaskFilter { tag: Some("work".to_string()), ..TaskFilter::default() };
+    assert_eq!(index.query(&tagged, today).len(), 1);
+    
+    // Toggling flips only the indexed line, and refuses stale tasks
+    let toggled = toggle_task_in_content(&note.content, &tasks[0]).unwrap();
+    assert!(toggled.contains("- [x] Ship release"));
+    assert!(toggled.contains("* [ ] Review PR"));
+    
+    let reopened = toggle_task_in_content(&note.content, &tasks[1]).unwrap();
+    assert!(reopened.contains("- [ ] Write changelog"));
+    
+    let mut edited = note.clone();
+    edited.update_content("- [ ] Something else\n- [ ] Ship release".to_string());
+    assert!(toggle_task_in_content(&edited.content, &tasks[0]).is_none());
+    
+    println!("✅ Task index tests passed");
+}
+
+/// UTC+1, or UTC+2 during 2026's European summer time.
+#[derive(Debug, Clone, Copy)]
+struct EuropeanZone;
+
+impl TimeZone for EuropeanZone {
+    type Offset = FixedOffset;
+    
+    fn from_offset(_: &FixedOffset) -> Self {
+        EuropeanZone
+    }
+    
+    fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+        self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+    }
+    
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+        let offsets: Vec<FixedOffset> = [2, 1]
+            .into_iter()
+            .map(|hours| FixedOffset::east_opt(hours * 3600).unwrap())
+            .filter(|&offset| self.offset_from_utc_datetime(&(*local - offset)) == offset)
+            .collect();
+        match offsets[..] {
+            [] => LocalResult::None,
+            [offset] => LocalResult::Single(offset),
+            [earliest, latest, ..] => LocalResult::Ambiguous(earliest, latest),
+        }
+    }
+    
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+        self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+    }
+    
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+        let summer = Utc.with_ymd_and_hms(2026, 3, 29, 1, 0, 0).unwrap().naive_utc()..Utc.with_ymd_and_hms(2026, 10, 25, 1, 0, 0).unwrap().naive_utc();
+        FixedOffset::east_opt(if summer.contains(utc) { 2 * 3600 } else { 3600 }).unwrap()
+    }
+}
+
+#[test]
+fn test_reminder_scheduling() {
+    // Recurrence
+    let anchor = Utc.with_ymd_and_hms(2026, 10, 1, 9, 0, 0).unwrap();
+    let now = Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap();
+    assert_eq!(Recurrence::parse("Daily"), Some(Recurrence::Daily));
+    assert_eq!(Recurrence::parse(""), Some(Recurrence::Once));
+    assert!(Recurrence::parse("0 9 * * Mon").is_some());
+    assert!(Recurrence::parse("every tuesday").is_none());
+    assert_eq!(Recurrence::Daily.next_after(anchor, now), Some(Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap()));
+    assert_eq!(Recurrence::Weekly.next_after(anchor, now), Some(Utc.with_ymd_and_hms(2026, 10, 22, 9, 0, 0).unwrap()));
+    assert_eq!(Recurrence::Once.next_after(anchor, now), None);
+    let cron_next = Recurrence::parse("*/15 * * * *").unwrap().next_after(anchor, now).unwrap();
+    assert!(cron_next > now && cron_next <= now + Duration::minutes(15));
+    
+    // Daily and weekly reminders keep their local time across DST changes
+    let anchor = Utc.with_ymd_and_hms(2026, 10, 20, 7, 0, 0).unwrap(); // 9:00 summer time
+    let after = Utc.with_ymd_and_hms(2026, 10, 26, 12, 0, 0).unwrap();
+    assert_eq!(Recurrence::Daily.next_after_in(anchor, after, &EuropeanZone), Some(Utc.with_ymd_and_hms(2026, 10, 27, 8, 0, 0).unwrap()));
+    assert_eq!(Recurrence::Weekly.next_after_in(anchor, after, &EuropeanZone), Some(Utc.with_ymd_and_hms(2026, 10, 27, 8, 0, 0).unwrap()));
+    let anchor = Utc.with_ymd_and_hms(2026, 3, 28, 1, 30, 0).unwrap(); // 2:30, which the clocks skip the next day
+    let after = Utc.with_ymd_and_hms(2026, 3, 28, 12, 0, 0).unwrap();
+    assert_eq!(Recurrence::Daily.next_after_in(anchor, after, &EuropeanZone), Some(Utc.with_ymd_and_hms(2026, 3, 29, 1, 30, 0).unwrap()));
+    
+    // Reminders from a property and from open tasks with due dates
+    let mut note = Note::new("Dentist".to_string());
+    note.update_content("---\nreminder: 2026-10-17 08:30\nrepeat: weekly\n---\n- [ ] Call office 📅 2026-10-25\n- [x] Book slot 📅 2026-10-10".to_string());
+    let mut tasks = TaskIndex::new();
+    tasks.update_note(&note);
+    
+    let derived = derive_reminders(&note, tasks.tasks_for_note(&note.id));
+    assert_eq!(derived.len(), 2);
+    assert_eq!(derived[0].recurrence, Recurrence::Weekly);
+    assert_eq!(Some(derived[0].remind_at), parse_reminder_time("2026-10-17 08:30"));
+    assert_eq!(derived[1].message, "Call office");
+    assert_eq!(Some(derived[1].remind_at), parse_reminder_time("2026-10-25"));
+    
+    // Reminders due before startup are reported once, as missed
+    let started = Utc.with_ymd_and_hms(2026, 10, 18, 8, 0, 0).unwrap();
+    let mut scheduler = ReminderScheduler::starting_at(started);
+    let changes = scheduler.sync_derived(&note.id, derived.clone());
+    assert_eq!(changes.saved.len(), 2);
+    assert!(scheduler.sync_derived(&note.id, derived.clone()).saved.is_empty());
+    
+    let manual = Reminder::new(note.id.clone(), "Pack bag".to_string(), now + Duration::hours(1), Recurrence::Once);
+    let manual_id = manual.id.clone();
+    scheduler.insert(manual);
+    
+    let fired = scheduler.poll(now);
+    assert_eq!(fired.len(), 1);
+    assert!(fired[0].missed);
+    assert!(scheduler.poll(now).is_empty());
+    
+    let fired = scheduler.poll(now + Duration::hours(2));
+    assert_eq!(fired.len(), 1);
+    assert!(!fired[0].missed);
+    
+    // Snoozing fires again later; completing moves recurring reminders on
+    scheduler.snooze(&manual_id, now + Duration::hours(3));
+    assert!(scheduler.poll(now + Duration::hours(2)).is_empty());
+    assert_eq!(scheduler.poll(now + Duration::hours(3)).len(), 1);
+    scheduler.complete(&manual_id, now + Duration::hours(3));
+    assert!(scheduler.get(&manual_id).unwrap().is_done);
+    
+    let property_id = format!("{}:property", note.id);
+    scheduler.complete(&property_id, now);
+    assert_eq!(scheduler.get(&property_id).unwrap().remind_at, parse_reminder_time("2026-10-24 08:30").unwrap());
+    // The advanced recurring reminder survives a re-sync of the unchanged note
+    assert!(scheduler.sync_derived(&note.id, derived).saved.is_empty());
+    
+    // Removing the property drops its reminder but keeps manual ones
+    note.update_content("- [ ] Call office 📅 2026-10-25".to_string());
+    tasks.update_note(&note);
+    let changes = scheduler.sync_derived(&note.id, derive_reminders(&note, tasks.tasks_for_note(&note.id)));
+    assert_eq!(changes.removed, vec![property_id]);
+    assert!(scheduler.get(&manual_id).is_some());
+    
+    println!("✅ Reminder scheduling tests passed");
+}
+
+#[test]
+fn test_saved_searches() {
+    let mut meeting = Note::new("Team Meeting".to_string());
+    meeting.update_content("Agenda for the weekly sync".to_string());
+    meeting.add_tag("work".to_string());
+    let mut recipe = Note::new("Pasta".to_string());
+    recipe.update_content("Boil water".to_string());
+    
+    let mut search_engine = SearchEngine::new();
+    search_engine.update_index(&[meeting.clone(), recipe.clone()]);
+    
+    let by_text = SavedSearch::new("Meetings".to_string(), SavedQuery::Text("meeting".to_string()));
+    let by_filter = SavedSearch::new("Work".to_string(), SavedQuery::Filter(SearchFilter {
+        tags: vec!["work".to_string()],
+        ..SearchFilter::default()
+    }));
+    
+    let titles = |engine: &SearchEngine, search: &SavedSearch| -> Vec<String> {
+        let mut titles: Vec<String> = engine.run_saved_search(search, 10).into_iter().map(|r| r.title).collect();
+        titles.sort();
+        titles
+    };
+    assert_eq!(titles(&search_engine, &by_text), vec!["Team Meeting"]);
+    assert_eq!(titles(&search_engine, &by_filter), vec!["Team Meeting"]);
+    
+    // Smart folders follow edits without a full re-index
+    let revision = search_engine.revision();
+    recipe.update_content("Bring pasta to the meeting".to_string());
+    recipe.add_tag("work".to_string());
+    search_engine.update_note(&recipe);
+    assert!(search_engine.revision() > revision);
+    assert_eq!(titles(&search_engine, &by_text), vec!["Pasta", "Team Meeting"]);
+    assert_eq!(titles(&search_engine, &by_filter), vec!["Pasta", "Team Meeting"]);
+    
+    meeting.move_to_trash();
+    search_engine.update_note(&meeting);
+    assert_eq!(titles(&search_engine, &by_text), vec!["Pasta"]);
+    search_engine.remove_note(&recipe.id);
+    assert!(titles(&search_engine, &by_filter).is_empty());
+    
+    // Queries round-trip through the JSON stored in the database
+    let json = serde_json::to_string(&by_filter.query).unwrap();
+    match serde_json::from_str::<SavedQuery>(&json).unwrap() {
+        SavedQuery::Filter(filter) => {
+            assert_eq!(filter.tags, vec!["work"]);
+            assert!(filter.include_titles && filter.include_content);
+        }
+        SavedQuery::Text(_) => panic!("expected a filter query"),
+    }
+    
+    println!("✅ Saved search tests passed");
+}
+
+#[test]
+fn test_query_blocks() {
+    let block = QueryBlock::parse("tag:project AND status:active SORT due DESC FIELDS title,owner,due LIMIT 2").unwrap();
+    assert_eq!(block.sort, Some(("due".to_string(), SortOrder::Desc)));
+    assert_eq!(block.fields, vec!["title", "owner", "due"]);
+    assert_eq!(block.columns(), vec!["title", "owner", "due"]);
+    assert_eq!(block.limit, Some(2));
+    assert_eq!(block.format, QueryFormat::Table);
+    assert_eq!(block.filter, Some(SearchQuery::parse("tag:project AND status:active").unwrap()));
+    
+    // The filter is a search box query; quoted text can't start a clause
+    assert_eq!(QueryBlock::parse("tag:project").unwrap().format, QueryFormat::List);
+    assert_eq!(QueryBlock::parse("").unwrap().filter, None);
+    assert_eq!(QueryBlock::parse("TABLE SORT title").unwrap().format, QueryFormat::Table);
+    let quoted = QueryBlock::parse("title:\"SORT order\" LIMIT 3").unwrap();
+    assert_eq!((quoted.filter, quoted.limit), (Some(SearchQuery::parse("title:\"SORT order\"").unwrap()), Some(3)));
+    assert!(QueryBlock::parse("LIMIT many").is_err());
+    assert!(QueryBlock::parse("(tag:project").is_err());
+    assert!(QueryBlock::parse("modified:>someday").is_err());
+    
+    let mut alpha = Note::new("Alpha".to_string());
+    alpha.update_content("---\nstatus: active\nowner: Ana\ndue: 2026-11-01\n---\nLaunch plan".to_string());
+    alpha.add_tag("project/web".to_string());
+    let mut beta = Note::new("Beta".to_string());
+    beta.update_content("---\nstatus: active\nowner: Ben\ndue: 2026-12-01\ntags: [project]\n---\nMigration".to_string());
+    let mut gamma = Note::new("Gamma".to_string());
+    gamma.update_content("---\nstatus: paused\n---\nOn hold".to_string());
+    gamma.add_tag("project".to_string());
+    
+    let mut search_engine = SearchEngine::new();
+    search_engine.update_index(&[alpha.clone(), beta.clone(), gamma.clone()]);
+    
+    // Tag prefixes, frontmatter tags and property matches
+    let result = search_engine.run_query(&block);
+    let rows: Vec<Vec<String>> = result.rows.iter().map(|row| row.values.clone()).collect();
+    assert_eq!(rows, vec![
+        vec!["Beta".to_string(), "Ben".to_string(), "2026-12-01".to_string()],
+        vec!["Alpha".to_string(), "Ana".to_string(), "2026-11-01".to_string()],
+    ]);
+    
+    let titles = |source: &str| -> Vec<String> {
+        search_engine.run_query(&QueryBlock::parse(source).unwrap()).rows.into_iter().map(|row| row.title).collect()
+    };
+    assert_eq!(titles("tag:project OR status:paused SORT title"), vec!["Alpha", "Beta", "Gamma"]);
+    assert_eq!(titles("tag:project -status:active"), vec!["Gamma"]);
+    assert_eq!(titles("NOT tag:project SORT title"), Vec::<String>::new());
+    assert_eq!(titles("owner:* SORT owner DESC LIMIT 1"), vec!["Beta"]);
+    assert_eq!(titles("migration"), vec!["Beta"]);
+    assert_eq!(titles("modified:>2026-01-01 SORT title"), vec!["Alpha", "Beta", "Gamma"]);
+    assert_eq!(titles("modified:<2026-01-01"), Vec::<String>::new());
+    assert_eq!(titles("title:\"Gam\" OR \"launch plan\" SORT title"), vec!["Alpha", "Gamma"]);
+    
+    // Blocks expand to markdown for export; other code fences are left alone
+    let content = "# Status\n\n```query\nstatus:paused\n```\n\n```rust\nfn main() {}\n```\n\n```query\nLIMIT x\n```\n";
+    let expanded = expand_query_blocks(content, |block| search_engine.run_query(block));
+    assert!(expanded.contains("- [[Gamma]]"));
+    assert!(expanded.contains("```rust\nfn main() {}\n```"));
+    assert!(expanded.contains("> Query error:"));
+    assert!(!expanded.contains("```query"));
+    
+    // The preview and the block finder share one fence scanner
+    let content = "```md\n```query\nnot a block\n```\n```query\ntag:project\n```";
+    let blocks = find_query_blocks(content);
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].1, "tag:project");
+    assert_eq!(&content[blocks[0].0.clone()], "```query\ntag:project\n```");
+    let mut fences = FenceScanner::new();
+    let lines: Vec<FenceLine> = content.lines().map(|line| fences.classify(line)).collect();
+    assert_eq!(lines, vec![
+        FenceLine::Open("md"),
+        FenceLine::Inside("md"),
+        FenceLine::Inside("md"),
+        FenceLine::Close("md"),
+        FenceLine::Open("query"),
+        FenceLine::Inside("query"),
+        FenceLine::Close("query"),
+    ]);
+    
+    println!("✅ Query block tests passed");
+}
+
+#[test]
+fn test_kanban_board() {
+    let content = "---\nboard: true\n---\n# Launch\n\n## Todo\n\n- Write docs\n  - cover the CLI\n- [ ] [[Pricing Page]]\n\n## Doing\n\n* Fix login\n\n```md\n## Not a column\n```\n\n## Done\n";
+    let mut board = Board::parse(content);
+    
+    // Unchanged boards write back byte for byte
+    assert_eq!(board.to_markdown(), content);
+    assert_eq!(Board::parse("## Todo\n- a").to_markdown(), "## Todo\n- a");
+    
+    let titles: Vec<&str> = board.columns.iter().map(|column| column.title.as_str()).collect();
+    assert_eq!(titles, vec!["Todo", "Doing", "Done"]);
+    let todo = board.columns[0].cards();
+    assert_eq!(todo.len(), 2);
+    assert_eq!(todo[0].text, "Write docs");
+    assert_eq!(todo[0].details(), vec!["- cover the CLI"]);
+    assert_eq!(todo[1].link.as_deref(), Some("Pricing Page"));
+    assert_eq!(todo[1].done, Some(false));
+    assert_eq!(board.columns[1].cards().len(), 1);
+    
+    // Moving a card carries its nested lines along
+    assert!(board.move_card(0, 0, 1, 0));
+    assert!(board.move_card(0, 0, 2, 0));
+    assert!(!board.move_card(0, 5, 1, 0));
+    assert!(board.add_card(0, "Plan launch"));
+    assert!(board.add_column("Blocked"));
+    let moved = board.to_markdown();
+    assert_eq!(moved, "---\nboard: true\n---\n# Launch\n\n## Todo\n\n- Plan launch\n\n## Doing\n\n- Write docs\n  - cover the CLI\n* Fix login\n\n```md\n## Not a column\n```\n\n## Done\n\n- [ ] [[Pricing Page]]\n\n## Blocked\n\n");
+    assert_eq!(Board::parse(&moved).to_markdown(), moved);
+    
+    // The rewritten markdown parses back into the same board
+    let reparsed = Board::parse(&moved);
+    assert_eq!(reparsed, board);
+    let titles: Vec<&str> = reparsed.columns.iter().map(|column| column.title.as_str()).collect();
+    assert_eq!(titles, vec!["Todo", "Doing", "Done", "Blocked"]);
+    assert_eq!(reparsed.columns[2].cards()[0].link.as_deref(), Some("Pricing Page"));
+    
+    println!("✅ Kanban board tests passed");
+}
+
+#[test]
+fn test_canvas_json() {
+    // A file written by another JSON Canvas editor
+    let json = r##"{
+        "nodes": [
+            {"id": "a1", "type": "text", "text": "# Ideas", "x": -100, "y": -50, "width": 250, "height": 60, "color": "4", "styleAttributes": {"shape": "pill"}},
+            {"id": "b2", "type": "file", "file": "Projects/Roadmap.md", "x": 300, "y": 0, "width": 400, "height": 300},
+            {"id": "c3", "type": "link", "url": "https://jsoncanvas.org", "x": 0, "y": 400, "width": 400, "height": 120},
+            {"id": "g4", "type": "group", "label": "Q4", "x": -200, "y": -100, "width": 1000, "height": 700}
+        ],
+        "edges": [
+            {"id": "e1", "fromNode": "a1", "fromSide": "right", "toNode": "b2", "toSide": "left", "toEnd": "arrow", "label": "plans", "weight": 2}
+        ],
+        "metadata": {"version": "1.0"}
+    }"##;
+    let mut canvas = Canvas::from_json(json).unwrap();
+    assert_eq!(canvas.nodes.len(), 4);
+    assert_eq!(canvas.nodes[1].note_title(), Some("Roadmap"));
+    assert!(matches!(&canvas.nodes[2].kind, CanvasNodeKind::Link { url } if url == "https://jsoncanvas.org"));
+    assert_eq!(canvas.edges[0].from_side, Some(CanvasSide::Right));
+    assert_eq!(canvas.edges[0].to_end, Some(CanvasEdgeEnd::Arrow));
+    assert!(canvas.edges[0].to_arrow() && !canvas.edges[0].from_arrow());
+    assert!(Canvas::from_json("").unwrap().nodes.is_empty());
+    
+    // Cards win over the group behind them
+    assert_eq!(canvas.node_at(0.0, 0.0).map(|node| node.id.as_str()), Some("a1"));
+    assert_eq!(canvas.node_at(-150.0, 300.0).map(|node| node.id.as_str()), Some("g4"));
+    assert!(canvas.node_at(5000.0, 0.0).is_none());
+    
+    // Writing keeps the spec's field names so other tools can read it back
+    let written = canvas.to_json().unwrap();
+    assert!(written.contains("\"fromNode\": \"a1\"") && written.contains("\"type\": \"file\""));
+    assert!(!written.contains("subpath") && !written.contains("null"));
+    assert_eq!(Canvas::from_json(&written).unwrap(), canvas);
+    
+    // Fields this editor doesn't know about survive a round trip, and known ones aren't duplicated
+    assert_eq!(canvas.nodes[0].extra.keys().collect::<Vec<_>>(), vec!["styleAttributes"]);
+    assert_eq!(canvas.edges[0].extra["weight"], 2);
+    assert_eq!(canvas.extra["metadata"]["version"], "1.0");
+    assert!(written.contains("\"shape\": \"pill\"") && written.contains("\"weight\": 2") && written.contains("\"metadata\""));
+    assert_eq!(written.matches("\"type\": \"text\"").count(), 1);
+    
+    let note = canvas.add_node(CanvasNodeKind::File { file: note_file_path("Meeting: Notes"), subpath: None }, 0, 800);
+    let image = canvas.add_node(CanvasNodeKind::File { file: "assets/diagram.PNG".to_string(), subpath: None }, 500, 800);
+    assert_eq!(canvas.node(&note).unwrap().note_title(), Some("Meeting: Notes"));
+    
+    // File nodes resolve to the note by its real title, even one containing a slash
+    let resolve = |title: &str| ["Meeting: Notes", "Q1/Q2 plan", "Roadmap"].contains(&title).then(|| title.to_string());
+    assert_eq!(canvas.node(&note).unwrap().resolve_note(resolve).as_deref(), Some("Meeting: Notes"));
+    let slashed = canvas.add_node(CanvasNodeKind::File { file: note_file_path("Q1/Q2 plan"), subpath: None }, 0, 1200);
+    assert_eq!(canvas.node(&slashed).unwrap().resolve_note(resolve).as_deref(), Some("Q1/Q2 plan"));
+    assert_eq!(canvas.node("b2").unwrap().resolve_note(resolve).as_deref(), Some("Roadmap"));
+    assert!(canvas.remove_node(&slashed));
+    assert!(canvas.node(&image).unwrap().is_image() && !canvas.node(&note).unwrap().is_image());
+    
+    assert!(canvas.connect(&note, "a1").is_some());
+    assert!(canvas.connect(&note, "a1").is_none());
+    assert!(canvas.connect(&note, &note).is_none());
+    assert!(canvas.connect(&note, "missing").is_none());
+    assert_eq!(canvas.edges.len(), 2);
+    
+    // Removing a card takes its arrows with it
+    assert!(canvas.remove_node("a1"));
+    assert!(canvas.edges.is_empty());
+    assert!(!canvas.remove_node("a1"));
+    
+    let path = std::env::temp_dir().join(format!("edison_canvas_{}.canvas", std::process::id()));
+    canvas.save(&path).unwrap();
+    assert_eq!(Canvas::load(&path).unwrap(), canvas);
+    std::fs::remove_file(&path).unwrap();
+    
+    println!("✅ Canvas tests passed");
+}
+
+#[test]
+fn test_search_query_language() {
+    // Grammar: juxtaposition is AND, OR binds looser, NOT and - bind tightest
+    assert_eq!(
+        SearchQuery::parse("title:meeting tag:work OR -draft").unwrap(),
+        SearchQuery::Or(vec![
+            SearchQuery::And(vec![
+                SearchQuery::Field { field: SearchField::Title, op: CompareOp::Matches, value: FieldValue::Text("meeting".to_string()) },
+                SearchQuery::Field { field: SearchField::Tag, op: CompareOp::Matches, value: FieldValue::Text("work".to_string()) },
+            ]),
+            SearchQuery::Not(Box::new(SearchQuery::Term("draft".to_string()))),
+        ])
+    );
+    assert_eq!(
+        SearchQuery::parse("priority:>=2 \"status report\"").unwrap(),
+        SearchQuery::And(vec![
+            SearchQuery::Field { field: SearchField::Property("priority".to_string()), op: CompareOp::Ge, value: FieldValue::Text("2".to_string()) },
+            SearchQuery::Phrase("status report".to_string()),
+        ])
+    );
+    assert_eq!(
+        SearchQuery::parse("property:owner=ana").unwrap(),
+        SearchQuery::Field { field: SearchField::Property("owner".to_string()), op: CompareOp::Eq, value: FieldValue::Text("ana".to_string()) }
+    );
+    assert!(SearchQuery::parse("meeting").unwrap().is_plain_term());
+    assert!(SearchQuery::parse("see https://example.com").is_ok());
+    assert!(SearchQuery::parse("todo: milk").is_ok());
+    
+    // Errors say what went wrong and where
+    let error = SearchQuery::parse("(tag:work OR tag:home").unwrap_err();
+    assert_eq!(error.position, 0);
+    assert!(error.message.contains("')'"));
+    assert_eq!(SearchQuery::parse("tag:work)").unwrap_err().position, 8);
+    assert_eq!(SearchQuery::parse("title:\"open").unwrap_err().message, "Unterminated quote");
+    assert!(SearchQuery::parse("/[a-/").unwrap_err().message.starts_with("Invalid regex"));
+    assert!(SearchQuery::parse("created:yesterday-ish").unwrap_err().message.starts_with("Invalid date"));
+    assert_eq!(SearchQuery::parse("tag:work AND").unwrap_err().message, "Expected a search term after AND");
+    assert!(SearchQuery::parse("title: meeting").unwrap_err().message.contains("title:"));
+    assert!(SearchQuery::parse("tag:/wo.k/").is_err());
+    
+    let mut work = Folder::new("Work".to_string(), None);
+    work.id = "folder-work".to_string();
+    
+    let mut standup = Note::new("Standup meeting".to_string());
+    standup.update_content("---\nstatus: active\npriority: 3\n---\nDiscussed the status report".to_string());
+    standup.add_tag("work".to_string());
+    standup.folder_id = Some(work.id.clone());
+    standup.created_at = chrono::Utc.with_ymd_and_hms(2024, 3, 10, 9, 0, 0).unwrap();
+    standup.modified_at = standup.created_at;
+    
+    let mut retro = Note::new("Retro meeting".to_string());
+    retro.update_content("---\nstatus: draft\npriority: 1\ntags: [work/team]\n---\nWhat went well".to_string());
+    retro.created_at = chrono::Utc.with_ymd_and_hms(2024, 6, 1, 9, 0, 0).unwrap();
+    retro.modified_at = retro.created_at;
+    
+    let mut groceries = Note::new("Groceries".to_string());
+    groceries.update_content("Milk and eggs for the meeting snacks".to_string());
+    groceries.add_tag("home".to_string());
+    groceries.created_at = chrono::Utc.with_ymd_and_hms(2023, 12, 15, 12, 0, 0).unwrap();
+    groceries.modified_at = groceries.created_at;
+    
+    let mut search_engine = SearchEngine::new();
+    search_engine.update_index(&[standup.clone(), retro.clone(), groceries.clone()]);
+    search_engine.set_folders([&work]);
+    
+    let titles = |query: &str| -> Vec<String> {
+        let mut titles: Vec<String> = search_engine.search(query, 10).into_iter().map(|r| r.title).collect();
+        titles.sort();
+        titles
+    };
+    
+    // Every part of the query counts, not just the first recognised prefix
+    assert_eq!(titles("title:meeting tag:work"), vec!["Retro meeting", "Standup meeting"]);
+    assert_eq!(titles("title:meeting tag:=work"), vec!["Standup meeting"]);
+    assert_eq!(titles("meeting -tag:work"), vec!["Groceries"]);
+    assert_eq!(titles("(tag:home OR status:draft) meeting"), vec!["Groceries", "Retro meeting"]);
+    assert_eq!(titles("NOT title:meeting"), vec!["Groceries"]);
+    assert_eq!(titles("\"status report\""), vec!["Standup meeting"]);
+    assert_eq!(titles("content:milk"), vec!["Groceries"]);
+    assert_eq!(titles("title:milk"), Vec::<String>::new());
+    assert_eq!(titles("folder:work"), vec!["Standup meeting"]);
+    assert_eq!(titles("folder:!=work title:meeting"), vec!["Retro meeting"]);
+    assert_eq!(titles("priority:>=2"), vec!["Standup meeting"]);
+    assert_eq!(titles("priority:<3 status:*"), vec!["Retro meeting"]);
+    assert_eq!(titles("status:active OR status:draft"), vec!["Retro meeting", "Standup meeting"]);
+    
+    // Candidates come from the word index, which still finds text inside longer words
+    assert_eq!(titles("eting snacks"), vec!["Groceries"]);
+    assert_eq!(titles("\"eting snac\""), vec!["Groceries"]);
+    assert_eq!(titles("eting OR milk"), vec!["Groceries", "Retro meeting", "Standup meeting"]);
+    assert_eq!(titles("title:=groceries"), vec!["Groceries"]);
+    assert_eq!(titles("content:wha"), vec!["Retro meeting"]); // Inside a stop word, which isn't indexed
+    
+    // Regex combines with the other filters
+    assert_eq!(titles("/m[e]+ting/ created:2024"), vec!["Retro meeting", "Standup meeting"]);
+    assert_eq!(titles("title:/^retro/"), vec!["Retro meeting"]);
+    
+    // Dates: whole years, months, days, open-ended ranges and comparisons
+    assert_eq!(titles("created:2024-03"), vec!["Standup meeting"]);
+    assert_eq!(titles("created:2024-03-10"), vec!["Standup meeting"]);
+    assert_eq!(titles("created:2024-01..2024-05"), vec!["Standup meeting"]);
+    assert_eq!(titles("created:..2024-03-09"), vec!["Groceries"]);
+    assert_eq!(titles("modified:>2024-03-10"), vec!["Retro meeting"]);
+    assert_eq!(titles("modified:>=2024-03-10"), vec!["Retro meeting", "Standup meeting"]);
+    assert_eq!(titles("created:<2024"), vec!["Groceries"]);
+    assert_eq!(titles("created:!=2024"), vec!["Groceries"]);
+    
+    // Title matches outrank content matches; malformed queries find nothing
+    let ranked = search_engine.search("meeting OR title:standup", 10);
+    assert_eq!(ranked.last().map(|r| r.title.as_str()), Some("Groceries"));
+    assert!(search_engine.search("(tag:work", 10).is_empty());
+    
+    println!("✅ Search query language tests passed");
+}
+
+#[test]
+fn test_date_search() {
+    let dated = |title: &str, created: (i32, u32, u32, u32), modified: (i32, u32, u32, u32)| {
+        let mut note = Note::new(title.to_string());
+        note.update_content(format!("Notes about {}", title));
+        note.created_at = Utc.with_ymd_and_hms(created.0, created.1, created.2, created.3, 0, 0).unwrap();
+        note.modified_at = Utc.with_ymd_and_hms(modified.0, modified.1, modified.2, modified.3, 0, 0).unwrap();
+        note
+    };
+    // Timestamps are UTC; Tokyo (+09:00) is already on the next day for late ones
+    let notes = vec![
+        dated("Late night", (2026, 3, 17, 16), (2026, 3, 17, 16)), // 2026-03-18 01:00 in Tokyo
+        dated("Last week", (2026, 3, 10, 12), (2026, 3, 10, 12)),
+        dated("February", (2026, 2, 20, 8), (2026, 2, 28, 8)),
+        dated("New year", (2025, 12, 31, 20), (2025, 12, 31, 20)), // 2026-01-01 05:00 in Tokyo
+    ];
+    let mut search_engine = SearchEngine::new();
+    search_engine.update_index(&notes);
+    
+    // Wednesday 2026-03-18, 10:00 in Tokyo
+    let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+    let now = tokyo.with_ymd_and_hms(2026, 3, 18, 10, 0, 0).unwrap();
+    let utc_now = now.with_timezone(&Utc);
+    let titles = |query: &str, now: &chrono::DateTime<FixedOffset>| -> Vec<String> {
+        let query = SearchQuery::parse(query).unwrap();
+        let mut titles: Vec<String> = search_engine.execute_at(&query, 10, now).into_iter().map(|r| r.title).collect();
+        titles.sort();
+        titles
+    };
+    let utc = |query: &str| titles(query, &utc_now.with_timezone(&FixedOffset::east_opt(0).unwrap()));
+    
+    // Relative ranges count from today in the given time zone
+    assert_eq!(titles("modified:today", &now), vec!["Late night"]);
+    assert_eq!(utc("modified:today"), Vec::<String>::new());
+    assert_eq!(utc("modified:yesterday"), vec!["Late night"]);
+    assert_eq!(titles("modified:last-7-days", &now), vec!["Late night"]);
+    assert_eq!(titles("modified:last-2-weeks", &now), vec!["Last week", "Late night"]);
+    assert_eq!(titles("modified:last-week", &now), vec!["Last week"]);
+    assert_eq!(titles("modified:this-week", &now), vec!["Late night"]);
+    assert_eq!(titles("created:this-month", &now), vec!["Last week", "Late night"]);
+    assert_eq!(titles("created:last-month", &now), vec!["February"]);
+    assert_eq!(titles("created:this-year", &now), vec!["February", "Last week", "Late night", "New year"]);
+    assert_eq!(titles("created:last-year", &now), Vec::<String>::new());
+    assert_eq!(utc("created:last-year"), vec!["New year"]);
+    assert_eq!(titles("modified:last-month..yesterday", &now), vec!["February", "Last week"]);
+    
+    // Absolute dates and open ranges use local days too
+    assert_eq!(titles("modified:>2026-03-10", &now), vec!["Late night"]);
+    assert_eq!(titles("modified:>=2026-03-10", &now), vec!["Last week", "Late night"]);
+    assert_eq!(titles("modified:<2026-02", &now), vec!["New year"]);
+    assert_eq!(titles("created:2026-01-01", &now), vec!["New year"]);
+    assert_eq!(utc("created:2026-01-01"), Vec::<String>::new());
+    assert_eq!(titles("created:..2026-02", &now), vec!["February", "New year"]);
+    assert_eq!(titles("modified:>last-week", &now), vec!["Late night"]);
+    
+    assert!(SearchQuery::parse("modified:last-0-days").is_err());
+    assert!(SearchQuery::parse("modified:last-fortnight").is_err());
+    assert!(SearchQuery::parse("created:THIS-MONTH").is_ok());
+    assert!(SearchQuery::parse("modified:>today..tomorrow").is_err());
+    
+    // Counts reaching past the calendar are an error rather than a panic
+    for query in ["modified:last-100000000-days", "modified:last-4000000000-weeks", "created:last-4294967295-months", "created:last-400000000-years"] {
+        let error = QueryPlan::compile_at(&SearchQuery::parse(query).unwrap(), &now).unwrap_err();
+        assert_eq!(error.message, "Date out of range");
+    }
+    assert!(titles("modified:last-100000000-days", &now).is_empty());
+    
+    // Structured filters apply their date bounds to the last modification
+    let filter = SearchFilter {
+        date_from: Some(Utc.with_ymd_and_hms(2026, 2, 1, 0, 0, 0).unwrap()),
+        date_to: Some(Utc.with_ymd_and_hms(2026, 3, 11, 0, 0, 0).unwrap()),
+        ..SearchFilter::default()
+    };
+    let mut filtered: Vec<String> = search_engine.advanced_search(&filter, 10).into_iter().map(|r| r.title).collect();
+    filtered.sort();
+    assert_eq!(filtered, vec!["February", "Last week"]);
+    
+    println!("✅ Date search tests passed");
+}
+
+#[test]
+fn test_bm25_ranking() {
+    let note = |title: &str, content: &str, tags: &[&str]| {
+        let mut note = Note::new(title.to_string());
+        note.update_content(content.to_string());
+        for tag in tags {
+            note.add_tag(tag.to_string());
+        }
+        note
+    };
+    let in_title = note("Garden planning", "Which seeds to order this spring", &[]);
+    let in_heading = note("Spring list", "# Garden\nTomatoes, beans and peas", &[]);
+    let in_body = note("Weekend", "Spent the weekend in the garden with friends and family, then cooked dinner for everyone", &[]);
+    let in_tags = note("Compost", "Turn the pile every two weeks", &["garden"]);
+    let short_body = note("Chores", "Water the garden", &[]);
+    let unrelated = note("Database notes", "Indexes speed up queries", &["work"]);
+    
+    let mut search_engine = SearchEngine::new();
+    search_engine.update_index(&[in_title.clone(), in_heading.clone(), in_body.clone(), in_tags.clone(), short_body.clone(), unrelated.clone()]);
+    
+    // One ranked hit per note; title beats heading and tags, which beat the body
+    let results = search_engine.search("garden", 10);
+    let titles: Vec<&str> = results.iter().map(|r| r.title.as_str()).collect();
+    assert_eq!(titles.len(), 5);
+    assert_eq!(titles[0], "Garden planning");
+    assert!(titles.iter().position(|t| *t == "Spring list").unwrap() < titles.iter().position(|t| *t == "Chores").unwrap());
+    assert!(titles.iter().position(|t| *t == "Compost").unwrap() < titles.iter().position(|t| *t == "Chores").unwrap());
+    // The same single mention counts for more in a shorter note
+    assert!(titles.iter().position(|t| *t == "Chores").unwrap() < titles.iter().position(|t| *t == "Weekend").unwrap());
+    assert!(results.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    
+    // Spans point at every matched word, in the field it was found in
+    let title_hit = &results[0];
+    assert_eq!(title_hit.match_type, MatchType::TitleExact);
+    assert_eq!(title_hit.title_highlights, vec![0..6]);
+    let span = title_hit.matches[0];
+    assert_eq!((span.field, &in_title.title[span.start..span.end]), (MatchField::Title, "Garden"));
+    let heading_hit = results.iter().find(|r| r.title == "Spring list").unwrap();
+    let span = heading_hit.matches[0];
+    assert_eq!((span.field, &in_heading.content[span.start..span.end]), (MatchField::Heading, "Garden"));
+    assert_eq!(heading_hit.match_type, MatchType::ContentExact);
+    let tag_hit = results.iter().find(|r| r.title == "Compost").unwrap();
+    assert_eq!(tag_hit.match_type, MatchType::Tag);
+    assert_eq!(tag_hit.matches[0].field, MatchField::Tag);
+    
+    // Words typed so far match as prefixes, and typos still find something
+    assert_eq!(search_engine.search("gard", 10).len(), 5);
+    let typo = search_engine.search("databse", 10);
+    assert_eq!(typo.len(), 1);
+    assert_eq!(typo[0].match_type, MatchType::TitleFuzzy);
+    
+    // Query language terms are ranked the same way and carry their spans
+    let combined = search_engine.search("garden spring", 10);
+    assert_eq!(combined.iter().map(|r| r.title.as_str()).collect::<Vec<_>>(), vec!["Spring list", "Garden planning"]);
+    assert_eq!(combined[0].matches.len(), 2);
+    
+    // Updating or removing one note leaves the rest of the index alone
+    let mut edited = short_body.clone();
+    edited.update_content("Water the lawn".to_string());
+    search_engine.update_note(&edited);
+    assert_eq!(search_engine.search("garden", 10).len(), 4);
+    assert_eq!(search_engine.search("lawn", 10)[0].title, "Chores");
+    
+    search_engine.remove_note(&in_title.id);
+    let results = search_engine.search("garden", 10);
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|r| r.note_id != in_title.id));
+    assert!(search_engine.search("seeds", 10).is_empty());
+    
+    let mut trashed = in_tags.clone();
+    trashed.is_deleted = true;
+    search_engine.update_note(&trashed);
+    assert_eq!(search_engine.search("garden", 10).len(), 2);
+    
+    println!("✅ BM25 ranking tests passed");
+}
+
+#[test]
+fn test_persistent_search_index() {
+    let note = |title: &str, content: &str| {
+        let mut note = Note::new(title.to_string());
+        note.update_content(content.to_string());
+        note
+    };
+    let mut notes = vec![
+        note("Harbour walk", "Boats and gulls along the quay"),
+        note("Recipes", "Lentil soup with cumin"),
+        note("Reading list", "Novels about the sea"),
+        note("Old idea", "Something about lighthouses"),
+    ];
+    let path = std::env::temp_dir().join(format!("edison-search-{}.index", notes[0].id));
+    
+    let mut first_run = SearchEngine::new();
+    first_run.update_index(&notes);
+    first_run.save_index(&path).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+    assert!(saved.starts_with("edison-search-index "));
+    
+    // Between launches: one note edited, one trashed, one added
+    notes[1].update_content("Lentil soup with smoked paprika".to_string());
+    notes[3].is_deleted = true;
+    notes.push(note("Garden", "Paprika peppers in the greenhouse"));
+    
+    // Nothing is read until the index is needed, and folders don't force it
+    let mut next_run = SearchEngine::new();
+    next_run.load_index(&path, notes.clone().into());
+    next_run.set_folders(Vec::<&Folder>::new());
+    assert!(!next_run.is_loaded());
+    assert!(next_run.load_report().is_none());
+    
+    let results = next_run.search("paprika", 10);
+    assert!(next_run.is_loaded());
+    assert_eq!(next_run.load_report(), Some(&IndexLoadReport { from_disk: true, reused: 2, reindexed: 2, dropped: 1 }));
+    let mut titles: Vec<&str> = results.iter().map(|r| r.title.as_str()).collect();
+    titles.sort();
+    assert_eq!(titles, vec!["Garden", "Recipes"]);
+    assert!(next_run.search("cumin", 10).is_empty());
+    assert!(next_run.search("lighthouses", 10).is_empty());
+    assert_eq!(next_run.search("gulls", 10)[0].title, "Harbour walk");
+    
+    // Refreshing an index that is already loaded updates it in place
+    notes[0].update_content("Boats and herons along the quay".to_string());
+    next_run.load_index(&path, notes.clone().into());
+    assert_eq!(next_run.search("herons", 10).len(), 1);
+    assert!(next_run.search("gulls", 10).is_empty());
+    
+    // A damaged file, another index version or no file at all means a full rebuild
+    let (header, body) = saved.split_once('\n').unwrap();
+    let damaged = [
+        format!("{}\n{}", header, body.replacen("quay", "quai", 1)),
+        format!("edison-search-index 0 {}\n{}", header.rsplit(' ').next().unwrap(), body),
+        "not an index".to_string(),
+    ];
+    for contents in damaged {
+        std::fs::write(&path, contents).unwrap();
+        let mut engine = SearchEngine::new();
+        engine.load_index(&path, notes.clone().into());
+        assert_eq!(engine.search("paprika", 10).len(), 2);
+        assert_eq!(engine.load_report(), Some(&IndexLoadReport { from_disk: false, reused: 0, reindexed: 4, dropped: 0 }));
+    }
+    std::fs::remove_file(&path).unwrap();
+    let mut engine = SearchEngine::new();
+    engine.load_index(&path, notes.clone().into());
+    assert_eq!(engine.search("greenhouse", 10).len(), 1);
+    assert!(!engine.load_report().unwrap().from_disk);
+    
+    // An index that was never needed has nothing new to write
+    SearchEngine::new().save_index(&path).unwrap();
+    assert!(!path.exists());
+    
+    println!("✅ Persistent search index tests passed");
+}
+
+#[test]
+fn test_match_spans() {
+    let note = |title: &str, content: &str| {
+        let mut note = Note::new(title.to_string());
+        note.update_content(content.to_string());
+        note
+    };
+    let trip = note("Trip notes", "# Packing\nBring the map\nAlso a café guide — map of Köln\n");
+    let cafe = note("Café Köln", "Opening hours");
+    let long = note("Long", &format!("{} needle {}", "é".repeat(60), "ü".repeat(60)));
+    
+    let mut search_engine = SearchEngine::new();
+    search_engine.update_index(&[trip.clone(), cafe.clone(), long.clone()]);
+    
+    // Every match carries its byte range and its 1-based line and character column
+    let results = search_engine.search("map", 10);
+    assert_eq!(results.len(), 1);
+    let spans: Vec<(MatchField, usize, usize)> = results[0].matches.iter().map(|s| (s.field, s.line, s.column)).collect();
+    assert_eq!(spans, vec![(MatchField::Body, 2, 11), (MatchField::Body, 3, 21)]);
+    assert!(results[0].matches.iter().all(|s| &trip.content[s.range()] == "map"));
+    assert_eq!(results[0].first_content_match().unwrap().start, trip.content.find("map").unwrap());
+    
+    // Snippet highlights are byte ranges into the snippet text
+    let snippet = &results[0].snippet;
+    assert_eq!(snippet.highlights.len(), 2);
+    assert!(snippet.highlights.iter().all(|h| &snippet.text[h.clone()] == "map"));
+    assert!(results[0].title_highlights.is_empty());
+    
+    let heading = search_engine.search("packing", 10);
+    let span = heading[0].matches[0];
+    assert_eq!((span.field, span.line, span.column, span.start), (MatchField::Heading, 1, 3, 2));
+    
+    // Regex matches are located the same way
+    let regex = search_engine.search("/K.ln/", 10);
+    let body = regex.iter().find(|r| r.title == "Trip notes").unwrap();
+    assert_eq!((body.matches[0].line, body.matches[0].column), (3, 28));
+    let titled = regex.iter().find(|r| r.title == "Café Köln").unwrap();
+    assert_eq!(titled.title_highlights, vec![6..11]);
+    assert_eq!(&titled.title[titled.title_highlights[0].clone()], "Köln");
+    
+    // One result per note, whether the pattern matched the title, the content or both
+    let regex = search_engine.search_regex("[Kk]öln|map", 10);
+    assert_eq!(regex.len(), 2);
+    assert_eq!(regex.iter().find(|r| r.title == "Trip notes").unwrap().matches.len(), 3);
+    
+    // Snippets of long notes are cut on character boundaries around the match
+    let results = search_engine.search("needle", 10);
+    let Snippet { text, highlights } = &results[0].snippet;
+    assert!(text.starts_with("...") && text.ends_with("..."));
+    assert!(text.len() <= 150);
+    assert_eq!(&text[highlights[0].clone()], "needle");
+    assert_eq!(results[0].matches[0].column, 62);
+    
+    // Notes matched without a located word still highlight the query text
+    let filtered = search_engine.advanced_search(&SearchFilter { query: "HOURS".to_string(), ..Default::default() }, 10);
+    assert_eq!(filtered.len(), 1);
+    assert_eq!(&filtered[0].snippet.text[filtered[0].snippet.highlights[0].clone()], "hours");
+    
+    println!("✅ Match span tests passed");
+}
+
+#[test]
+fn test_tokenizer_pipeline() {
+    // Words are segmented, folded and stemmed, and stop words are dropped
+    let tokenizer = Tokenizer::new(TokenizerConfig::default());
+    let tokens = tokenizer.tokenize("The Meetings at the Café");
+    assert_eq!(tokens.iter().map(|t| t.term.as_str()).collect::<Vec<_>>(), vec!["meet", "cafe"]);
+    assert_eq!(tokens[0].surface, "meetings");
+    assert_eq!((tokens[1].start, tokens[1].end), (20, 25));
+    
+    // Runs of CJK characters become overlapping bigrams
+    let terms: Vec<String> = tokenizer.tokenize("東京都に住む, tower").into_iter().map(|t| t.term).collect();
+    assert_eq!(terms, vec!["東京", "京都", "都に", "に住", "住む", "tower"]);
+    
+    // Every stage can be configured or turned off
+    let plain = Tokenizer::new(TokenizerConfig::plain());
+    let terms: Vec<String> = plain.tokenize("The Meetings at the Café").into_iter().map(|t| t.term).collect();
+    assert_eq!(terms, vec!["the", "meetings", "at", "the", "café"]);
+    let french = Tokenizer::new(TokenizerConfig {
+        language: Some(Language::French),
+        stop_words: vec!["les".to_string()],
+        cjk_ngram: 3,
+        ..TokenizerConfig::default()
+    });
+    let terms: Vec<String> = french.tokenize("les chanteuses 東京都").into_iter().map(|t| t.term).collect();
+    assert_eq!(terms, vec!["chanteux", "東京都"]);
+    
+    let note = |title: &str, content: &str| {
+        let mut note = Note::new(title.to_string());
+        note.update_content(content.to_string());
+        note
+    };
+    let notes = vec![
+        note("Weekly meeting", "Agenda for the team"),
+        note("Café list", "Best coffee in town"),
+        note("旅行", "東京都に住む友達に会う"),
+    ];
+    let mut search_engine = SearchEngine::new();
+    search_engine.update_index(&notes);
+    
+    let results = search_engine.search("meetings", 10);
+    assert_eq!(results.len(), 1);
+    assert_eq!(&results[0].title[results[0].title_highlights[0].clone()], "meeting");
+    assert_eq!(search_engine.search("meetin", 10).len(), 1);
+    assert_eq!(search_engine.search("cafe", 10)[0].title, "Café list");
+    assert_eq!(search_engine.search("東京", 10)[0].title, "旅行");
+    assert_eq!(search_engine.search("友達", 10)[0].title, "旅行");
+    assert!(search_engine.search("the", 10).is_empty());
+    
+    // Changing the settings re-indexes, and an index saved with other settings is rebuilt
+    let path = std::env::temp_dir().join(format!("edison-tokenizer-{}.index", notes[0].id));
+    search_engine.save_index(&path).unwrap();
+    search_engine.set_tokenizer(TokenizerConfig::plain());
+    assert!(search_engine.search("meetings", 10).is_empty());
+    assert_eq!(search_engine.search("the", 10).len(), 1);
+    
+    let mut reloaded = SearchEngine::new();
+    reloaded.set_tokenizer(TokenizerConfig::plain());
+    reloaded.load_index(&path, notes.clone().into());
+    assert!(reloaded.search("meetings", 10).is_empty());
+    assert!(!reloaded.load_report().unwrap().from_disk);
+    let _ = std::fs::remove_file(&path);
+    
+    println!("✅ Tokenizer pipeline tests passed");
+}
+
+#[test]
+fn test_semantic_search() {
+    // The local model gives related texts close vectors, whatever their length
+    let model = HashedEmbedding::default();
+    let garden = model.embed("Planting tomatoes in the vegetable garden");
+    assert_eq!(garden.len(), model.dimensions());
+    assert!((cosine_similarity(&garden, &garden) - 1.0).abs() < 1e-5);
+    assert!(cosine_similarity(&garden, &model.embed("tomato plants for gardens")) > cosine_similarity(&garden, &model.embed("quarterly revenue forecast")));
+    
+    let note = |title: &str, content: &str| {
+        let mut note = Note::new(title.to_string());
+        note.update_content(content.to_string());
+        note
+    };
+    let mut notes = vec![
+        note("Tomato growing", "Planting tomatoes in the garden needs sun, compost and regular watering. Tomato seedlings go out in May."),
+        note("Vegetable garden", "Growing vegetables: plant the seedlings in compost and water the garden beds every morning."),
+        note("Quarterly budget", "Revenue forecast, expenses and the quarterly budget spreadsheet for finance."),
+        note("Finance meeting", "Reviewed expenses and revenue with the finance team; the budget forecast was approved."),
+    ];
+    let mut search_engine = SearchEngine::new();
+    search_engine.update_index(&notes);
+    
+    // Similar notes, never the note itself
+    let related = search_engine.related_notes(&notes[0].id, 10);
+    assert_eq!(related[0].title, "Vegetable garden");
+    assert!(related.iter().all(|r| r.note_id != notes[0].id && r.match_type == MatchType::Semantic));
+    assert!(related.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    assert_eq!(search_engine.related_notes(&notes[2].id, 10)[0].title, "Finance meeting");
+    
+    // Natural-language queries are ranked by meaning
+    let results = search_engine.semantic_search("when should I water my tomato plants", 10);
+    assert_eq!(results[0].title, "Tomato growing");
+    let results = search_engine.semantic_search("approving the budget with the finance team", 2);
+    assert!(results.iter().all(|r| r.title == "Finance meeting" || r.title == "Quarterly budget"));
+    assert!(search_engine.semantic_search("   ", 10).is_empty());
+    
+    // Saved notes are re-embedded, trashed ones dropped
+    notes[1].update_content("Expenses and revenue for the finance budget review".to_string());
+    search_engine.update_note(&notes[1]);
+    notes[3].is_deleted = true;
+    search_engine.update_note(&notes[3]);
+    let related = search_engine.related_notes(&notes[2].id, 10);
+    assert_eq!(related[0].title, "Vegetable garden");
+    assert!(related.iter().all(|r| r.title != "Finance meeting"));
+    
+    // Vectors are saved with their model and reused on the next launch
+    let path = std::env::temp_dir().join(format!("edison-embeddings-{}.json", notes[0].id));
+    search_engine.save_vectors(&path).unwrap();
+    assert!(std::fs::read_to_string(&path).unwrap().contains(model.id()));
+    let mut next_run = SearchEngine::new();
+    next_run.load_vectors(&path, notes.clone().into());
+    next_run.update_index(&notes);
+    assert_eq!(next_run.related_notes(&notes[2].id, 10)[0].title, "Vegetable garden");
+    assert!(!next_run.is_embedding());
+    
+    // Any model can be plugged in; vectors from another model aren't reused
+    struct Keywords;
+    impl EmbeddingModel for Keywords {
+        fn id(&self) -> &str {
+            "keywords"
+        }
+        fn dimensions(&self) -> usize {
+            2
+        }
+        fn embed(&self, text: &str) -> Vec<f32> {
+            let text = text.to_lowercase();
+            vec![text.matches("garden").count() as f32, text.matches("budget").count() as f32]
+        }
+    }
+    let mut custom = SearchEngine::new();
+    custom.set_embedding_model(Box::new(Keywords));
+    custom.load_vectors(&path, notes.clone().into());
+    custom.update_index(&notes);
+    let related = custom.related_notes(&notes[2].id, 10);
+    assert_eq!(related.iter().map(|r| r.title.as_str()).collect::<Vec<_>>(), vec!["Vegetable garden"]);
+    assert!((related[0].score - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-5);
+    
+    // Edits made while vectors load neither wait for the load nor get lost to it
+    struct SlowKeywords;
+    impl EmbeddingModel for SlowKeywords {
+        fn id(&self) -> &str {
+            "slow-keywords"
+        }
+        fn dimensions(&self) -> usize {
+            2
+        }
+        fn embed(&self, text: &str) -> Vec<f32> {
+            if std::thread::current().name().is_none() {
+                std::thread::sleep(std::time::Duration::from_millis(100)); // Only the background load is slow
+            }
+            Keywords.embed(text)
+        }
+    }
+    let mut loading = SearchEngine::new();
+    loading.set_embedding_model(Box::new(SlowKeywords));
+    loading.load_vectors(&path, notes.clone().into());
+    notes[0].update_content("A budget for garden tools".to_string());
+    loading.update_note(&notes[0]);
+    assert!(loading.is_embedding());
+    let related = loading.related_notes(&notes[2].id, 10);
+    assert!(!loading.is_embedding());
+    assert!(related.iter().any(|r| r.title == "Tomato growing"));
+    let _ = std::fs::remove_file(&path);
+    
+    println!("✅ Semantic search tests passed");
+}
+
+#[test]
+fn test_faceted_search() {
+    let mut work = Folder::new("Work".to_string(), None);
+    work.id = "folder-work".to_string();
+    let note = |title: &str, content: &str, tag: Option<&str>, folder: Option<&Folder>, created: (i32, u32, u32)| {
+        let mut note = Note::new(title.to_string());
+        note.update_content(content.to_string());
+        if let Some(tag) = tag {
+            note.add_tag(tag.to_string());
+        }
+        note.folder_id = folder.map(|folder| folder.id.clone());
+        note.created_at = Utc.with_ymd_and_hms(created.0, created.1, created.2, 12, 0, 0).unwrap();
+        note.modified_at = note.created_at;
+        note
+    };
+    let notes = [
+        note("Standup meeting", "---\nstatus: active\npriority: 3\n---\nStatus report", Some("work"), Some(&work), (2024, 3, 10)),
+        note("Retro meeting", "---\nstatus: draft\npriority: 1\ntags: [work/team]\n---\nWhat went well", None, None, (2024, 6, 14)),
+        note("Meeting plan", "---\nstatus: active\n---\nAgenda", Some("Work"), Some(&work), (2024, 3, 20)),
+        note("Groceries", "Milk and eggs for the meeting snacks", Some("home"), None, (2023, 12, 15)),
+        note("Recipes", "Pancakes", Some("home"), None, (2023, 12, 16)),
+    ];
+    let mut search_engine = SearchEngine::new();
+    search_engine.update_index(&notes);
+    search_engine.set_folders([&work]);
+    let faceted_search = |query: &str, selected: &[Facet], max_results: usize| {
+        search_engine.facet_results(search_engine.search(query, 10), selected, max_results)
+    };
+    
+    // Counts cover every matching note, busiest values first; tags differing
+    // only in case count as one
+    let faceted = faceted_search("meeting", &[], 10);
+    assert_eq!(faceted.total, 4);
+    let counts = |group: &[FacetCount]| group.iter().map(|c| (c.label.clone(), c.count)).collect::<Vec<_>>();
+    let owned = |pairs: &[(&str, usize)]| pairs.iter().map(|(label, count)| (label.to_string(), *count)).collect::<Vec<_>>();
+    assert_eq!(counts(&faceted.facets.tags), owned(&[("#work", 2), ("#home", 1), ("#work/team", 1)]));
+    assert_eq!(counts(&faceted.facets.folders), owned(&[("No folder", 2), ("Work", 2)]));
+    assert_eq!(counts(&faceted.facets.months), owned(&[("June 2024", 1), ("March 2024", 2), ("December 2023", 1)]));
+    assert_eq!(counts(&faceted.facets.properties), owned(&[("status: active", 2), ("priority: 1", 1), ("priority: 3", 1), ("status: draft", 1)]));
+    assert_eq!(counts(&faceted.facets.match_types), owned(&[("Title", 3), ("Content", 1)]));
+    assert_eq!(faceted.facets.folders[1].facet, Facet::Folder(Some(work.id.clone())));
+    
+    // Selected facets narrow the results, and the counts with them
+    let work_tag = Facet::Tag("work".to_string());
+    let narrowed = faceted_search("meeting", std::slice::from_ref(&work_tag), 10);
+    let mut titles: Vec<&str> = narrowed.results.iter().map(|r| r.title.as_str()).collect();
+    titles.sort();
+    assert_eq!(titles, vec!["Meeting plan", "Standup meeting"]);
+    assert_eq!(counts(&narrowed.facets.months), owned(&[("March 2024", 2)]));
+    assert_eq!(faceted_search("meeting", &[Facet::Month(2023, 12)], 10).results[0].title, "Groceries");
+    assert_eq!(faceted_search("meeting", &[Facet::MatchType(MatchType::ContentExact)], 10).total, 1);
+    assert_eq!(faceted_search("meeting", &[work_tag, Facet::Folder(None)], 10).total, 0);
+    
+    // `max_results` limits the results, not the counts
+    let limited = faceted_search("meeting", &[], 1);
+    assert_eq!((limited.results.len(), limited.total), (1, 4));
+    assert_eq!(limited.facets, faceted.facets);
+    
+    // Filters are faceted the same way
+    let filter = SearchFilter { query: "meeting".to_string(), ..SearchFilter::default() };
+    let active = search_engine.facet_results(search_engine.advanced_search(&filter, 10), &[Facet::Property("status".to_string(), "active".to_string())], 10);
+    assert_eq!(active.total, 2);
+    
+    println!("✅ Faceted search tests passed");
+}
+
+#[test]
+fn test_background_search() {
+    let notes: Vec<Note> = (0..1800)
+        .map(|i| {
+            let mut note = Note::new(format!("Project note {}", i));
+            note.update_content(if i % 3 == 0 { "Budget review".to_string() } else { "Weekly sync".to_string() });
+            if i % 2 == 0 {
+                note.add_tag("even".to_string());
+            }
+            note
+        })
+        .collect();
+    let mut search_engine = SearchEngine::new();
+    search_engine.update_index(&notes);
+    
+    // Results stream in batch by batch and end up the same as a plain search;
+    // only the 600 notes mentioning the budget are checked at all
+    let mut updates = 0;
+    let mut totals = Vec::new();
+    let faceted = search_engine
+        .search_progressive("project budget", &[], 50, || false, |partial, total| {
+            assert!(partial.len() <= 50);
+            totals.push(total);
+            updates += 1;
+        })
+        .unwrap();
+    assert!(updates >= 2);
+    assert!(totals.windows(2).all(|pair| pair[0] <= pair[1]));
+    let ids = |results: &[edison_note::features::SearchResult]| results.iter().map(|r| r.note_id.clone()).collect::<Vec<_>>();
+    assert_eq!(ids(&faceted.results), ids(&search_engine.search("project budget", 50)));
+    assert_eq!(faceted.results.len(), 50);
+    
+    // Only the top results are kept, but every match is counted
+    assert_eq!(faceted.total, 600);
+    assert_eq!(faceted.facets.match_types.iter().map(|c| c.count).sum::<usize>(), 600);
+    let even = Facet::Tag("even".to_string());
+    assert_eq!(faceted.facets.tags.iter().find(|c| c.facet == even).map(|c| c.count), Some(300));
+    let narrowed = search_engine.search_progressive("project budget", &[even], 5, || false, |_, _| {}).unwrap();
+    assert_eq!((narrowed.results.len(), narrowed.total), (5, 300));
+    
+    // A cancelled search gives up without results
+    assert!(search_engine.search_progressive("project budget", &[], 50, || true, |_, _| {}).is_none());
+    
+    // Queries typed in quick succession only run the last one
+    let engine = Arc::new(RwLock::new(search_engine));
+    let mut worker = SearchWorker::spawn(engine.clone(), std::time::Duration::from_millis(50));
+    assert!(!worker.is_busy());
+    worker.submit("proj", &[], 10);
+    worker.submit("project", &[], 10);
+    let generation = worker.submit("project budget", &[], 20);
+    assert!(worker.is_busy());
+    
+    // A writer interrupts the running search instead of waiting it out, and
+    // the search starts over once the write is done
+    std::thread::sleep(std::time::Duration::from_millis(60));
+    drop(worker.write());
+    
+    let started = std::time::Instant::now();
+    let mut last = None;
+    while worker.is_busy() && started.elapsed() < std::time::Duration::from_secs(10) {
+        if let Some(update) = worker.poll() {
+            assert_eq!(update.generation, generation);
+            assert_eq!(update.query, "project budget");
+            last = Some(update);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    let last = last.expect("search worker finished");
+    assert!(last.complete);
+    assert_eq!((last.results.len(), last.total), (20, 600));
+    assert_eq!(last.facets.match_types.iter().map(|c| c.count).sum::<usize>(), 600);
+    
+    // Cancelling drops the results of the running query
+    worker.submit("weekly", &[], 10);
+    worker.cancel();
+    assert!(!worker.is_busy());
+    std::thread::sleep(std::time::Duration::from_millis(150));
+    assert!(worker.poll().is_none());
+    
+    println!("✅ Background search tests passed");
+}
+
+#[test]
+fn test_history_search() {
+    let mut plan = Note::new("Launch plan".to_string());
+    plan.update_content("Ship in May\nBudget approved".to_string());
+    let mut old_draft = Note::new("Old draft".to_string());
+    old_draft.update_content("Pricing tiers for the launch".to_string());
+    old_draft.move_to_trash();
+    let notes = vec![plan.clone(), old_draft.clone()];
+    
+    let version = |number: i32, content: &str, days_ago: i64| NoteVersion {
+        id: format!("{}-v{}", plan.id, number),
+        note_id: plan.id.clone(),
+        content: content.to_string(),
+        timestamp: Utc::now() - Duration::days(days_ago),
+        version_number: number,
+    };
+    let versions = vec![
+        version(1, "Ship in April\nPricing tiers: free and pro", 30),
+        version(2, "Ship in May\nPricing tiers: free, pro and team", 20),
+        version(3, "Ship in May\nBudget approved", 1), // Same as now
+    ];
+    let mut search_engine = SearchEngine::new();
+    search_engine.update_index(&notes);
+    assert!(search_engine.search("pricing", 10).is_empty());
+    
+    // Removed text is found in the newest version that still had it
+    let versions_only = HistoryScope { versions: true, trash: false };
+    let found = search_engine.search_history("pricing", versions_only, &notes, &versions, 10);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].result.note_id, plan.id);
+    assert_eq!(found[0].result.title, "Launch plan");
+    assert!(found[0].result.snippet.text.contains("team"));
+    match &found[0].source {
+        HistorySource::Version { version_id, version_number, .. } => {
+            assert_eq!(version_id, &versions[1].id);
+            assert_eq!(*version_number, 2);
+        }
+        other => panic!("expected a version, got {:?}", other),
+    }
+    
+    // Text the note still has isn't reported again from its versions
+    assert!(search_engine.search_history("budget", versions_only, &notes, &versions, 10).is_empty());
+    assert!(search_engine.search_history("launch", versions_only, &notes, &versions, 10).is_empty());
+    assert_eq!(search_engine.search_history("april", versions_only, &notes, &versions, 10).len(), 1);
+    
+    // Version 2 still has "Ship" on a line the note kept, so only the
+    // removed "Ship in April" of version 1 matches
+    let found = search_engine.search_history("ship", versions_only, &notes, &versions, 10);
+    assert_eq!(found.len(), 1);
+    assert!(matches!(found[0].source, HistorySource::Version { version_number: 1, .. }));
+    assert!(found[0].result.snippet.text.contains("April"));
+    
+    // Trashed notes come back only when the trash is searched
+    let everything = HistoryScope { versions: true, trash: true };
+    let found = search_engine.search_history("pricing", everything, &notes, &versions, 10);
+    assert_eq!(found.len(), 2);
+    assert!(found.iter().any(|f| f.source == HistorySource::Trash && f.result.note_id == old_draft.id));
+    assert!(search_engine.search_history("pricing", HistoryScope::default(), &notes, &versions, 10).is_empty());
+    
+    // Diffs keep shared lines and show what changed since the version
+    let diff = diff_lines(&versions[1].content, &plan.content);
+    assert_eq!(diff, vec![
+        DiffLine::Same("Ship in May".to_string()),
+        DiffLine::Removed("Pricing tiers: free, pro and team".to_string()),
+        DiffLine::Added("Budget approved".to_string()),
+    ]);
+    let diff = diff_lines("a\nb\nc\nd", "a\nc\nx\nd");
+    assert_eq!(diff, vec![
+        DiffLine::Same("a".to_string()),
+        DiffLine::Removed("b".to_string()),
+        DiffLine::Same("c".to_string()),
+        DiffLine::Added("x".to_string()),
+        DiffLine::Same("d".to_string()),
+    ]);
+    assert!(diff_lines("same", "same").iter().all(|line| matches!(line, DiffLine::Same(_))));
+    
+    // Shortest edit scripts: ABCABBA to CBABAC keeps four lines and changes five
+    let diff = diff_lines("A\nB\nC\nA\nB\nB\nA", "C\nB\nA\nB\nA\nC");
+    let same = diff.iter().filter(|line| matches!(line, DiffLine::Same(_))).count();
+    assert_eq!((same, diff.len() - same), (4, 5));
+    let old: Vec<String> = diff.iter().filter_map(|line| match line {
+        DiffLine::Same(text) | DiffLine::Removed(text) => Some(text.clone()),
+        DiffLine::Added(_) => None,
+    }).collect();
+    let new: Vec<String> = diff.iter().filter_map(|line| match line {
+        DiffLine::Same(text) | DiffLine::Added(text) => Some(text.clone()),
+        DiffLine::Removed(_) => None,
+    }).collect();
+    assert_eq!((old.join("\n"), new.join("\n")), ("A\nB\nC\nA\nB\nB\nA".to_string(), "C\nB\nA\nB\nA\nC".to_string()));
+    
+    // Long texts with a few edits diff without a table of every line pair
+    let long: Vec<String> = (0..20000).map(|i| format!("line {}", i)).collect();
+    let mut edited = long.clone();
+    edited[5000] = "changed".to_string();
+    edited.remove(15000);
+    let diff = diff_lines(&long.join("\n"), &edited.join("\n"));
+    assert_eq!(diff.iter().filter(|line| !matches!(line, DiffLine::Same(_))).count(), 3);
+    
+    println!("✅ History search tests passed");
+}
+
+#[tokio::test]
+async fn test_history_after_restart() {
+    let db = Arc::new(Mutex::new(Database::new().await.unwrap()));
+    let mut manager = NoteManager::new(db.clone());
+    let note_id = manager.create_new_note();
+    // Words of letters only, so no other note in the database shares them
+    let word = |skip: usize| -> String {
+        note_id.chars().filter(|c| c.is_ascii_hexdigit()).skip(skip).take(12).map(|c| (b'g' + c.to_digit(16).unwrap() as u8) as char).collect()
+    };
+    let (removed, kept) = (format!("gone{}", word(0)), format!("kept{}", word(12)));
+    
+    // Text that's edited away, then the note goes to the trash
+    manager.get_note_mut(&note_id).unwrap().update_content(format!("{}\n{}", removed, kept));
+    manager.save_note(&note_id).unwrap();
+    manager.get_note_mut(&note_id).unwrap().update_content(kept.clone());
+    manager.save_note(&note_id).unwrap();
+    manager.delete_note(&note_id).unwrap();
+    drop(manager);
+    
+    // After a restart the trashed note and its versions are still searchable
+    let mut manager = NoteManager::new(db);
+    assert!(manager.get_note(&note_id).is_none());
+    let everything = HistoryScope { versions: true, trash: true };
+    let found = manager.search_history(&kept, everything, 10);
+    assert!(found.iter().any(|f| f.source == HistorySource::Trash && f.result.note_id == note_id));
+    let found = manager.search_history(&removed, everything, 10);
+    let version_id = match found.iter().find(|f| f.result.note_id == note_id).map(|f| &f.source) {
+        Some(HistorySource::Version { version_id, .. }) => version_id.clone(),
+        other => panic!("expected a version, got {:?}", other),
+    };
+    
+    // Its versions can be compared and restored, which takes it out of the trash
+    let diff = manager.diff_version(&version_id).unwrap();
+    assert!(diff.contains(&DiffLine::Removed(removed.clone())));
+    manager.restore_version(&version_id).unwrap();
+    let note = manager.get_note(&note_id).unwrap();
+    assert!(!note.is_deleted);
+    assert!(note.content.contains(&removed));
+    
+    // The cached history index is rebuilt once notes change
+    assert!(manager.search_history(&removed, everything, 10).iter().all(|f| f.result.note_id != note_id));
+    
+    manager.delete_note(&note_id).unwrap();
+    
+    println!("✅ History after restart tests passed");
+}
+
+#[test]
+fn test_force_layout() {
+    let ids = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+    let edge = |from: &str, to: &str| (from.to_string(), to.to_string());
+    
+    let mut layout = ForceLayout::new();
+    layout.sync(&ids(&["a", "b", "c", "d"]), &[edge("a", "b"), edge("c", "d")]);
+    let mut steps = 0;
+    while layout.step() {
+        steps += 1;
+        assert!(steps < 5000, "layout never settled");
+    }
+    assert!(layout.is_settled());
+    
+    // Linked notes end up closer together than unlinked ones
+    let position = |layout: &ForceLayout, id: &str| layout.position(id).unwrap();
+    let linked = (position(&layout, "a") - position(&layout, "b")).length();
+    let unlinked = (position(&layout, "a") - position(&layout, "c")).length();
+    assert!(linked < unlinked);
+    
+    // Adding a note keeps the existing positions as the starting point
+    let before = position(&layout, "a");
+    layout.sync(&ids(&["a", "b", "c", "d", "e"]), &[edge("a", "b"), edge("c", "d"), edge("e", "a")]);
+    assert_eq!(layout.position("a"), Some(before));
+    assert!(layout.position("e").is_some());
+    
+    // Removed notes are forgotten
+    layout.sync(&ids(&["a", "b", "e"]), &[edge("a", "b"), edge("e", "a")]);
+    assert!(layout.position("c").is_none());
+    
+    // Dropped nodes are pinned and stay put while the rest moves
+    let dropped = position(&layout, "b") * 3.0;
+    layout.set_position("b", dropped);
+    assert!(layout.is_pinned("b"));
+    for _ in 0..200 {
+        layout.step();
+    }
+    assert_eq!(layout.position("b"), Some(dropped));
+    
+    layout.toggle_pin("b");
+    assert!(!layout.is_pinned("b"));
+    layout.set_position("a", position(&layout, "a"));
+    layout.unpin_all();
+    assert!(!layout.is_pinned("a"));
+    
+    println!("✅ Force layout tests passed");
+}
+
 #[test]
 fn test_markdown_processing() {
     let markdown_content = "# Title\n\nThis is **bold** and *italic* text.\n\n- List item 1\n- List item 2";
@@ -341,6 +2006,32 @@ async fn main() {
     test_ai_enhancement_requests();
     test_collaboration_manager().await;
     test_note_linking_patterns();
+    test_rename_link_rewriting();
+    test_backlink_index();
+    test_unlinked_mentions();
+    test_broken_link_detection();
+    test_link_positions();
+    test_link_graph_analytics();
+    test_graph_export();
+    test_relation_links();
+    test_task_index();
+    test_reminder_scheduling();
+    test_saved_searches();
+    test_query_blocks();
+    test_kanban_board();
+    test_canvas_json();
+    test_search_query_language();
+    test_date_search();
+    test_bm25_ranking();
+    test_persistent_search_index();
+    test_match_spans();
+    test_tokenizer_pipeline();
+    test_semantic_search();
+    test_faceted_search();
+    test_background_search();
+    test_history_search();
+    test_history_after_restart().await;
+    test_force_layout();
     test_markdown_processing();
     test_ui_state_management();
     test_performance_optimizations();